@@ -83,9 +83,11 @@ function broadcastStatus() {
   chrome.runtime.sendMessage({ type: "status", connected }).catch(() => {});
 }
 
-chrome.runtime.onMessage.addListener((msg, _sender, sendResponse) => {
+chrome.runtime.onMessage.addListener((msg, sender, sendResponse) => {
   if (msg.type === "get_status") {
     sendResponse({ connected });
+  } else if (msg.type === "console" && sender.tab) {
+    pushConsole(sender.tab.id, msg.entry);
   }
 });
 
@@ -93,8 +95,88 @@ chrome.runtime.onMessage.addListener((msg, _sender, sendResponse) => {
 // Command handler
 // ---------------------------------------------------------------------------
 
+// Actions that accept a `ref` from list_elements in place of a selector.
+const REF_ACTIONS = new Set([
+  "click",
+  "type",
+  "read",
+  "wait_for",
+  "fill",
+  "hover",
+  "select_option",
+  "check",
+  "press_key",
+  "scroll",
+  "upload",
+  "screenshot",
+]);
+
+// Actions that wait for their target to be actionable first.
+const ACTIONABLE_ACTIONS = new Set(["click", "type", "hover"]);
+
+// Actions that can run inside an iframe.
+const FRAME_ACTIONS = new Set([
+  "click",
+  "type",
+  "fill",
+  "hover",
+  "select_option",
+  "check",
+  "press_key",
+  "scroll",
+  "read",
+  "extract",
+  "list_elements",
+  "execute_js",
+  "wait_for",
+  "storage",
+  "upload",
+  "screenshot",
+]);
+
+// Actions that interact with the page: they may start requests and raise
+// errors. wait_for_request and console since_action look at what happened
+// since the latest one.
+const INTERACTIONS = new Set([
+  "navigate",
+  "click",
+  "type",
+  "fill",
+  "hover",
+  "select_option",
+  "check",
+  "press_key",
+  "scroll",
+  "execute_js",
+  "go_back",
+  "go_forward",
+  "reload",
+  "upload",
+]);
+
+// Actions that don't run script in the page, so they work while a dialog
+// blocks it.
+const PAGE_FREE_ACTIONS = new Set([
+  "get_tabs",
+  "switch_tab",
+  "new_tab",
+  "close_tab",
+  "dialog",
+  "console",
+  "wait_for_request",
+  "wait_for_download",
+  "cookies",
+]);
+
 async function handleCommand(cmd) {
-  const { id, action, tabId, params = {} } = cmd;
+  const { id, action, tabId } = cmd;
+  const params = cmd.params || {};
+  if (cmd.dialogPolicy) dialogPolicy = cmd.dialogPolicy;
+  if (INTERACTIONS.has(action)) {
+    networkMark = networkSeq;
+    consoleMark = consoleSeq;
+    downloadMark = downloadSeq;
+  }
   try {
     // Resolve target tab
     let tab;
@@ -108,31 +190,746 @@ async function handleCommand(cmd) {
       tab = activeTab;
     }
 
-    switch (action) {
-      case "get_tabs":
-        return await handleGetTabs(id);
-      case "navigate":
-        return await handleNavigate(id, tab, params);
-      case "click":
-        return await handleClick(id, tab, params);
-      case "type":
-        return await handleType(id, tab, params);
-      case "read":
-        return await handleRead(id, tab, params);
-      case "list_elements":
-        return await handleListElements(id, tab, params);
-      case "execute_js":
-        return await handleExecuteJs(id, tab, params);
-      case "wait_for":
-        return await handleWaitFor(id, tab, params);
-      default:
-        return { id, success: false, error: `Unknown action: ${action}` };
-    }
+    if (PAGE_FREE_ACTIONS.has(action))
+      return await runCommand(id, action, tab, tabId, params);
+    const open = tab && openDialogs.get(tab.id);
+    if (open)
+      return {
+        id,
+        success: false,
+        error: `A ${describeDialog(open)} is open — answer it with browser_dialog first`,
+      };
+    if (INTERACTIONS.has(action))
+      return await watchDialogs(id, tab, action, () =>
+        runCommand(id, action, tab, tabId, params)
+      );
+    return await runCommand(id, action, tab, tabId, params);
   } catch (e) {
     return { id, success: false, error: e.message || String(e) };
   }
 }
 
+// Resolve frame, ref and locator arguments, then dispatch to the handler.
+async function runCommand(id, action, tab, tabId, params) {
+  // `frame` narrows every page script below to one iframe of the tab.
+  if (params.frame != null && FRAME_ACTIONS.has(action)) {
+    const frame = await resolveFrame(tab, String(params.frame));
+    if (frame.error) return { id, success: false, error: frame.error };
+    tab = { ...tab, frameId: frame.frameId };
+  }
+
+  if (params.ref && REF_ACTIONS.has(action)) {
+    const resolved = await resolveRef(tab, params.ref);
+    if (resolved.error) return { id, success: false, error: resolved.error };
+    params = { ...params, selector: resolved.selector };
+  }
+  if (params.locator && !params.selector && REF_ACTIONS.has(action)) {
+    const located = await resolveLocator(tab, params.locator, action, params.timeout_ms);
+    if (located.error) return { id, success: false, error: located.error };
+    params = { ...params, selector: located.selector };
+  }
+  if (ACTIONABLE_ACTIONS.has(action) && params.selector) {
+    const ready = await waitActionable(tab, params.selector, action, params.timeout_ms);
+    if (ready.error) return { id, success: false, error: ready.error };
+    params = { ...params, point: { x: ready.x, y: ready.y } };
+  }
+
+  switch (action) {
+    case "get_tabs":
+      return await handleGetTabs(id);
+    case "switch_tab":
+      return await handleSwitchTab(id, tab, tabId);
+    case "new_tab":
+      return await handleNewTab(id, params);
+    case "close_tab":
+      return await handleCloseTab(id, tab);
+    case "navigate":
+      return await handleNavigate(id, tab, params);
+    case "click":
+      return await handleClick(id, tab, params);
+    case "type":
+      return await handleType(id, tab, params);
+    case "fill":
+      return await handleFill(id, tab, params);
+    case "hover":
+      return await handleHover(id, tab, params);
+    case "select_option":
+      return await handleSelectOption(id, tab, params);
+    case "check":
+      return await handleCheck(id, tab, params);
+    case "upload":
+      return await handleUpload(id, tab, params);
+    case "press_key":
+      return await handlePressKey(id, tab, params);
+    case "scroll":
+      return await handleScroll(id, tab, params);
+    case "go_back":
+    case "go_forward":
+    case "reload":
+      return await handleHistory(id, tab, action);
+    case "read":
+      return await handleRead(id, tab, params);
+    case "extract":
+      return await handleExtract(id, tab, params);
+    case "list_elements":
+      return await handleListElements(id, tab, params);
+    case "snapshot":
+      return await handleSnapshot(id, tab, params);
+    case "screenshot":
+      return await handleScreenshot(id, tab, params);
+    case "execute_js":
+      return await handleExecuteJs(id, tab, params);
+    case "wait_for":
+      return await handleWaitFor(id, tab, params);
+    case "wait_for_request":
+      return await handleWaitForRequest(id, tab, params);
+    case "wait_for_download":
+      return await handleWaitForDownload(id, params);
+    case "console":
+      return await handleConsole(id, tab, params);
+    case "dialog":
+      return await handleDialog(id, tab, params);
+    case "cookies":
+      return await handleCookies(id, tab, params);
+    case "storage":
+      return await handleStorage(id, tab, params);
+    case "export_session":
+    case "import_session":
+      return {
+        id,
+        success: false,
+        error:
+          "Session bundles belong to the dedicated CDP profile (~/.zeptobot/chrome-profile) — connect with CDP to export or import one",
+      };
+    default:
+      return { id, success: false, error: `Unknown action: ${action}` };
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Page helpers (dom.js)
+// ---------------------------------------------------------------------------
+
+// chrome.scripting target for the tab, or just its selected frame.
+function scriptTarget(tab) {
+  return tab.frameId != null
+    ? { tabId: tab.id, frameIds: [tab.frameId] }
+    : { tabId: tab.id };
+}
+
+// Load the shared page-side helpers (window.__zb) into the tab.
+async function injectDom(tab) {
+  await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    files: ["dom.js"],
+    world: "MAIN",
+  });
+}
+
+// Frames of the tab we can script, main frame first: [{ frameId, url, name }].
+async function listFrames(tab) {
+  const results = await chrome.scripting.executeScript({
+    target: { tabId: tab.id, allFrames: true },
+    func: () => ({ url: location.href, name: window.name }),
+    world: "MAIN",
+  });
+  return results
+    .filter((r) => r.result)
+    .map((r) => ({ frameId: r.frameId, ...r.result }))
+    .sort((a, b) => a.frameId - b.frameId);
+}
+
+// Find a frame by index (as listed by list_elements), name, URL substring
+// or a selector for its <iframe>. Returns { frameId } or { error }.
+async function resolveFrame(tab, spec) {
+  const frames = await listFrames(tab);
+  let frame = /^\d+$/.test(spec) ? frames[Number(spec)] : null;
+  frame ||= frames.find((f) => f.name === spec) ||
+    frames.find((f) => f.url.includes(spec));
+  if (!frame) {
+    await injectDom(tab);
+    const results = await chrome.scripting.executeScript({
+      target: { tabId: tab.id },
+      func: (sel) => {
+        try {
+          return window.__zb.frameOwner(sel);
+        } catch (e) {
+          return { error: e.message };
+        }
+      },
+      args: [spec],
+      world: "MAIN",
+    });
+    const owner = results[0]?.result || {};
+    frame = frames.find(
+      (f) =>
+        (owner.name && f.name === owner.name) || (owner.src && f.url === owner.src)
+    );
+  }
+  if (frame) return { frameId: frame.frameId };
+  const known = frames.map((f, i) => `${i}: ${f.name || "-"} ${f.url}`).join("; ");
+  return { error: `No frame matches '${spec}'. Frames: ${known}` };
+}
+
+// Turn an element ref into its selector, or { error } if it is stale.
+async function resolveRef(tab, ref) {
+  await injectDom(tab);
+  const results = await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    func: (r) => {
+      try {
+        return { selector: window.__zb.refSelector(r) };
+      } catch (e) {
+        return { error: e.message };
+      }
+    },
+    args: [ref],
+    world: "MAIN",
+  });
+  return results[0]?.result || { error: "Could not resolve ref" };
+}
+
+// Resolve a role/label/placeholder/text locator to a selector, or { error }.
+// wait_for keeps polling while nothing matches yet.
+async function resolveLocator(tab, locator, action, timeoutMs = 5000) {
+  const deadline = Date.now() + (action === "wait_for" ? timeoutMs : 0);
+  for (;;) {
+    await injectDom(tab);
+    const results = await chrome.scripting.executeScript({
+      target: scriptTarget(tab),
+      func: (loc) => window.__zb.locate(loc),
+      args: [locator],
+      world: "MAIN",
+    });
+    const r = results[0]?.result || { error: "Could not resolve locator" };
+    if (r.kind !== "none" || Date.now() >= deadline) return r;
+    await new Promise((resolve) => setTimeout(resolve, 300));
+  }
+}
+
+// Poll until the element passes the actionability checks for `action`,
+// or return { error } naming the check that was still failing.
+async function waitActionable(tab, selector, action, timeoutMs = 5000) {
+  const deadline = Date.now() + timeoutMs;
+  for (;;) {
+    await injectDom(tab);
+    const results = await chrome.scripting.executeScript({
+      target: scriptTarget(tab),
+      func: (sel, act) => window.__zb.actionable(sel, act),
+      args: [selector, action],
+      world: "MAIN",
+    });
+    const r = results[0]?.result || { ok: false, check: "unknown", error: "no result" };
+    if (r.ok) return r;
+    if (Date.now() >= deadline) {
+      return {
+        error: `Not actionable after ${timeoutMs}ms (${r.check} check failed): ${r.error}`,
+      };
+    }
+    await new Promise((resolve) => setTimeout(resolve, 100));
+  }
+}
+
+// Call a window.__zb action that returns { ok, result } or { error }, and
+// turn it into a command response.
+async function callZb(id, tab, method, args) {
+  await injectDom(tab);
+  const results = await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    func: (m, a) => window.__zb[m](...a),
+    args: [method, args],
+    world: "MAIN",
+  });
+  const r = results[0]?.result || { error: "no result" };
+  if (r.error) return { id, success: false, error: r.error };
+  return { id, success: true, result: r.result };
+}
+
+// Resolve true once the tab finishes loading, or false after `timeoutMs`.
+function waitForLoad(tabId, timeoutMs = 15000) {
+  return new Promise((resolve) => {
+    const listener = (updatedTabId, changeInfo) => {
+      if (updatedTabId === tabId && changeInfo.status === "complete") {
+        chrome.tabs.onUpdated.removeListener(listener);
+        resolve(true);
+      }
+    };
+    chrome.tabs.onUpdated.addListener(listener);
+    setTimeout(() => {
+      chrome.tabs.onUpdated.removeListener(listener);
+      resolve(false);
+    }, timeoutMs);
+  });
+}
+
+// Resolve true once the tab's document is parsed, or false after `timeoutMs`.
+function waitForDomReady(tabId, timeoutMs = 15000) {
+  return new Promise((resolve) => {
+    const listener = (details) => {
+      if (details.tabId === tabId && details.frameId === 0) {
+        chrome.webNavigation.onDOMContentLoaded.removeListener(listener);
+        resolve(true);
+      }
+    };
+    chrome.webNavigation.onDOMContentLoaded.addListener(listener);
+    setTimeout(() => {
+      chrome.webNavigation.onDOMContentLoaded.removeListener(listener);
+      resolve(false);
+    }, timeoutMs);
+  });
+}
+
+// ---------------------------------------------------------------------------
+// Trusted input (chrome.debugger)
+// ---------------------------------------------------------------------------
+
+// Key events for Input.dispatchKeyEvent, matching src-tauri/src/tools/web_input.rs.
+const KEYS = {
+  Enter: { key: "Enter", code: "Enter", windowsVirtualKeyCode: 13, text: "\r" },
+  Backspace: { key: "Backspace", code: "Backspace", windowsVirtualKeyCode: 8 },
+};
+
+// One debugger session per tab, shared by trusted input, snapshots and
+// dialog watching, and detached when the last user lets go. Chrome shows
+// its "started debugging this browser" bar while attached.
+const debuggerSessions = new Map();
+
+async function acquireDebugger(tabId) {
+  let session = debuggerSessions.get(tabId);
+  if (!session) {
+    session = { users: 0, attached: chrome.debugger.attach({ tabId }, "1.3") };
+    debuggerSessions.set(tabId, session);
+  }
+  session.users++;
+  try {
+    await session.attached;
+  } catch (e) {
+    session.users--;
+    if (debuggerSessions.get(tabId) === session) debuggerSessions.delete(tabId);
+    throw e;
+  }
+}
+
+function releaseDebugger(tabId) {
+  const session = debuggerSessions.get(tabId);
+  if (!session || --session.users > 0) return;
+  debuggerSessions.delete(tabId);
+  // Requests still in flight won't report back to the next session.
+  responseBodies.get(tabId)?.pending.clear();
+  chrome.debugger.detach({ tabId }).catch(() => {});
+}
+
+// The user closed the debugging bar, or the tab went away.
+chrome.debugger.onDetach.addListener(({ tabId }) => {
+  debuggerSessions.delete(tabId);
+  responseBodies.get(tabId)?.pending.clear();
+  openDialogs.delete(tabId);
+});
+
+// Attach the debugger to the tab for the duration of fn(target).
+async function withDebugger(tab, fn) {
+  await acquireDebugger(tab.id);
+  try {
+    return await fn({ tabId: tab.id });
+  } finally {
+    releaseDebugger(tab.id);
+  }
+}
+
+async function trustedKey(target, name) {
+  const { text, ...def } = KEYS[name];
+  await chrome.debugger.sendCommand(target, "Input.dispatchKeyEvent", {
+    type: text ? "keyDown" : "rawKeyDown",
+    ...def,
+    ...(text ? { text, unmodifiedText: text } : {}),
+  });
+  await chrome.debugger.sendCommand(target, "Input.dispatchKeyEvent", {
+    type: "keyUp",
+    ...def,
+  });
+}
+
+// Input events and screenshot clips use top-level viewport coordinates:
+// shift a point inside the tab's selected frame by the frame's position.
+async function topLevelPoint(tab, point) {
+  if (!tab.frameId) return point;
+  const results = await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    func: () => window.__zb.frameOffset(),
+    world: "MAIN",
+  });
+  const offset = results[0]?.result;
+  if (!offset)
+    return {
+      error: "Can't locate this frame on screen (cross-origin parent) — retry without trusted",
+    };
+  return { x: point.x + offset.x, y: point.y + offset.y };
+}
+
+async function trustedClick(target, { x, y }) {
+  const base = { x, y };
+  await chrome.debugger.sendCommand(target, "Input.dispatchMouseEvent", {
+    type: "mouseMoved",
+    ...base,
+  });
+  for (const type of ["mousePressed", "mouseReleased"]) {
+    await chrome.debugger.sendCommand(target, "Input.dispatchMouseEvent", {
+      type,
+      ...base,
+      button: "left",
+      clickCount: 1,
+    });
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Network activity (chrome.webRequest)
+// ---------------------------------------------------------------------------
+
+// Same bookkeeping as src-tauri/src/tools/web_network.rs: per tab, requests
+// in flight and recently finished ones, numbered so "since the last action"
+// is a comparison against networkMark.
+const NETWORK_LOG_SIZE = 300;
+const IDLE_QUIET_MS = 500;
+const LONG_LIVED_TYPES = new Set(["websocket"]);
+const networkLogs = new Map();
+let networkSeq = 0;
+let networkMark = 0;
+
+function networkLog(tabId) {
+  let log = networkLogs.get(tabId);
+  if (!log) {
+    log = { entries: new Map(), lastActivity: Date.now() };
+    networkLogs.set(tabId, log);
+  }
+  return log;
+}
+
+chrome.webRequest.onBeforeRequest.addListener(
+  (d) => {
+    if (d.tabId < 0) return;
+    const log = networkLog(d.tabId);
+    log.lastActivity = Date.now();
+    // Redirects reuse the request id: follow the request to its new URL.
+    const existing = log.entries.get(d.requestId);
+    if (existing && existing.duration_ms == null) {
+      existing.url = d.url;
+      return;
+    }
+    log.entries.set(d.requestId, {
+      seq: ++networkSeq,
+      url: d.url,
+      method: d.method,
+      type: d.type,
+      status: null,
+      mime_type: null,
+      error: null,
+      duration_ms: null,
+      started: Date.now(),
+    });
+    if (log.entries.size > NETWORK_LOG_SIZE) {
+      for (const [key, entry] of log.entries) {
+        if (entry.duration_ms != null) {
+          log.entries.delete(key);
+          break;
+        }
+      }
+    }
+  },
+  { urls: ["<all_urls>"] }
+);
+
+function finishRequest(d, error) {
+  const log = networkLogs.get(d.tabId);
+  const entry = log?.entries.get(d.requestId);
+  if (!entry) return;
+  log.lastActivity = Date.now();
+  entry.duration_ms = Date.now() - entry.started;
+  entry.error = error;
+  if (d.statusCode) entry.status = d.statusCode;
+  const type = d.responseHeaders?.find((h) => h.name.toLowerCase() === "content-type");
+  if (type?.value) entry.mime_type = type.value.split(";")[0].trim();
+  // Failed requests go to the console log too, as Chrome's own console
+  // shows them. Aborted ones are just navigations cancelling loads.
+  if ((error && error !== "net::ERR_ABORTED") || entry.status >= 400) {
+    pushConsole(d.tabId, {
+      level: "error",
+      source: "network",
+      text: `${entry.method} ${entry.url} → ${error ? `failed: ${error}` : entry.status}`,
+    });
+  }
+}
+
+chrome.webRequest.onCompleted.addListener((d) => finishRequest(d, null), { urls: ["<all_urls>"] }, [
+  "responseHeaders",
+]);
+chrome.webRequest.onErrorOccurred.addListener((d) => finishRequest(d, d.error), {
+  urls: ["<all_urls>"],
+});
+chrome.tabs.onRemoved.addListener((tabId) => networkLogs.delete(tabId));
+
+function inFlight(tabId) {
+  const log = networkLogs.get(tabId);
+  if (!log) return [];
+  return [...log.entries.values()].filter(
+    (e) => e.duration_ms == null && !LONG_LIVED_TYPES.has(e.type)
+  );
+}
+
+// Resolve null once nothing is in flight for IDLE_QUIET_MS, or a note on
+// what is still loading at `deadline`.
+async function waitNetworkIdle(tabId, deadline) {
+  for (;;) {
+    const busy = inFlight(tabId);
+    const quiet = Date.now() - (networkLogs.get(tabId)?.lastActivity ?? 0);
+    if (!busy.length && quiet >= IDLE_QUIET_MS) return null;
+    if (Date.now() >= deadline) {
+      const example = busy[0] ? `, e.g. ${busy[0].url}` : "";
+      return `network still busy — ${busy.length} request(s) in flight${example}`;
+    }
+    await new Promise((resolve) => setTimeout(resolve, 100));
+  }
+}
+
+// Substring, or a glob with * over the whole URL (see web_network.rs).
+function urlMatches(pattern, url) {
+  if (!pattern.includes("*")) return url.includes(pattern);
+  const escaped = pattern.split("*").map((p) => p.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"));
+  return new RegExp(`^${escaped.join(".*")}$`).test(url);
+}
+
+function requestSummary(e) {
+  let line = `${e.method} ${e.url}`;
+  if (e.error) line += ` → failed: ${e.error}`;
+  else if (e.status != null) line += ` → ${e.status}${e.mime_type ? ` ${e.mime_type}` : ""}`;
+  else line += " → pending";
+  if (e.duration_ms != null) line += ` (${e.duration_ms} ms)`;
+  return line;
+}
+
+// ---------------------------------------------------------------------------
+// Console log (console.js hook)
+// ---------------------------------------------------------------------------
+
+// Same ring buffer as src-tauri/src/tools/web_console.rs.
+const CONSOLE_LOG_SIZE = 500;
+const LEVEL_RANK = { debug: 0, log: 1, info: 1, warning: 2, error: 3 };
+const consoleLogs = new Map();
+let consoleSeq = 0;
+let consoleMark = 0;
+
+function normalizeLevel(level) {
+  if (level === "warn" || level === "warning") return "warning";
+  if (level === "error" || level === "assert") return "error";
+  if (level === "info" || level === "debug") return level;
+  return "log";
+}
+
+function pushConsole(tabId, entry) {
+  let log = consoleLogs.get(tabId);
+  if (!log) {
+    log = [];
+    consoleLogs.set(tabId, log);
+  }
+  log.push({
+    seq: ++consoleSeq,
+    level: normalizeLevel(entry.level),
+    source: entry.source || "console",
+    text: String(entry.text ?? "").slice(0, 500),
+    url: entry.url || null,
+    line: entry.line || null,
+  });
+  if (log.length > CONSOLE_LOG_SIZE) log.shift();
+}
+
+chrome.tabs.onRemoved.addListener((tabId) => consoleLogs.delete(tabId));
+
+// ---------------------------------------------------------------------------
+// Downloads (chrome.downloads)
+// ---------------------------------------------------------------------------
+
+// Same bookkeeping as src-tauri/src/tools/web_files.rs. Downloads belong to
+// the browser, not a tab, and go wherever Chrome is set to save them.
+const DOWNLOAD_LOG_SIZE = 100;
+const downloads = [];
+let downloadSeq = 0;
+let downloadMark = 0;
+
+const baseName = (path) => path.split(/[\\/]/).pop();
+
+chrome.downloads.onCreated.addListener((item) => {
+  downloads.push({
+    seq: ++downloadSeq,
+    id: item.id,
+    url: item.finalUrl || item.url,
+    filename: item.filename ? baseName(item.filename) : "",
+    state: "in_progress",
+    path: item.filename || null,
+    error: null,
+  });
+  if (downloads.length > DOWNLOAD_LOG_SIZE) downloads.shift();
+});
+
+chrome.downloads.onChanged.addListener((delta) => {
+  const entry = downloads.find((d) => d.id === delta.id);
+  if (!entry) return;
+  if (delta.filename?.current) {
+    entry.path = delta.filename.current;
+    entry.filename = baseName(entry.path);
+  }
+  if (delta.error?.current) entry.error = delta.error.current;
+  if (delta.state?.current === "complete") entry.state = "completed";
+  if (delta.state?.current === "interrupted") entry.state = "canceled";
+});
+
+// ---------------------------------------------------------------------------
+// JavaScript dialogs (chrome.debugger)
+// ---------------------------------------------------------------------------
+
+// Same policy and wording as src-tauri/src/tools/web_dialog.rs. Dialogs are
+// seen only while the debugger is attached, so interactions run under
+// watchDialogs; one opened from a timer while nothing watches still blocks
+// the tab until the user answers it.
+let dialogPolicy = "report";
+const openDialogs = new Map(); // tabId -> dialog left for browser_dialog
+const dialogWaiters = new Map(); // tabId -> resolve(dialog) of the running action
+const dialogNotes = new Map(); // tabId -> dialogs the policy answered
+
+function describeDialog(d) {
+  let text = `${d.type || "JavaScript"} dialog "${d.message}"`;
+  if (d.type === "prompt" && d.default_prompt)
+    text += ` (default answer "${d.default_prompt}")`;
+  return text;
+}
+
+function addDialogNote(tabId, note) {
+  const notes = dialogNotes.get(tabId) || [];
+  notes.push(note);
+  dialogNotes.set(tabId, notes);
+}
+
+chrome.debugger.onEvent.addListener(({ tabId }, method, params) => {
+  if (method === "Page.javascriptDialogClosed") {
+    if (openDialogs.delete(tabId)) releaseDebugger(tabId);
+    return;
+  }
+  if (method !== "Page.javascriptDialogOpening") return;
+  const dialog = {
+    type: params.type,
+    message: params.message,
+    default_prompt: params.defaultPrompt ?? null,
+    url: params.url,
+  };
+  if (dialogPolicy === "report") {
+    // Hold the session: browser_dialog answers through it.
+    if (!openDialogs.has(tabId)) acquireDebugger(tabId).catch(() => {});
+    openDialogs.set(tabId, dialog);
+    dialogWaiters.get(tabId)?.(dialog);
+    return;
+  }
+  const accept = dialogPolicy === "accept";
+  addDialogNote(
+    tabId,
+    `Dialog ${accept ? "accepted" : "dismissed"} automatically: ${describeDialog(dialog)}`
+  );
+  chrome.debugger
+    .sendCommand({ tabId }, "Page.handleJavaScriptDialog", {
+      accept,
+      ...(accept && params.defaultPrompt != null ? { promptText: params.defaultPrompt } : {}),
+    })
+    .catch((e) => addDialogNote(tabId, `Could not answer ${describeDialog(dialog)}: ${e.message}`));
+});
+
+// Response bodies for wait_for_request capture_body. webRequest can't read
+// them and fetching the URL again would send a second, real request, so
+// they come from the debugger: fetch/XHR responses that finish while it is
+// attached (during an interaction or the wait itself) are read as they
+// arrive, keeping the latest few per tab.
+const BODY_TYPES = new Set(["XHR", "Fetch"]);
+const BODY_LOG_SIZE = 20;
+const responseBodies = new Map(); // tabId -> { pending: Map(requestId -> request), done: [] }
+
+chrome.debugger.onEvent.addListener(({ tabId }, method, params) => {
+  if (method === "Network.requestWillBeSent") {
+    if (!BODY_TYPES.has(params.type)) return;
+    let log = responseBodies.get(tabId);
+    if (!log) {
+      log = { pending: new Map(), done: [] };
+      responseBodies.set(tabId, log);
+    }
+    // Redirects reuse the request id: keep the final URL.
+    log.pending.set(params.requestId, { url: params.request.url, method: params.request.method });
+  } else if (method === "Network.loadingFailed") {
+    responseBodies.get(tabId)?.pending.delete(params.requestId);
+  } else if (method === "Network.loadingFinished") {
+    const log = responseBodies.get(tabId);
+    const request = log?.pending.get(params.requestId);
+    if (!request) return;
+    log.pending.delete(params.requestId);
+    log.done.push({ ...request, body: readBody({ tabId }, params.requestId) });
+    if (log.done.length > BODY_LOG_SIZE) log.done.shift();
+  }
+});
+
+// Network.getResponseBody as { body } text, or { body_error }.
+async function readBody(target, requestId) {
+  let res;
+  try {
+    res = await chrome.debugger.sendCommand(target, "Network.getResponseBody", { requestId });
+  } catch (e) {
+    return { body_error: `Response body unavailable: ${e.message}` };
+  }
+  if (!res.base64Encoded) return { body: res.body };
+  const bytes = Uint8Array.from(atob(res.body), (c) => c.charCodeAt(0));
+  try {
+    return { body: new TextDecoder("utf-8", { fatal: true }).decode(bytes) };
+  } catch {
+    return { body_error: `Binary response body (${bytes.length} bytes)` };
+  }
+}
+
+chrome.tabs.onRemoved.addListener((tabId) => responseBodies.delete(tabId));
+
+// Run an interaction, returning as soon as it opens a dialog: a page with a
+// dialog open runs no script, so the action would only hang.
+async function watchDialogs(id, tab, action, run) {
+  try {
+    await acquireDebugger(tab.id);
+  } catch {
+    // chrome:// pages and the like can't be debugged; run unwatched.
+    return await run();
+  }
+  try {
+    await chrome.debugger.sendCommand({ tabId: tab.id }, "Page.enable");
+    await chrome.debugger.sendCommand({ tabId: tab.id }, "Network.enable");
+    const opened = new Promise((resolve) => dialogWaiters.set(tab.id, resolve));
+    const res = await Promise.race([
+      run(),
+      opened.then((dialog) => ({
+        id,
+        success: true,
+        result: `${action} opened a ${describeDialog(dialog)}. The page is blocked until you answer it with browser_dialog (accept or dismiss).`,
+      })),
+    ]);
+    const notes = dialogNotes.get(tab.id) || [];
+    dialogNotes.delete(tab.id);
+    if (notes.length) {
+      const text = `\n\n${notes.join("\n")}`;
+      if (!res.success) res.error = `${res.error}${text}`;
+      else if (typeof res.result === "string") res.result += text;
+    }
+    return res;
+  } finally {
+    dialogWaiters.delete(tab.id);
+    releaseDebugger(tab.id);
+  }
+}
+
+chrome.tabs.onRemoved.addListener((tabId) => {
+  dialogNotes.delete(tabId);
+  openDialogs.delete(tabId);
+});
+
 // ---------------------------------------------------------------------------
 // Action handlers
 // ---------------------------------------------------------------------------
@@ -148,88 +945,95 @@ async function handleGetTabs(id) {
   return { id, success: true, result: JSON.stringify(list) };
 }
 
-async function handleNavigate(id, tab, params) {
+async function handleSwitchTab(id, tab, tabId) {
+  if (!tabId) return { id, success: false, error: "Missing 'tab' parameter" };
+  await chrome.tabs.update(tab.id, { active: true });
+  await chrome.windows.update(tab.windowId, { focused: true });
+  return {
+    id,
+    success: true,
+    result: `Switched to tab ${tab.id} — title: "${tab.title}"`,
+  };
+}
+
+async function handleNewTab(id, params) {
   const { url } = params;
-  if (!url) return { id, success: false, error: "Missing 'url' parameter" };
+  const created = await chrome.tabs.create(url ? { url } : {});
+  return {
+    id,
+    success: true,
+    result: `Opened new tab ${created.id}${url ? " at " + url : ""}`,
+  };
+}
 
-  await chrome.tabs.update(tab.id, { url });
+async function handleCloseTab(id, tab) {
+  if (!tab) return { id, success: false, error: "No tab to close" };
+  await chrome.tabs.remove(tab.id);
+  return { id, success: true, result: `Closed tab ${tab.id}` };
+}
 
-  // Wait for page to finish loading (max 15s)
-  await new Promise((resolve) => {
-    const listener = (updatedTabId, changeInfo) => {
-      if (updatedTabId === tab.id && changeInfo.status === "complete") {
-        chrome.tabs.onUpdated.removeListener(listener);
-        resolve();
-      }
+async function handleNavigate(id, tab, params) {
+  const { url, wait_until = "load" } = params;
+  if (!url) return { id, success: false, error: "Missing 'url' parameter" };
+  if (!["load", "domcontentloaded", "networkidle"].includes(wait_until))
+    return {
+      id,
+      success: false,
+      error: `Unknown wait_until '${wait_until}' — use load, domcontentloaded or networkidle`,
     };
-    chrome.tabs.onUpdated.addListener(listener);
-    setTimeout(() => {
-      chrome.tabs.onUpdated.removeListener(listener);
-      resolve();
-    }, 15000);
-  });
+  const timeout = Math.min(params.timeout_ms ?? 15000, 30000);
+  const deadline = Date.now() + timeout;
+
+  const ready =
+    wait_until === "domcontentloaded" ? waitForDomReady(tab.id, timeout) : waitForLoad(tab.id, timeout);
+  await chrome.tabs.update(tab.id, { url });
+  let note = (await ready) ? "" : ` (still loading after ${timeout}ms)`;
+  if (wait_until === "networkidle" && !note) {
+    const busy = await waitNetworkIdle(tab.id, deadline);
+    if (busy) note = ` (${busy} after ${timeout}ms)`;
+  }
 
   const updated = await chrome.tabs.get(tab.id);
   return {
     id,
     success: true,
-    result: `Navigated to ${url} — title: "${updated.title}"`,
+    result: `Navigated to ${url} — title: "${updated.title}"${note}`,
   };
 }
 
 async function handleClick(id, tab, params) {
-  const { selector, text } = params;
-  if (!selector && !text)
-    return { id, success: false, error: "Provide 'selector' or 'text'" };
+  const { selector, trusted = false, point } = params;
+  if (!selector)
+    return {
+      id,
+      success: false,
+      error: "Provide 'ref', 'selector', 'text', 'role', 'label' or 'placeholder'",
+    };
+
+  if (trusted) {
+    const at = await topLevelPoint(tab, point);
+    if (at.error) return { id, success: false, error: at.error };
+    const { x, y } = at;
+    await withDebugger(tab, (target) => trustedClick(target, { x, y }));
+    return {
+      id,
+      success: true,
+      result: `clicked (trusted) at (${x}, ${y}): ${selector}`,
+    };
+  }
 
+  await injectDom(tab);
   const results = await chrome.scripting.executeScript({
-    target: { tabId: tab.id },
-    func: (sel, txt) => {
-      if (sel) {
-        const el = document.querySelector(sel);
-        if (el) {
-          el.click();
-          return "clicked: " + sel;
-        }
-        return "not_found: " + sel;
-      }
-      if (txt) {
-        const lower = txt.toLowerCase();
-        // Try XPath text search
-        const xpath = `//*[contains(translate(text(),'ABCDEFGHIJKLMNOPQRSTUVWXYZ','abcdefghijklmnopqrstuvwxyz'), '${lower.replace(/'/g, "\\'")}')]`;
-        const xr = document.evaluate(
-          xpath,
-          document,
-          null,
-          XPathResult.FIRST_ORDERED_NODE_TYPE,
-          null
-        );
-        if (xr.singleNodeValue) {
-          xr.singleNodeValue.click();
-          return "clicked text: " + txt;
-        }
-        // Try aria-label
-        const ariaEl = document.querySelector(
-          `[aria-label*="${txt}" i], [data-tooltip*="${txt}" i]`
-        );
-        if (ariaEl) {
-          ariaEl.click();
-          return "clicked aria: " + txt;
-        }
-        // Try role=button with text
-        const btns = document.querySelectorAll(
-          'button, [role="button"], a, [role="link"]'
-        );
-        for (const b of btns) {
-          if (b.textContent.toLowerCase().includes(lower)) {
-            b.click();
-            return "clicked button: " + txt;
-          }
-        }
-        return "not_found: " + txt;
+    target: scriptTarget(tab),
+    func: (sel) => {
+      const el = window.__zb.query(sel);
+      if (el) {
+        el.click();
+        return "clicked: " + sel;
       }
+      return "not_found: " + sel;
     },
-    args: [selector || null, text || null],
+    args: [selector],
     world: "MAIN",
   });
 
@@ -240,41 +1044,18 @@ async function handleClick(id, tab, params) {
 }
 
 async function handleType(id, tab, params) {
-  const { selector, text, value, clear_first = true } = params;
+  const { selector, value, clear_first = true, submit = false, trusted = false } = params;
   if (!value)
     return { id, success: false, error: "Missing 'value' parameter" };
 
-  const results = await chrome.scripting.executeScript({
-    target: { tabId: tab.id },
-    func: (sel, label, val, clear) => {
-      let el = null;
+  if (trusted) return await handleTrustedType(id, tab, params);
 
-      if (sel) {
-        el = document.querySelector(sel);
-      } else if (label) {
-        // Find by label text
-        for (const l of document.querySelectorAll("label")) {
-          if (l.textContent.toLowerCase().includes(label.toLowerCase())) {
-            el = l.htmlFor
-              ? document.getElementById(l.htmlFor)
-              : l.querySelector("input,textarea,select");
-            if (el) break;
-          }
-        }
-        // Try placeholder
-        if (!el)
-          el = document.querySelector(
-            `input[placeholder*="${label}" i], textarea[placeholder*="${label}" i]`
-          );
-        // Try aria-label
-        if (!el)
-          el = document.querySelector(
-            `[aria-label*="${label}" i][contenteditable], input[aria-label*="${label}" i], textarea[aria-label*="${label}" i]`
-          );
-      } else {
-        // Use focused element
-        el = document.activeElement;
-      }
+  await injectDom(tab);
+  const results = await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    func: (sel, val, clear, submit) => {
+      // Without a selector or locator, type into the focused element.
+      const el = sel ? window.__zb.query(sel) : document.activeElement;
 
       if (
         !el ||
@@ -299,9 +1080,17 @@ async function handleType(id, tab, params) {
         el.dispatchEvent(new Event("input", { bubbles: true }));
         el.dispatchEvent(new Event("change", { bubbles: true }));
       }
-      return "typed";
+      if (!submit) return "typed";
+
+      const init = { key: "Enter", code: "Enter", keyCode: 13, which: 13, bubbles: true };
+      el.dispatchEvent(new KeyboardEvent("keydown", init));
+      el.dispatchEvent(new KeyboardEvent("keypress", init));
+      el.dispatchEvent(new KeyboardEvent("keyup", init));
+      // Synthetic Enter doesn't submit forms by itself.
+      if (el.form) el.form.requestSubmit();
+      return "typed, pressed Enter";
     },
-    args: [selector || null, text || null, value, clear_first],
+    args: [selector || null, value, clear_first, submit],
     world: "MAIN",
   });
 
@@ -311,22 +1100,151 @@ async function handleType(id, tab, params) {
   return { id, success: true, result };
 }
 
+// Type through the debugger's Input domain so the page sees trusted events.
+async function handleTrustedType(id, tab, params) {
+  const { selector, value, clear_first = true, submit = false } = params;
+  await injectDom(tab);
+  const results = await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    func: (sel, clear) => window.__zb.focusField(sel, clear),
+    args: [selector || null, clear_first],
+    world: "MAIN",
+  });
+  const focused = results[0]?.result || { error: "Could not focus the field" };
+  if (focused.error) return { id, success: false, error: focused.error };
+
+  await withDebugger(tab, async (target) => {
+    if (clear_first) await trustedKey(target, "Backspace");
+    await chrome.debugger.sendCommand(target, "Input.insertText", { text: value });
+    if (submit) await trustedKey(target, "Enter");
+  });
+  const into = selector || "focused element";
+  return {
+    id,
+    success: true,
+    result: `typed (trusted) into ${into}${submit ? ", pressed Enter" : ""}`,
+  };
+}
+
+// Set one form field of any kind (see window.__zb.fill).
+async function handleFill(id, tab, params) {
+  const { selector, value, submit = false } = params;
+  if (!selector)
+    return {
+      id,
+      success: false,
+      error: "Provide 'ref', 'selector', 'role', 'label' or 'placeholder'",
+    };
+  if (value === undefined)
+    return { id, success: false, error: "Missing 'value' parameter" };
+
+  return await callZb(id, tab, "fill", [selector, value, submit]);
+}
+
+async function handleHover(id, tab, params) {
+  const { selector, trusted = false, point } = params;
+  if (!selector)
+    return {
+      id,
+      success: false,
+      error: "Provide 'ref', 'selector', 'text', 'role', 'label' or 'placeholder'",
+    };
+  if (!trusted) return await callZb(id, tab, "hover", [selector]);
+
+  const at = await topLevelPoint(tab, point);
+  if (at.error) return { id, success: false, error: at.error };
+  await withDebugger(tab, (target) =>
+    chrome.debugger.sendCommand(target, "Input.dispatchMouseEvent", {
+      type: "mouseMoved",
+      x: at.x,
+      y: at.y,
+    })
+  );
+  return { id, success: true, result: `hovered (trusted): ${selector}` };
+}
+
+async function handleSelectOption(id, tab, params) {
+  const { selector, values } = params;
+  if (!selector) return { id, success: false, error: "Provide 'ref', 'selector', 'label' or 'role'" };
+  if (values === undefined) return { id, success: false, error: "Missing 'values' parameter" };
+  return await callZb(id, tab, "selectOption", [selector, values]);
+}
+
+async function handleCheck(id, tab, params) {
+  const { selector, checked = true } = params;
+  if (!selector) return { id, success: false, error: "Provide 'ref', 'selector', 'label' or 'role'" };
+  return await callZb(id, tab, "check", [selector, checked]);
+}
+
+// Real key presses through the debugger. ZeptoBot sends the
+// Input.dispatchKeyEvent params (built by web_input.rs) as `key_events`.
+async function handlePressKey(id, tab, params) {
+  const { key, selector, key_events = [] } = params;
+  if (!key_events.length) return { id, success: false, error: "Missing 'key' parameter" };
+  if (selector) {
+    await injectDom(tab);
+    const results = await chrome.scripting.executeScript({
+      target: scriptTarget(tab),
+      func: (sel) => window.__zb.focusField(sel, false),
+      args: [selector],
+      world: "MAIN",
+    });
+    const focused = results[0]?.result || { error: "Could not focus the element" };
+    if (focused.error) return { id, success: false, error: focused.error };
+  }
+  await withDebugger(tab, async (target) => {
+    for (const ev of key_events) {
+      await chrome.debugger.sendCommand(target, "Input.dispatchKeyEvent", ev);
+    }
+  });
+  return { id, success: true, result: `pressed ${key}${selector ? " in " + selector : ""}` };
+}
+
+async function handleScroll(id, tab, params) {
+  const { selector, direction = "down", amount, to, into_view = false } = params;
+  return await callZb(id, tab, "scroll", [
+    selector || null,
+    { direction, amount, to, intoView: into_view },
+  ]);
+}
+
+async function handleExtract(id, tab, params) {
+  if (!params.spec) return { id, success: false, error: "Missing 'spec'" };
+  return await callZb(id, tab, "extract", [params.spec]);
+}
+
+async function handleHistory(id, tab, action) {
+  const loaded = waitForLoad(tab.id);
+  try {
+    if (action === "go_back") await chrome.tabs.goBack(tab.id);
+    else if (action === "go_forward") await chrome.tabs.goForward(tab.id);
+    else await chrome.tabs.reload(tab.id);
+  } catch (e) {
+    // goBack/goForward reject when there is no such history entry.
+    return { id, success: false, error: e.message || String(e) };
+  }
+  await loaded;
+  const updated = await chrome.tabs.get(tab.id);
+  const verb = { go_back: "Went back to", go_forward: "Went forward to", reload: "Reloaded" }[action];
+  return { id, success: true, result: `${verb} ${updated.url} — title: "${updated.title}"` };
+}
+
 async function handleRead(id, tab, params) {
-  const { selector, page_text = false } = params;
+  const { selector, page_text = false, format = "text", main_content = true } = params;
+  if (format === "markdown")
+    return await callZb(id, tab, "markdown", [selector || null, { main: main_content }]);
 
+  await injectDom(tab);
   const results = await chrome.scripting.executeScript({
-    target: { tabId: tab.id },
+    target: scriptTarget(tab),
     func: (sel, fullPage) => {
       if (sel) {
-        const el = document.querySelector(sel);
+        const el = window.__zb.query(sel);
         if (!el) return "Element not found: " + sel;
-        return (el.innerText || el.textContent || el.value || "").substring(
-          0,
-          3000
-        );
+        return el.innerText || el.textContent || el.value || "";
       }
       if (fullPage) {
-        return (document.body.innerText || "").substring(0, 5000);
+        return document.body.innerText || "";
       }
       return JSON.stringify({
         title: document.title,
@@ -342,52 +1260,138 @@ async function handleRead(id, tab, params) {
 
 async function handleListElements(id, tab, params) {
   const { filter = "" } = params;
+  const list = async (t) => {
+    await injectDom(t);
+    const results = await chrome.scripting.executeScript({
+      target: scriptTarget(t),
+      func: (filterText) => window.__zb.listElements(filterText),
+      args: [filter],
+      world: "MAIN",
+    });
+    return results[0]?.result || [];
+  };
+  if (tab.frameId != null)
+    return { id, success: true, result: JSON.stringify(await list(tab)) };
 
-  const results = await chrome.scripting.executeScript({
-    target: { tabId: tab.id },
-    func: (filterText) => {
-      const elements = [];
-      const all = document.querySelectorAll(
-        'a, button, input, textarea, select, [role="button"], [role="link"], [role="tab"], [onclick], [aria-label]'
-      );
-      for (let i = 0; i < Math.min(all.length, 100); i++) {
-        const el = all[i];
-        const text = (
-          el.innerText ||
-          el.value ||
-          el.placeholder ||
-          el.getAttribute("aria-label") ||
-          ""
-        )
-          .trim()
-          .substring(0, 80);
-        if (
-          filterText &&
-          !text.toLowerCase().includes(filterText.toLowerCase())
-        )
-          continue;
-        const tag = el.tagName.toLowerCase();
-        const type = el.type || el.getAttribute("role") || "";
-        const elId = el.id ? "#" + el.id : "";
-        const cls =
-          el.className && typeof el.className === "string"
-            ? "." +
-              el.className
-                .split(" ")
-                .filter(Boolean)
-                .slice(0, 2)
-                .join(".")
-            : "";
-        elements.push({ tag, text, type, selector: tag + elId + cls });
+  // Whole tab: every frame, with elements tagged by frame index.
+  const elements = [];
+  const frames = await listFrames(tab);
+  for (const [i, frame] of frames.entries()) {
+    let found;
+    try {
+      found = await list({ ...tab, frameId: frame.frameId });
+    } catch {
+      continue; // frame navigated away or isn't scriptable
+    }
+    for (const el of found) {
+      elements.push(i === 0 ? el : { ...el, frame: i, frame_url: frame.url });
+    }
+  }
+  return { id, success: true, result: JSON.stringify(elements) };
+}
+
+// Accessibility tree via the debugger API, with refs tagged on interactive
+// nodes. The role list comes from ZeptoBot so both sides agree.
+async function handleSnapshot(id, tab, params) {
+  const { ref_roles = [], max_refs = 300 } = params;
+  await injectDom(tab);
+
+  return await withDebugger(tab, async (target) => {
+    const { nodes } = await chrome.debugger.sendCommand(
+      target,
+      "Accessibility.getFullAXTree",
+      {}
+    );
+    const roles = new Set(ref_roles);
+    const candidates = nodes
+      .filter(
+        (n) => !n.ignored && roles.has(n.role?.value) && n.backendDOMNodeId
+      )
+      .slice(0, max_refs);
+
+    const refs = {};
+    for (const n of candidates) {
+      try {
+        const { object } = await chrome.debugger.sendCommand(
+          target,
+          "DOM.resolveNode",
+          { backendNodeId: n.backendDOMNodeId }
+        );
+        if (!object?.objectId) continue;
+        const { result } = await chrome.debugger.sendCommand(
+          target,
+          "Runtime.callFunctionOn",
+          {
+            objectId: object.objectId,
+            functionDeclaration:
+              "function() { return window.__zb ? window.__zb.ref(this) : null; }",
+            returnByValue: true,
+          }
+        );
+        if (typeof result?.value === "string")
+          refs[n.backendDOMNodeId] = result.value;
+      } catch {
+        // Node went away or lives in another frame — leave it without a ref.
       }
-      return JSON.stringify(elements);
-    },
-    args: [filter],
-    world: "MAIN",
+    }
+    return { id, success: true, result: JSON.stringify({ nodes, refs }) };
   });
+}
 
-  const raw = results[0]?.result || "[]";
-  return { id, success: true, result: raw };
+// Tallest full-page capture, in CSS pixels (Chrome's texture limit).
+const MAX_CAPTURE_HEIGHT = 16384;
+
+// The viewport of a tab in front of its window comes from captureVisibleTab;
+// background tabs, full pages and elements from the debugger, which needs
+// neither the tab nor the window in front.
+async function handleScreenshot(id, tab, params) {
+  const { selector, full_page = false } = params;
+  if (!selector && !full_page && tab.active) {
+    try {
+      const url = await chrome.tabs.captureVisibleTab(tab.windowId, { format: "png" });
+      const data = url.replace(/^data:image\/png;base64,/, "");
+      return { id, success: true, result: JSON.stringify({ data }) };
+    } catch {
+      // Minimized window: fall through to the debugger.
+    }
+  }
+  let box = null;
+  if (selector) {
+    await injectDom(tab);
+    const results = await chrome.scripting.executeScript({
+      target: scriptTarget(tab),
+      func: (sel) => window.__zb.box(sel),
+      args: [selector],
+      world: "MAIN",
+    });
+    box = results[0]?.result || { error: "no result" };
+    if (box.error) return { id, success: false, error: box.error };
+    // The box is in the frame's viewport: move it to the top-level one.
+    const origin = await topLevelPoint(tab, { x: box.x, y: box.y });
+    if (origin.error)
+      return {
+        id,
+        success: false,
+        error: "Can't locate this frame on screen (cross-origin parent) — screenshot the whole page instead",
+      };
+    box = { ...box, ...origin };
+  }
+  return await withDebugger(tab, async (target) => {
+    const { cssVisualViewport: view, cssContentSize: size } =
+      await chrome.debugger.sendCommand(target, "Page.getLayoutMetrics");
+    // Clips are in document coordinates.
+    let clip = null;
+    if (box) {
+      clip = { x: view.pageX + box.x, y: view.pageY + box.y, width: box.width, height: box.height };
+    } else if (full_page) {
+      clip = { x: 0, y: 0, width: size.width, height: Math.min(size.height, MAX_CAPTURE_HEIGHT) };
+    }
+    const { data } = await chrome.debugger.sendCommand(target, "Page.captureScreenshot", {
+      format: "png",
+      ...(clip ? { clip: { ...clip, scale: 1 }, captureBeyondViewport: true } : {}),
+    });
+    return { id, success: true, result: JSON.stringify({ data }) };
+  });
 }
 
 async function handleExecuteJs(id, tab, params) {
@@ -395,7 +1399,7 @@ async function handleExecuteJs(id, tab, params) {
   if (!code) return { id, success: false, error: "Missing 'code' parameter" };
 
   const results = await chrome.scripting.executeScript({
-    target: { tabId: tab.id },
+    target: scriptTarget(tab),
     func: (codeStr) => {
       try {
         const fn = new Function("return (" + codeStr + ")");
@@ -417,18 +1421,24 @@ async function handleExecuteJs(id, tab, params) {
 }
 
 async function handleWaitFor(id, tab, params) {
-  const { selector, text, timeout_ms = 5000 } = params;
+  const { selector, text, timeout_ms = 5000, network_idle = false } = params;
+  if (network_idle) {
+    const busy = await waitNetworkIdle(tab.id, Date.now() + Math.min(timeout_ms, 15000));
+    if (busy) return { id, success: false, error: `Timeout after ${timeout_ms}ms: ${busy}` };
+    if (!selector && !text) return { id, success: true, result: "Network idle" };
+  }
   if (!selector && !text)
     return { id, success: false, error: "Provide 'selector' or 'text'" };
 
+  await injectDom(tab);
   const results = await chrome.scripting.executeScript({
-    target: { tabId: tab.id },
+    target: scriptTarget(tab),
     func: (sel, txt, timeout) => {
       return new Promise((resolve) => {
         const deadline = Date.now() + timeout;
         const check = () => {
           if (sel) {
-            const el = document.querySelector(sel);
+            const el = window.__zb.query(sel);
             if (el) return resolve(el.innerText || el.textContent || "found");
           }
           if (txt) {
@@ -463,3 +1473,254 @@ async function handleWaitFor(id, tab, params) {
     error: `Timeout: not found after ${timeout_ms}ms`,
   };
 }
+
+async function handleWaitForRequest(id, tab, params) {
+  if (!params.capture_body) return await waitForRequest(id, tab, params);
+  // Watch the network while waiting, so a response arriving now is read.
+  return await withDebugger(tab, async (target) => {
+    await chrome.debugger.sendCommand(target, "Network.enable");
+    return await waitForRequest(id, tab, params);
+  });
+}
+
+async function waitForRequest(id, tab, params) {
+  const { url, method, capture_body = false } = params;
+  if (!url) return { id, success: false, error: "Missing 'url' pattern" };
+  const timeout = Math.min(params.timeout_ms ?? 10000, 30000);
+  const since = networkMark;
+  const deadline = Date.now() + timeout;
+
+  for (;;) {
+    const entries = [...(networkLogs.get(tab.id)?.entries.values() ?? [])];
+    const match = entries.find(
+      (e) =>
+        e.seq > since &&
+        e.duration_ms != null &&
+        (!method || e.method.toUpperCase() === method.toUpperCase()) &&
+        urlMatches(url, e.url)
+    );
+    if (match) {
+      const { seq, started, ...result } = match;
+      if (capture_body) Object.assign(result, await fetchBody(tab, match));
+      return { id, success: true, result: JSON.stringify(result) };
+    }
+    if (Date.now() >= deadline) {
+      const recent = entries.slice(-10).map((e) => `  ${requestSummary(e)}`);
+      return {
+        id,
+        success: false,
+        error: `No request matching '${url}' within ${timeout}ms. Latest requests:\n${
+          recent.join("\n") || "  (none)"
+        }`,
+      };
+    }
+    await new Promise((resolve) => setTimeout(resolve, 100));
+  }
+}
+
+// The body of `entry` as the page received it, from the responses read
+// while the debugger was attached (see responseBodies). The response may
+// land a moment after webRequest reports the request finished.
+async function fetchBody(tab, entry) {
+  const deadline = Date.now() + 1000;
+  for (;;) {
+    const read = responseBodies
+      .get(tab.id)
+      ?.done.findLast((r) => r.url === entry.url && r.method === entry.method);
+    if (read) return await read.body;
+    if (Date.now() >= deadline)
+      return {
+        body_error:
+          "the extension only reads fetch/XHR bodies that arrive during an action or this wait — use the CDP backend",
+      };
+    await new Promise((resolve) => setTimeout(resolve, 100));
+  }
+}
+
+async function handleConsole(id, tab, params) {
+  const { level = "log", since_action = false, limit = 50, clear = false } = params;
+  const since = since_action ? consoleMark : 0;
+  const min = LEVEL_RANK[normalizeLevel(level)];
+  const entries = (consoleLogs.get(tab.id) || [])
+    .filter((e) => e.seq > since && LEVEL_RANK[e.level] >= min)
+    .slice(-limit)
+    .map(({ seq, ...entry }) => entry);
+  if (clear) consoleLogs.delete(tab.id);
+  return { id, success: true, result: JSON.stringify(entries) };
+}
+
+async function handleDialog(id, tab, params) {
+  const dialog = openDialogs.get(tab.id);
+  if (!dialog) return { id, success: false, error: "No dialog is open" };
+  const accept = params.action !== "dismiss";
+  await chrome.debugger.sendCommand({ tabId: tab.id }, "Page.handleJavaScriptDialog", {
+    accept,
+    ...(params.prompt_text != null ? { promptText: String(params.prompt_text) } : {}),
+  });
+  return {
+    id,
+    success: true,
+    result: `${accept ? "Accepted" : "Dismissed"} the ${describeDialog(dialog)}`,
+  };
+}
+
+// Page script can't set a file input's files; DOM.setFileInputFiles can.
+// Main frame only.
+async function handleUpload(id, tab, params) {
+  const { selector, files = [] } = params;
+  if (!selector)
+    return { id, success: false, error: "Provide 'ref', 'selector', 'label' or 'text'" };
+  await injectDom(tab);
+  // CDP numbers frames differently from the extension: mark the input from
+  // the selected frame, then find the mark in the debugger's DOM tree.
+  const token = crypto.randomUUID();
+  const results = await chrome.scripting.executeScript({
+    target: scriptTarget(tab),
+    func: (sel, mark) => {
+      try {
+        const input = window.__zb.fileInput(sel);
+        input.setAttribute("data-zb-upload", mark);
+        window.__zbUpload = input;
+        return { ok: true };
+      } catch (e) {
+        return { error: e.message };
+      }
+    },
+    args: [selector, token],
+    world: "MAIN",
+  });
+  const marked = results[0]?.result || { error: "no result" };
+  if (marked.error) return { id, success: false, error: marked.error };
+  try {
+    return await withDebugger(tab, async (target) => {
+      const { root } = await chrome.debugger.sendCommand(target, "DOM.getDocument", {
+        depth: -1,
+        pierce: true,
+      });
+      const input = findNode(root, "data-zb-upload", token);
+      if (!input)
+        return {
+          id,
+          success: false,
+          error: "Can't reach file inputs in this cross-origin frame — use the CDP backend",
+        };
+      await chrome.debugger.sendCommand(target, "DOM.setFileInputFiles", {
+        files,
+        nodeId: input.nodeId,
+      });
+      const names = files.map(baseName);
+      const what =
+        names.length === 1 ? `1 file: ${names[0]}` : `${names.length} files: ${names.join(", ")}`;
+      return { id, success: true, result: `Attached ${what}` };
+    });
+  } finally {
+    await chrome.scripting
+      .executeScript({
+        target: scriptTarget(tab),
+        func: () => {
+          window.__zbUpload?.removeAttribute("data-zb-upload");
+          delete window.__zbUpload;
+        },
+        world: "MAIN",
+      })
+      .catch(() => {});
+  }
+}
+
+// The node in a DOM.getDocument tree (pierced into frames and shadow
+// roots) whose attribute `name` is `value`, or null.
+function findNode(node, name, value) {
+  const attrs = node.attributes || [];
+  for (let i = 0; i < attrs.length; i += 2) {
+    if (attrs[i] === name && attrs[i + 1] === value) return node;
+  }
+  const children = [
+    ...(node.children || []),
+    ...(node.shadowRoots || []),
+    ...(node.contentDocument ? [node.contentDocument] : []),
+  ];
+  for (const child of children) {
+    const found = findNode(child, name, value);
+    if (found) return found;
+  }
+  return null;
+}
+
+async function handleWaitForDownload(id, params) {
+  const { filename, timeout_ms = 30000 } = params;
+  const timeout = Math.min(timeout_ms, 30000);
+  const since = downloadMark;
+  const deadline = Date.now() + timeout;
+  for (;;) {
+    const entry = downloads.find(
+      (d) =>
+        d.seq > since &&
+        (!filename || urlMatches(filename, d.filename) || urlMatches(filename, d.url))
+    );
+    if (entry && entry.state !== "in_progress") {
+      const [item] = await chrome.downloads.search({ id: entry.id });
+      const download = {
+        url: entry.url,
+        filename: entry.filename,
+        state: entry.state,
+        path: item?.filename || entry.path,
+        bytes: item?.bytesReceived ?? 0,
+        error: entry.error,
+      };
+      return { id, success: true, result: JSON.stringify(download) };
+    }
+    if (Date.now() >= deadline) {
+      return {
+        id,
+        success: false,
+        error: entry
+          ? `Timeout after ${timeout}ms: ${entry.filename || entry.url} still downloading`
+          : `No download started within ${timeout}ms${filename ? ` matching '${filename}'` : ""}`,
+      };
+    }
+    await new Promise((resolve) => setTimeout(resolve, 200));
+  }
+}
+
+// Same matching and JSON shape as src-tauri/src/tools/web_session.rs: a
+// site's cookies include those of its parent domains and subdomains.
+const bareDomain = (d) => d.replace(/^\./, "").toLowerCase();
+const withinDomain = (host, domain) => host === domain || host.endsWith(`.${domain}`);
+const SAME_SITE = { strict: "strict", lax: "lax", no_restriction: "none" };
+
+async function handleCookies(id, tab, params) {
+  let domain = params.domain?.trim();
+  if (!domain) {
+    const url = tab?.url || "";
+    if (!/^https?:\/\//.test(url))
+      return { id, success: false, error: `The tab (${url}) isn't a website — pass 'domain'` };
+    domain = new URL(url).hostname;
+  }
+  const site = bareDomain(domain);
+  const matching = (await chrome.cookies.getAll({})).filter((c) => {
+    const d = bareDomain(c.domain);
+    return (withinDomain(site, d) || withinDomain(d, site)) && (!params.name || c.name === params.name);
+  });
+  if (params.clear) {
+    for (const c of matching) {
+      const url = `http${c.secure ? "s" : ""}://${bareDomain(c.domain)}${c.path}`;
+      await chrome.cookies.remove({ url, name: c.name, storeId: c.storeId });
+    }
+  }
+  const cookies = matching.map((c) => ({
+    name: c.name,
+    value: c.value,
+    domain: c.domain,
+    path: c.path,
+    expires: c.session ? null : c.expirationDate,
+    http_only: c.httpOnly,
+    secure: c.secure,
+    same_site: SAME_SITE[c.sameSite] ?? null,
+  }));
+  return { id, success: true, result: JSON.stringify({ domain, cookies }) };
+}
+
+async function handleStorage(id, tab, params) {
+  const op = { key: params.key, clear: !!params.clear };
+  return await callZb(id, tab, "storage", [params.area || "local", op]);
+}
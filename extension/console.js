@@ -0,0 +1,44 @@
+// ZeptoBot console hook — runs in every frame's MAIN world at document_start.
+//
+// Forwards console messages, uncaught exceptions and unhandled rejections to
+// console_relay.js (isolated world), which hands them to the background
+// worker's per-tab console log for browser_console.
+
+(() => {
+  if (window.__zbConsoleHooked) return;
+  window.__zbConsoleHooked = true;
+
+  const describe = (value) => {
+    if (typeof value === "string") return value;
+    if (value instanceof Error) return `${value.name}: ${value.message}`;
+    try {
+      return JSON.stringify(value) ?? String(value);
+    } catch {
+      return String(value);
+    }
+  };
+  const send = (level, source, text, url, line) => {
+    try {
+      window.postMessage(
+        { __zbConsole: { level, source, text: String(text).slice(0, 500), url: url || null, line: line || null } },
+        "*"
+      );
+    } catch {
+      // Never let reporting break the page.
+    }
+  };
+
+  for (const level of ["log", "info", "warn", "error", "debug"]) {
+    const original = console[level];
+    console[level] = function (...args) {
+      send(level, "console", args.map(describe).join(" "));
+      return original.apply(this, args);
+    };
+  }
+  window.addEventListener("error", (e) => {
+    send("error", "exception", e.error ? describe(e.error) : e.message, e.filename, e.lineno);
+  });
+  window.addEventListener("unhandledrejection", (e) => {
+    send("error", "exception", `Unhandled rejection: ${describe(e.reason)}`);
+  });
+})();
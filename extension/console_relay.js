@@ -0,0 +1,5 @@
+// Relays console.js reports from the page to the background worker.
+window.addEventListener("message", (e) => {
+  if (e.source !== window || !e.data?.__zbConsole) return;
+  chrome.runtime.sendMessage({ type: "console", entry: e.data.__zbConsole }).catch(() => {});
+});
@@ -0,0 +1,1365 @@
+// ZeptoBot page-side helpers, shared by every browser backend.
+//
+// Injected into the page's MAIN world — by the extension via
+// chrome.scripting.executeScript({ files: ["dom.js"] }) and by the Rust CDP
+// backend via include_str!. Safe to inject repeatedly: the first copy wins.
+//
+// Element refs: browser_list_elements tags each element with a
+// `data-zb-ref` attribute and hands out refs like "e12:k3f9", where "k3f9"
+// is a token minted once per document. A ref whose token doesn't match the
+// current document (navigation, reload) or whose element has left the DOM
+// is reported as stale instead of silently hitting something else.
+//
+// Locators: Playwright-style { role, name, label, placeholder, text, exact,
+// nth } resolved against visible elements only. An ambiguous locator
+// reports its candidates (with refs) instead of picking the first match.
+//
+// Selectors pierce open shadow roots: a plain selector matches inside web
+// components too (light DOM first), and `host >>> inner` scopes the search
+// to the shadow tree of `host`. Every backend resolves selectors through
+// query() so refs and selectors reach the same elements.
+//
+// Form filling: fill() sets one field of any kind — text-like inputs,
+// textareas, contenteditable, <select> by value or label, checkboxes and
+// radio groups — through native setters and real clicks, so framework
+// state sees the change.
+//
+// Markdown: markdown() renders the page's main content (or one element)
+// as Markdown — headings, lists, links, emphasis, code, quotes and tables —
+// skipping hidden nodes and, for main content, navigation and sidebars.
+//
+// Extraction: extract() turns repeated page structure into typed JSON —
+// a JSON Schema whose nodes carry `selector` (relative CSS) or locator keys
+// and an optional `attribute`, or an HTML table turned into row objects.
+//
+// Storage: storage() reads, clears or fills localStorage/sessionStorage of
+// the page's origin; which values reach the agent is decided on the Rust
+// side (src-tauri/src/tools/web_session.rs).
+//
+// Actionability: before a click or type, backends poll actionable() until
+// the target is attached, visible, enabled (and editable for type), stable
+// in position and the topmost element at its centre — the same checks
+// Playwright runs — and report which check failed on timeout.
+//
+// Outline: axTree() builds AXNode-shaped JSON from the DOM for browsers
+// without a protocol accessibility tree (Firefox over WebDriver BiDi), so
+// web_snapshot.rs renders it like Chrome's Accessibility.getFullAXTree.
+
+(() => {
+  if (window.__zb) return;
+
+  const REF_ATTR = "data-zb-ref";
+  const INTERACTIVE =
+    'a, button, input, textarea, select, [role="button"], [role="link"], ' +
+    '[role="tab"], [role="checkbox"], [role="menuitem"], [onclick], [aria-label], ' +
+    "[contenteditable]";
+
+  // ---- Shadow-piercing queries -------------------------------------------
+
+  // All matches under `root`, then inside every open shadow root below it.
+  function deepQueryAll(root, selector) {
+    const out = Array.from(root.querySelectorAll(selector));
+    for (const el of root.querySelectorAll("*")) {
+      if (el.shadowRoot) out.push(...deepQueryAll(el.shadowRoot, selector));
+    }
+    return out;
+  }
+
+  function queryAll(selector) {
+    const parts = String(selector)
+      .split(">>>")
+      .map((p) => p.trim());
+    if (parts.some((p) => !p)) throw new Error(`Invalid selector '${selector}'`);
+    let roots = [document];
+    for (let i = 0; ; i++) {
+      const found = [...new Set(roots.flatMap((r) => deepQueryAll(r, parts[i])))];
+      if (i === parts.length - 1) return found;
+      roots = found.map((el) => el.shadowRoot || el);
+    }
+  }
+
+  const query = (selector) => queryAll(selector)[0] || null;
+
+  // Shadow hosts between `el` and the document, outermost first.
+  function shadowPath(el) {
+    const hosts = [];
+    for (let root = el.getRootNode(); root instanceof ShadowRoot; root = root.host.getRootNode()) {
+      hosts.unshift(root.host);
+    }
+    return hosts;
+  }
+
+  // Like Node.contains, but across shadow boundaries.
+  function composedContains(ancestor, node) {
+    for (let n = node; n; n = n.parentNode || (n instanceof ShadowRoot ? n.host : null)) {
+      if (n === ancestor) return true;
+    }
+    return false;
+  }
+
+  function refState() {
+    if (!window.__zbRefs) {
+      window.__zbRefs = {
+        token: Math.random().toString(36).slice(2, 6),
+        next: 1,
+      };
+    }
+    return window.__zbRefs;
+  }
+
+  function refFor(el) {
+    const state = refState();
+    let n = el.getAttribute(REF_ATTR);
+    if (!n) {
+      n = String(state.next++);
+      el.setAttribute(REF_ATTR, n);
+    }
+    return `e${n}:${state.token}`;
+  }
+
+  // Parse and validate a ref. Returns { selector } or { error }.
+  function checkRef(ref) {
+    const m = /^@?e(\d+):([a-z0-9]+)$/.exec(String(ref).trim());
+    if (!m) {
+      return { error: `Invalid ref '${ref}' — use a ref from browser_list_elements` };
+    }
+    const state = window.__zbRefs;
+    if (!state || state.token !== m[2]) {
+      return {
+        error: `stale ref '${ref}': the page has changed (navigation or reload) since browser_list_elements — list elements again`,
+      };
+    }
+    const selector = `[${REF_ATTR}="${m[1]}"]`;
+    const el = query(selector);
+    if (!el || !el.isConnected) {
+      return {
+        error: `stale ref '${ref}': the element is no longer on the page — list elements again`,
+      };
+    }
+    return { selector, element: el };
+  }
+
+  // ---- Locators -----------------------------------------------------------
+
+  const norm = (s) => String(s || "").replace(/\s+/g, " ").trim();
+
+  function isVisible(el) {
+    if (!el.getClientRects().length) return false;
+    const style = getComputedStyle(el);
+    return style.visibility !== "hidden" && style.visibility !== "collapse";
+  }
+
+  const INPUT_ROLES = {
+    button: "button",
+    submit: "button",
+    reset: "button",
+    image: "button",
+    checkbox: "checkbox",
+    radio: "radio",
+    range: "slider",
+    number: "spinbutton",
+    search: "searchbox",
+  };
+
+  const TAG_ROLES = {
+    BUTTON: "button",
+    SUMMARY: "button",
+    TEXTAREA: "textbox",
+    NAV: "navigation",
+    MAIN: "main",
+    HEADER: "banner",
+    FOOTER: "contentinfo",
+    ASIDE: "complementary",
+    FORM: "form",
+    DIALOG: "dialog",
+    OPTION: "option",
+    UL: "list",
+    OL: "list",
+    LI: "listitem",
+    TABLE: "table",
+    TR: "row",
+    TD: "cell",
+    TH: "columnheader",
+    H1: "heading",
+    H2: "heading",
+    H3: "heading",
+    H4: "heading",
+    H5: "heading",
+    H6: "heading",
+  };
+
+  // Explicit role, else the implicit role for common elements.
+  function roleOf(el) {
+    const explicit = norm(el.getAttribute("role")).split(" ")[0];
+    if (explicit) return explicit.toLowerCase();
+    const tag = el.tagName;
+    if (tag === "A" || tag === "AREA") return el.hasAttribute("href") ? "link" : "";
+    if (tag === "INPUT") {
+      const type = (el.getAttribute("type") || "text").toLowerCase();
+      if (type === "hidden") return "";
+      return INPUT_ROLES[type] || "textbox";
+    }
+    if (tag === "SELECT") return el.multiple || el.size > 1 ? "listbox" : "combobox";
+    if (tag === "IMG") return el.getAttribute("alt") === "" ? "presentation" : "img";
+    if (el.isContentEditable && el.parentElement && !el.parentElement.isContentEditable)
+      return "textbox";
+    return TAG_ROLES[tag] || "";
+  }
+
+  function labelledBy(el) {
+    const ids = norm(el.getAttribute("aria-labelledby"));
+    if (!ids) return "";
+    return norm(
+      ids
+        .split(" ")
+        .map((id) => document.getElementById(id)?.innerText || "")
+        .join(" ")
+    );
+  }
+
+  function labelText(el) {
+    const labels = el.labels ? Array.from(el.labels) : [];
+    return norm(labels.map((l) => l.innerText).join(" "));
+  }
+
+  // Simplified accessible name computation.
+  function accessibleName(el) {
+    const byId = labelledBy(el);
+    if (byId) return byId;
+    const aria = norm(el.getAttribute("aria-label"));
+    if (aria) return aria;
+    const tag = el.tagName;
+    if (tag === "INPUT" || tag === "TEXTAREA" || tag === "SELECT") {
+      const type = (el.getAttribute("type") || "").toLowerCase();
+      if (["button", "submit", "reset"].includes(type))
+        return norm(el.value) || (type === "submit" ? "Submit" : type === "reset" ? "Reset" : "");
+      if (type === "image") return norm(el.getAttribute("alt"));
+      return labelText(el) || norm(el.getAttribute("title")) || norm(el.getAttribute("placeholder"));
+    }
+    if (tag === "IMG") return norm(el.getAttribute("alt")) || norm(el.getAttribute("title"));
+    return norm(el.innerText || el.textContent) || norm(el.getAttribute("title"));
+  }
+
+  function matches(value, wanted, exact) {
+    const v = norm(value);
+    const w = norm(wanted);
+    return exact ? v === w : v.toLowerCase().includes(w.toLowerCase());
+  }
+
+  // All elements (visible or not) under `root` matching the locator.
+  function candidatesFor(loc, root = document) {
+    const exact = !!loc.exact;
+    const all = deepQueryAll(root, "*");
+    if (loc.role) {
+      const role = String(loc.role).toLowerCase();
+      return all.filter(
+        (el) =>
+          roleOf(el) === role &&
+          (loc.name == null || matches(accessibleName(el), loc.name, exact))
+      );
+    }
+    if (loc.label != null) {
+      return all.filter(
+        (el) =>
+          (el.labels && el.labels.length && matches(labelText(el), loc.label, exact)) ||
+          (el.hasAttribute("aria-label") && matches(el.getAttribute("aria-label"), loc.label, exact)) ||
+          (el.hasAttribute("aria-labelledby") && matches(labelledBy(el), loc.label, exact))
+      );
+    }
+    if (loc.placeholder != null) {
+      return all.filter(
+        (el) =>
+          el.hasAttribute("placeholder") &&
+          matches(el.getAttribute("placeholder"), loc.placeholder, exact)
+      );
+    }
+    if (loc.text != null) {
+      // Innermost elements whose text matches (skip ancestors of a match).
+      const hits = all.filter(
+        (el) =>
+          !["SCRIPT", "STYLE", "NOSCRIPT", "HTML", "HEAD"].includes(el.tagName) &&
+          matches(el.innerText ?? el.textContent, loc.text, exact)
+      );
+      return hits.filter((el) => !hits.some((o) => o !== el && el.contains(o)));
+    }
+    return null;
+  }
+
+  function describeLocator(loc) {
+    return ["role", "name", "label", "placeholder", "text"]
+      .filter((k) => loc[k] != null)
+      .map((k) => `${k}=${JSON.stringify(String(loc[k]))}`)
+      .concat(loc.exact ? ["exact"] : [])
+      .join(" ");
+  }
+
+  // Resolve a locator to one visible element.
+  // Returns { selector, element } or { error, kind, candidates? }.
+  function locate(loc) {
+    const found = candidatesFor(loc);
+    if (!found) {
+      return { kind: "invalid", error: "Locator needs role, label, placeholder or text" };
+    }
+    const visible = found.filter(isVisible);
+    const what = describeLocator(loc);
+    if (!visible.length) {
+      const hidden = found.length ? ` (${found.length} hidden match(es) ignored)` : "";
+      return { kind: "none", error: `No visible element matches ${what}${hidden}` };
+    }
+    let el;
+    if (loc.nth != null) {
+      const i = Number(loc.nth) < 0 ? visible.length + Number(loc.nth) : Number(loc.nth);
+      el = visible[i];
+      if (!el) {
+        return {
+          kind: "none",
+          error: `nth=${loc.nth} out of range: ${visible.length} visible match(es) for ${what}`,
+        };
+      }
+    } else if (visible.length > 1) {
+      const candidates = visible.slice(0, 10).map((c) => ({
+        ref: refFor(c),
+        role: roleOf(c) || c.tagName.toLowerCase(),
+        name: accessibleName(c).substring(0, 80),
+      }));
+      const lines = candidates
+        .map((c, i) => `  nth=${i} [${c.ref}] ${c.role} "${c.name}"`)
+        .join("\n");
+      return {
+        kind: "ambiguous",
+        error:
+          `${visible.length} visible elements match ${what} — pass nth, exact=true, ` +
+          `a more specific name, or one of these refs:\n${lines}`,
+        candidates,
+      };
+    } else {
+      el = visible[0];
+    }
+    refFor(el);
+    return { selector: `[${REF_ATTR}="${el.getAttribute(REF_ATTR)}"]`, element: el };
+  }
+
+  // ---- Actionability -----------------------------------------------------
+
+  function tagOf(el) {
+    const id = el.id ? `#${el.id}` : "";
+    const cls =
+      typeof el.className === "string" && el.className.trim()
+        ? "." + el.className.trim().split(/\s+/).slice(0, 2).join(".")
+        : "";
+    return `<${el.tagName.toLowerCase()}${id}${cls}>`;
+  }
+
+  function isEditable(el) {
+    if (el.isContentEditable) return true;
+    if (el.tagName === "TEXTAREA" || el.tagName === "SELECT") return !el.readOnly;
+    if (el.tagName !== "INPUT") return false;
+    const type = (el.getAttribute("type") || "text").toLowerCase();
+    const NON_TEXT = ["button", "submit", "reset", "image", "checkbox", "radio", "file", "hidden", "range", "color"];
+    return !NON_TEXT.includes(type) && !el.readOnly;
+  }
+
+  const nextFrame = () =>
+    new Promise((resolve) => {
+      // rAF doesn't fire in background tabs; don't hang on it.
+      requestAnimationFrame(() => resolve());
+      setTimeout(resolve, 100);
+    });
+
+  const fail = (check, error) => ({ ok: false, check, error });
+
+  // One round of actionability checks for `action` ("click" or "type").
+  // Returns { ok, x, y } with the viewport centre, or { ok: false, check, error }.
+  async function actionable(selector, action) {
+    const el = query(selector);
+    if (!el || !el.isConnected) return fail("attached", `${selector} is not attached to the page`);
+    if (!isVisible(el)) return fail("visible", `${tagOf(el)} is not visible`);
+    let rect = el.getBoundingClientRect();
+    if (rect.width === 0 || rect.height === 0)
+      return fail("visible", `${tagOf(el)} has zero size`);
+    if (el.matches(":disabled") || el.closest('[aria-disabled="true"]'))
+      return fail("enabled", `${tagOf(el)} is disabled`);
+    if (action === "type" && !isEditable(el))
+      return fail("editable", `${tagOf(el)} is not an editable field (or is read-only)`);
+
+    const inView =
+      rect.top >= 0 &&
+      rect.left >= 0 &&
+      rect.bottom <= innerHeight &&
+      rect.right <= innerWidth;
+    if (!inView) el.scrollIntoView({ block: "center", inline: "center", behavior: "instant" });
+
+    // Stable: same box across two consecutive frames.
+    await nextFrame();
+    rect = el.getBoundingClientRect();
+    await nextFrame();
+    const after = el.getBoundingClientRect();
+    if (
+      rect.x !== after.x ||
+      rect.y !== after.y ||
+      rect.width !== after.width ||
+      rect.height !== after.height
+    )
+      return fail("stable", `${tagOf(el)} is still moving (animating or layout shifting)`);
+
+    const x = Math.round(after.left + after.width / 2);
+    const y = Math.round(after.top + after.height / 2);
+    if (x < 0 || y < 0 || x >= innerWidth || y >= innerHeight)
+      return fail("in_viewport", `${tagOf(el)} could not be scrolled into view`);
+    // elementFromPoint stops at shadow hosts; follow it into their trees.
+    let hit = document.elementFromPoint(x, y);
+    while (hit && hit.shadowRoot) {
+      const inner = hit.shadowRoot.elementFromPoint(x, y);
+      if (!inner || inner === hit) break;
+      hit = inner;
+    }
+    if (!hit || !composedContains(el, hit))
+      return fail(
+        "hit_target",
+        `${tagOf(el)} is covered by ${hit ? tagOf(hit) : "nothing (outside the page)"} at (${x}, ${y})`
+      );
+    return { ok: true, x, y };
+  }
+
+  // ---- Form filling ------------------------------------------------------
+
+  const TEXT_INPUTS = [
+    "text", "email", "password", "search", "tel", "url", "number",
+    "date", "time", "datetime-local", "month", "week", "range", "color",
+  ];
+
+  function wantChecked(value) {
+    if (typeof value === "boolean") return value;
+    const v = norm(value).toLowerCase();
+    if (["true", "on", "yes", "checked", "1"].includes(v)) return true;
+    if (["false", "off", "no", "unchecked", "0", ""].includes(v)) return false;
+    return null;
+  }
+
+  // Set `value` through the prototype's setter: React and friends track the
+  // instance property and would miss a plain assignment.
+  function setNativeValue(el, value) {
+    const proto =
+      el.tagName === "TEXTAREA"
+        ? HTMLTextAreaElement.prototype
+        : el.tagName === "SELECT"
+          ? HTMLSelectElement.prototype
+          : HTMLInputElement.prototype;
+    Object.getOwnPropertyDescriptor(proto, "value").set.call(el, value);
+  }
+
+  function fireChange(el) {
+    el.dispatchEvent(new Event("input", { bubbles: true }));
+    el.dispatchEvent(new Event("change", { bubbles: true }));
+  }
+
+  const optionLabel = (o) => norm(o.label || o.text);
+
+  function fillSelect(el, value) {
+    const wanted = Array.isArray(value) ? value.map(String) : [String(value)];
+    if (wanted.length > 1 && !el.multiple)
+      throw new Error(`${tagOf(el)} takes a single value, got ${wanted.length}`);
+    const options = Array.from(el.options);
+    const picked = wanted.map((w) => {
+      const o =
+        options.find((o) => o.value === w) ||
+        options.find((o) => optionLabel(o).toLowerCase() === norm(w).toLowerCase());
+      if (!o) {
+        const known = options.slice(0, 15).map((o) => `"${optionLabel(o)}" (${o.value})`);
+        throw new Error(`No option '${w}' in ${tagOf(el)}; options: ${known.join(", ")}`);
+      }
+      return o;
+    });
+    for (const o of options) o.selected = picked.includes(o);
+    fireChange(el);
+    return `selected ${picked.map((o) => `"${optionLabel(o)}"`).join(", ")}`;
+  }
+
+  function fillCheckbox(el, value) {
+    const want = wantChecked(value);
+    if (want === null) throw new Error(`Checkbox value must be true or false, got '${value}'`);
+    const isChecked = () =>
+      el.tagName === "INPUT" ? el.checked : el.getAttribute("aria-checked") === "true";
+    if (isChecked() !== want) el.click();
+    if (isChecked() !== want)
+      throw new Error(`${tagOf(el)} did not change to ${want ? "checked" : "unchecked"}`);
+    return want ? "checked" : "unchecked";
+  }
+
+  // A radio input picks from its name group; a fieldset or role=radiogroup
+  // picks from the radios inside it. Matched by value or label.
+  function fillRadio(el, value) {
+    let radios;
+    if (roleOf(el) === "radio") {
+      if (value === true) radios = [el];
+      else if (el.name) {
+        const scope = el.form || el.getRootNode();
+        radios = Array.from(
+          scope.querySelectorAll(`input[type="radio"][name="${CSS.escape(el.name)}"]`)
+        );
+      } else radios = [el];
+    } else {
+      radios = Array.from(el.querySelectorAll('input[type="radio"], [role="radio"]'));
+    }
+    const label = (r) => labelText(r) || accessibleName(r) || norm(r.value);
+    const w = norm(value).toLowerCase();
+    const radio =
+      value === true
+        ? radios[0]
+        : radios.find((r) => r.value === String(value)) ||
+          radios.find((r) => label(r).toLowerCase() === w);
+    if (!radio) {
+      const known = radios.slice(0, 15).map((r) => `"${label(r)}"`);
+      throw new Error(`No radio option '${value}'; options: ${known.join(", ") || "none"}`);
+    }
+    if (radio.matches(":disabled")) throw new Error(`Radio option "${label(radio)}" is disabled`);
+    radio.click();
+    return `chose "${label(radio)}"`;
+  }
+
+  function fillText(el, value) {
+    if (el.readOnly) throw new Error(`${tagOf(el)} is read-only`);
+    const text = String(value);
+    el.focus();
+    if (el.isContentEditable) {
+      el.textContent = text;
+      el.dispatchEvent(new Event("input", { bubbles: true }));
+      return `filled "${text}"`;
+    }
+    setNativeValue(el, text);
+    // Date/number inputs silently drop values they can't parse.
+    if (el.value !== text) {
+      const type = el.getAttribute("type");
+      const hint = type === "date" ? " (use YYYY-MM-DD)" : type === "time" ? " (use HH:MM)" : "";
+      throw new Error(`${tagOf(el)} rejected '${text}'${hint}`);
+    }
+    fireChange(el);
+    return `filled "${text}"`;
+  }
+
+  // Fill one form field; with `submit`, submit its form afterwards.
+  // Returns { ok: true, result } or { error }.
+  function fill(selector, value, submit) {
+    const el = query(selector);
+    if (!el || !el.isConnected) throw new Error(`${selector} is not attached to the page`);
+    if (el.matches(":disabled") || el.closest('[aria-disabled="true"]'))
+      throw new Error(`${tagOf(el)} is disabled`);
+
+    const type = (el.getAttribute("type") || "text").toLowerCase();
+    const role = roleOf(el);
+    let result;
+    if (el.tagName === "SELECT") result = fillSelect(el, value);
+    else if (role === "checkbox" || role === "switch") result = fillCheckbox(el, value);
+    else if (role === "radio" || role === "radiogroup" || el.tagName === "FIELDSET")
+      result = fillRadio(el, value);
+    else if (el.isContentEditable || el.tagName === "TEXTAREA") result = fillText(el, value);
+    else if (el.tagName === "INPUT" && TEXT_INPUTS.includes(type)) result = fillText(el, value);
+    else throw new Error(`${tagOf(el)} is not a fillable form field`);
+
+    if (submit) {
+      const form = el.form || el.closest("form");
+      if (!form) throw new Error(`${result}, but ${tagOf(el)} is not in a form to submit`);
+      form.requestSubmit();
+      result += ", submitted the form";
+    }
+    return { ok: true, result };
+  }
+
+  // ---- Other interactions ------------------------------------------------
+
+  function target(selector) {
+    const el = query(selector);
+    if (!el || !el.isConnected) throw new Error(`${selector} is not attached to the page`);
+    return el;
+  }
+
+  // Synthetic hover: runs mouseover/mouseenter handlers, but CSS :hover
+  // only follows real pointer input.
+  function hover(selector) {
+    const el = target(selector);
+    el.scrollIntoView({ block: "center", inline: "center", behavior: "instant" });
+    const r = el.getBoundingClientRect();
+    const at = { clientX: r.left + r.width / 2, clientY: r.top + r.height / 2 };
+    for (const [type, Ctor, bubbles] of [
+      ["pointerover", PointerEvent, true],
+      ["pointerenter", PointerEvent, false],
+      ["mouseover", MouseEvent, true],
+      ["mouseenter", MouseEvent, false],
+      ["pointermove", PointerEvent, true],
+      ["mousemove", MouseEvent, true],
+    ]) {
+      el.dispatchEvent(new Ctor(type, { ...at, bubbles, composed: true }));
+    }
+    return { ok: true, result: `hovered ${tagOf(el)}` };
+  }
+
+  function selectOption(selector, values) {
+    const el = target(selector);
+    if (el.tagName !== "SELECT")
+      throw new Error(
+        `${tagOf(el)} is not a <select> — for custom dropdowns click it, then click the option`
+      );
+    if (el.matches(":disabled")) throw new Error(`${tagOf(el)} is disabled`);
+    return { ok: true, result: fillSelect(el, values) };
+  }
+
+  function check(selector, checked) {
+    const el = target(selector);
+    if (el.matches(":disabled") || el.closest('[aria-disabled="true"]'))
+      throw new Error(`${tagOf(el)} is disabled`);
+    const role = roleOf(el);
+    if (role === "checkbox" || role === "switch")
+      return { ok: true, result: fillCheckbox(el, checked) };
+    if (role === "radio") {
+      if (!checked) throw new Error("A radio button can't be unchecked — check another option");
+      return { ok: true, result: fillRadio(el, true) };
+    }
+    throw new Error(`${tagOf(el)} is not a checkbox, switch or radio button`);
+  }
+
+  // The <input type=file> behind an element: the input itself, the control
+  // of a <label>, or the one inside a styled upload button or drop zone.
+  function fileInput(selector) {
+    const el = target(selector);
+    const input =
+      (el.matches('input[type="file"]') && el) ||
+      (el.tagName === "LABEL" && el.control) ||
+      el.querySelector('input[type="file"]') ||
+      el.closest("label")?.control;
+    if (!input || !input.matches('input[type="file"]'))
+      throw new Error(`${tagOf(el)} is not a file input and contains none`);
+    if (input.matches(":disabled")) throw new Error(`${tagOf(input)} is disabled`);
+    return input;
+  }
+
+  function isScrollable(el) {
+    const style = getComputedStyle(el);
+    const canY = /(auto|scroll)/.test(style.overflowY) && el.scrollHeight > el.clientHeight;
+    const canX = /(auto|scroll)/.test(style.overflowX) && el.scrollWidth > el.clientWidth;
+    return canY || canX;
+  }
+
+  // Scroll the page, or the scroll container around `selector`, by
+  // direction/amount or to an edge; or bring the element into view.
+  function scroll(selector, { direction = "down", amount, to, intoView } = {}) {
+    const el = selector ? target(selector) : null;
+    if (intoView) {
+      if (!el) throw new Error("Scrolling into view needs an element");
+      el.scrollIntoView({ block: "center", inline: "nearest", behavior: "instant" });
+      const r = el.getBoundingClientRect();
+      return { ok: true, result: `scrolled ${tagOf(el)} into view at (${Math.round(r.left)}, ${Math.round(r.top)})` };
+    }
+
+    let box = el;
+    while (box && box !== document.body && !isScrollable(box)) {
+      box = box.parentElement || box.getRootNode().host || null;
+    }
+    if (!box || box === document.body) box = document.scrollingElement;
+    const name = box === document.scrollingElement ? "page" : tagOf(box);
+
+    if (to) {
+      const edges = {
+        top: { top: 0 },
+        bottom: { top: box.scrollHeight },
+        left: { left: 0 },
+        right: { left: box.scrollWidth },
+      };
+      if (!edges[to]) throw new Error(`Unknown scroll target '${to}' — use top, bottom, left or right`);
+      box.scrollTo({ ...edges[to], behavior: "instant" });
+    } else {
+      const page = box === document.scrollingElement;
+      const step = (n) => (amount != null ? Number(amount) : Math.round(n * 0.8));
+      const deltas = {
+        down: { top: step(page ? innerHeight : box.clientHeight) },
+        up: { top: -step(page ? innerHeight : box.clientHeight) },
+        right: { left: step(page ? innerWidth : box.clientWidth) },
+        left: { left: -step(page ? innerWidth : box.clientWidth) },
+      };
+      if (!deltas[direction])
+        throw new Error(`Unknown direction '${direction}' — use up, down, left or right`);
+      box.scrollBy({ ...deltas[direction], behavior: "instant" });
+    }
+    const maxX = box.scrollWidth - box.clientWidth;
+    const maxY = box.scrollHeight - box.clientHeight;
+    return {
+      ok: true,
+      result: `scrolled ${name} to (${Math.round(box.scrollLeft)}, ${Math.round(box.scrollTop)}) of (${maxX}, ${maxY})`,
+    };
+  }
+
+  // ---- Markdown extraction -----------------------------------------------
+
+  const SKIP_TAGS = new Set([
+    "SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE", "SVG", "CANVAS", "IFRAME",
+    "OBJECT", "EMBED", "HEAD", "META", "LINK", "INPUT", "SELECT", "TEXTAREA",
+  ]);
+  const CHROME =
+    "nav, aside, footer, header, dialog, [role=navigation], [role=banner], " +
+    "[role=contentinfo], [role=complementary], [role=search], button";
+
+  function skipNode(el, opts) {
+    if (SKIP_TAGS.has(el.tagName.toUpperCase())) return true;
+    if (el.getAttribute("aria-hidden") === "true") return true;
+    const style = getComputedStyle(el);
+    if (style.display === "none" || style.visibility === "hidden") return true;
+    return opts.main && el !== opts.root && el.matches(CHROME);
+  }
+
+  // Rendered children: the shadow tree if there is one, slotted nodes for <slot>.
+  function kids(node) {
+    if (node.shadowRoot) return Array.from(node.shadowRoot.childNodes);
+    if (node.tagName === "SLOT") return node.assignedNodes({ flatten: true });
+    return Array.from(node.childNodes);
+  }
+
+  function isBlock(el) {
+    const display = getComputedStyle(el).display;
+    return !display.startsWith("inline") && display !== "contents";
+  }
+
+  // Collapse runs of spaces on each line and drop blank edges.
+  const tidy = (text) =>
+    text
+      .split("\n")
+      .map((line) => line.replace(/[ \t\u00a0]+/g, " ").trim())
+      .join("\n")
+      .replace(/\n{3,}/g, "\n\n")
+      .trim();
+
+  function inlineMd(node, opts) {
+    if (node.nodeType === 3) return node.textContent.replace(/\s+/g, " ");
+    if (node.nodeType !== 1 || skipNode(node, opts)) return "";
+    const tag = node.tagName;
+    if (tag === "BR") return "\n";
+    if (tag === "IMG") {
+      const alt = norm(node.alt);
+      return alt ? `![${alt}](${node.src})` : "";
+    }
+    const inner = kids(node)
+      .map((n) => inlineMd(n, opts))
+      .join(isBlock(node) ? " " : "");
+    const text = inner.trim();
+    if (!text) return inner;
+    const [lead, trail] = [inner.match(/^\s*/)[0], inner.match(/\s*$/)[0]];
+    const wrap = (md) => lead + md + trail;
+    switch (tag) {
+      case "A": {
+        const href = node.href;
+        if (!href || href.startsWith("javascript:")) return inner;
+        return wrap(`[${text}](${href})`);
+      }
+      case "STRONG":
+      case "B":
+        return wrap(`**${text}**`);
+      case "EM":
+      case "I":
+        return wrap(`*${text}*`);
+      case "CODE":
+      case "KBD":
+      case "SAMP":
+        return wrap("`" + text + "`");
+      case "S":
+      case "DEL":
+        return wrap(`~~${text}~~`);
+      default:
+        return inner;
+    }
+  }
+
+  function listMd(list, opts, depth) {
+    const items = kids(list).filter((n) => n.nodeType === 1 && n.tagName === "LI");
+    const start = list.tagName === "OL" ? Number(list.getAttribute("start") || 1) : 0;
+    return items
+      .filter((li) => !skipNode(li, opts))
+      .map((li, i) => {
+        let text = "";
+        const nested = [];
+        for (const child of kids(li)) {
+          if (child.nodeType === 1 && (child.tagName === "UL" || child.tagName === "OL")) {
+            if (!skipNode(child, opts)) nested.push(listMd(child, opts, depth + 1));
+          } else {
+            const gap = child.nodeType === 1 && isBlock(child) ? " " : "";
+            text += gap + inlineMd(child, opts);
+          }
+        }
+        const marker = list.tagName === "OL" ? `${start + i}.` : "-";
+        const line = `${"  ".repeat(depth)}${marker} ${tidy(text).replace(/\n+/g, " ")}`;
+        return [line, ...nested].join("\n");
+      })
+      .join("\n");
+  }
+
+  function tableMd(table, opts) {
+    const rows = Array.from(table.rows)
+      .filter((r) => !skipNode(r, opts))
+      .map((r) =>
+        Array.from(r.cells).map((c) =>
+          tidy(inlineMd(c, opts)).replace(/\n+/g, " ").replace(/\|/g, "\\|")
+        )
+      )
+      .filter((cells) => cells.some(Boolean));
+    if (!rows.length) return "";
+    const width = Math.max(...rows.map((r) => r.length));
+    const line = (cells) =>
+      `| ${Array.from({ length: width }, (_, i) => cells[i] || "").join(" | ")} |`;
+    return [line(rows[0]), line(Array(width).fill("---")), ...rows.slice(1).map(line)].join("\n");
+  }
+
+  // Append the Markdown blocks of `el`'s children to `out`.
+  function blocksMd(el, opts, out) {
+    let para = "";
+    const flush = () => {
+      const text = tidy(para);
+      if (text) out.push(text);
+      para = "";
+    };
+    for (const node of kids(el)) {
+      if (node.nodeType === 3) {
+        para += node.textContent.replace(/\s+/g, " ");
+        continue;
+      }
+      if (node.nodeType !== 1 || skipNode(node, opts)) continue;
+      if (!isBlock(node)) {
+        para += inlineMd(node, opts);
+        continue;
+      }
+      flush();
+      const tag = node.tagName;
+      const heading = /^H([1-6])$/.exec(tag);
+      if (heading) {
+        const text = tidy(inlineMd(node, opts)).replace(/\n+/g, " ");
+        if (text) out.push(`${"#".repeat(Number(heading[1]))} ${text}`);
+      } else if (tag === "UL" || tag === "OL") {
+        const md = listMd(node, opts, 0);
+        if (md) out.push(md);
+      } else if (tag === "PRE") {
+        out.push("```\n" + node.innerText.replace(/\n+$/, "") + "\n```");
+      } else if (tag === "BLOCKQUOTE") {
+        const inner = [];
+        blocksMd(node, opts, inner);
+        if (inner.length)
+          out.push(inner.join("\n\n").split("\n").map((l) => `> ${l}`).join("\n"));
+      } else if (tag === "TABLE") {
+        const md = tableMd(node, opts);
+        if (md) out.push(md);
+      } else if (tag === "HR") {
+        out.push("---");
+      } else {
+        blocksMd(node, opts, out);
+      }
+    }
+    flush();
+  }
+
+  // Readability-style guess at the main content: an obvious landmark if
+  // there is one, else the block holding the most paragraph text.
+  function mainContent() {
+    for (const sel of ["main", "[role=main]", "article"]) {
+      const found = Array.from(document.querySelectorAll(sel)).filter(isVisible);
+      // Several articles are a feed or result list, not one main article.
+      if (found.length !== 1) continue;
+      if (norm(found[0].innerText).length >= 200) return found[0];
+    }
+    const scores = new Map();
+    for (const p of document.querySelectorAll("p, pre, td, li, blockquote")) {
+      const len = norm(p.innerText).length;
+      if (len < 25) continue;
+      let weight = 1;
+      for (let el = p.parentElement; el && el !== document.documentElement; el = el.parentElement) {
+        scores.set(el, (scores.get(el) || 0) + len * weight);
+        weight /= 2;
+        if (weight < 0.25) break;
+      }
+    }
+    let best = document.body;
+    let bestScore = 0;
+    for (const [el, score] of scores) {
+      const text = norm(el.innerText).length || 1;
+      const links = Array.from(el.querySelectorAll("a")).reduce(
+        (n, a) => n + norm(a.innerText).length,
+        0
+      );
+      const adjusted = score * (1 - Math.min(links / text, 0.9));
+      if (adjusted > bestScore) [best, bestScore] = [el, adjusted];
+    }
+    return best;
+  }
+
+  // Markdown for `selector`, or for the page (its main content unless
+  // `main` is false), headed by the page title and URL.
+  function markdown(selector, { main = true } = {}) {
+    if (selector) {
+      const root = query(selector);
+      if (!root) throw new Error(`Element not found: ${selector}`);
+      const out = [];
+      blocksMd(root, { main: false, root }, out);
+      return out.join("\n\n");
+    }
+    const root = main ? mainContent() : document.body;
+    const out = [];
+    blocksMd(root, { main, root }, out);
+    return [`Title: ${document.title}\nURL: ${location.href}`, ...out].join("\n\n");
+  }
+
+  // ---- Structured extraction ---------------------------------------------
+
+  const LOCATOR_KEYS = ["role", "label", "placeholder", "text"];
+
+  // Elements under `root` a schema node points at: its `selector` (CSS,
+  // "" = root itself) or locator keys; the root when it names neither.
+  function pick(root, node) {
+    if (LOCATOR_KEYS.some((k) => node[k] != null)) {
+      return candidatesFor(node, root).filter(isVisible);
+    }
+    if (node.selector == null || node.selector === "") return [root];
+    const parts = String(node.selector).split(">>>").map((p) => p.trim());
+    let found = [root];
+    parts.forEach((part, i) => {
+      found = found.flatMap((r) => deepQueryAll(i === 0 ? r : r.shadowRoot || r, part));
+    });
+    return [...new Set(found)];
+  }
+
+  const NUMBER = /-?\d[\d,]*(\.\d+)?|-?\.\d+/;
+
+  function scalar(el, node) {
+    const type = node.type || "string";
+    const attr = node.attribute || node.attr;
+    if (type === "boolean") {
+      if (attr) return el.hasAttribute(attr) && el.getAttribute(attr) !== "false";
+      if ("checked" in el) return el.checked;
+      if (el.hasAttribute("aria-checked")) return el.getAttribute("aria-checked") === "true";
+      return true;
+    }
+    if (node.format === "uri" || node.format === "url" || type === "url") {
+      const raw = attr
+        ? el.getAttribute(attr)
+        : el.getAttribute("href") ?? el.getAttribute("src") ?? el.querySelector("a[href]")?.getAttribute("href");
+      if (raw == null) return null;
+      try {
+        return new URL(raw, document.baseURI).href;
+      } catch {
+        return raw;
+      }
+    }
+    const text = attr
+      ? el.getAttribute(attr)
+      : "value" in el && ["INPUT", "SELECT", "TEXTAREA"].includes(el.tagName)
+        ? el.value
+        : norm(el.innerText ?? el.textContent);
+    if (text == null) return null;
+    if (type === "number" || type === "integer") {
+      const m = NUMBER.exec(text);
+      if (!m) return null;
+      const n = Number(m[0].replace(/,/g, ""));
+      return type === "integer" ? Math.round(n) : n;
+    }
+    return text;
+  }
+
+  const schemaType = (node) =>
+    node.type || (node.properties ? "object" : node.items ? "array" : "string");
+
+  // Value of schema `node` under `parent`: arrays map `items` over every
+  // match, objects resolve each property inside the first match, scalars
+  // read and coerce it. `missing` counts nulls per property path.
+  function resolve(parent, node, missing, path, limit) {
+    const type = schemaType(node);
+    if (type === "array") {
+      const items = node.items || { type: "string" };
+      return pick(parent, node)
+        .slice(0, limit)
+        .map((el) => resolve(el, items, missing, path, limit));
+    }
+    const el = pick(parent, node)[0];
+    let value = null;
+    if (el && type === "object") {
+      value = {};
+      for (const [key, prop] of Object.entries(node.properties || {})) {
+        value[key] = resolve(el, prop, missing, path ? `${path}.${key}` : key, limit);
+      }
+    } else if (el) {
+      value = scalar(el, node);
+    } else if (type === "boolean") {
+      value = false;
+    }
+    if (value == null && path) missing[path] = (missing[path] || 0) + 1;
+    return value;
+  }
+
+  // Rows of an HTML table as objects keyed by header text (arrays when the
+  // table has no header). Spanned cells repeat across their span.
+  function tableRows(table, limit) {
+    if (table.tagName !== "TABLE") {
+      const inner = table.querySelector("table");
+      if (!inner) throw new Error(`${tagOf(table)} is not a table and contains none`);
+      table = inner;
+    }
+    const grid = [];
+    const spans = [];
+    for (const row of Array.from(table.rows).filter(isVisible)) {
+      const cells = [];
+      let col = 0;
+      const take = () => {
+        while (spans[col] && spans[col].left > 0) {
+          cells[col] = spans[col].text;
+          spans[col].left--;
+          col++;
+        }
+      };
+      for (const cell of row.cells) {
+        take();
+        const text = norm(cell.innerText);
+        const colspan = Math.max(1, cell.colSpan || 1);
+        const rowspan = Math.max(1, cell.rowSpan || 1);
+        for (let i = 0; i < colspan; i++) {
+          cells[col] = text;
+          if (rowspan > 1) spans[col] = { text, left: rowspan - 1 };
+          col++;
+        }
+      }
+      take();
+      grid.push({ cells, header: Array.from(row.cells).every((c) => c.tagName === "TH") || row.parentElement.tagName === "THEAD" });
+    }
+    const typed = (v) => (/^-?(\d{1,3}(,\d{3})+|\d+)(\.\d+)?$/.test(v) ? Number(v.replace(/,/g, "")) : v);
+    const headerRows = grid.filter((r) => r.header);
+    if (!headerRows.length) return grid.slice(0, limit).map((r) => r.cells.map(typed));
+
+    const header = headerRows[headerRows.length - 1].cells;
+    const seen = {};
+    const keys = header.map((h, i) => {
+      let key = h || `column_${i + 1}`;
+      if (seen[key]) key = `${key}_${++seen[key]}`;
+      else seen[key] = 1;
+      return key;
+    });
+    return grid
+      .filter((r) => !r.header)
+      .slice(0, limit)
+      .map((r) => Object.fromEntries(keys.map((k, i) => [k, typed(r.cells[i] ?? "")])));
+  }
+
+  // Run an extraction spec: { schema } or { table } (selector or locator),
+  // plus `limit`. Returns the data and which fields never matched.
+  function extract(spec) {
+    const limit = Math.max(1, Math.min(Number(spec.limit) || 100, 1000));
+    if (spec.table != null) {
+      const loc = typeof spec.table === "string" ? { selector: spec.table } : spec.table;
+      const [table] = pick(document, loc).filter(isVisible);
+      if (!table) throw new Error(`No visible table matches ${JSON.stringify(spec.table)}`);
+      const rows = tableRows(table, limit);
+      return { items: rows, count: rows.length };
+    }
+    if (!spec.schema || typeof spec.schema !== "object")
+      throw new Error("Provide 'schema' (or 'items' + 'fields') or 'table'");
+    const missing = {};
+    const data = resolve(document, spec.schema, missing, "", limit);
+    const count = Array.isArray(data) ? data.length : 1;
+    // A field missing from every item is probably a wrong selector.
+    const never = Object.entries(missing)
+      .filter(([, n]) => n >= count)
+      .map(([k]) => k);
+    return { items: data, count, missing: never };
+  }
+
+  // Read, clear or fill localStorage / sessionStorage of this frame's
+  // origin. `key` narrows reading and clearing to one item; `set` is an
+  // object of items to write.
+  function storage(area, { key, clear, set } = {}) {
+    const store = area === "session" ? sessionStorage : localStorage;
+    const origin = location.origin;
+    if (set) {
+      for (const [k, v] of Object.entries(set)) store.setItem(k, String(v));
+      return { origin, area, set: Object.keys(set).length };
+    }
+    const keys = key != null ? (store.getItem(key) === null ? [] : [key]) : Object.keys(store);
+    if (clear) {
+      for (const k of keys) store.removeItem(k);
+      return { origin, area, cleared: keys.length };
+    }
+    return { origin, area, items: Object.fromEntries(keys.map((k) => [k, store.getItem(k)])) };
+  }
+
+  // ---- Accessibility outline ---------------------------------------------
+
+  // Roles whose accessible name comes from their content.
+  const NAME_FROM_CONTENT = new Set([
+    "button", "link", "heading", "cell", "columnheader", "option", "tab",
+    "menuitem", "menuitemcheckbox", "menuitemradio", "treeitem", "checkbox",
+    "radio", "switch", "tooltip",
+  ]);
+  const VALUE_ROLES = new Set(["textbox", "searchbox", "combobox", "spinbutton", "slider"]);
+  const OUTLINE_SKIP = new Set([
+    "SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE", "HEAD", "META", "LINK", "IFRAME", "FRAME",
+  ]);
+
+  function outlineName(el, role) {
+    if (NAME_FROM_CONTENT.has(role) || ["INPUT", "TEXTAREA", "SELECT", "IMG"].includes(el.tagName))
+      return accessibleName(el);
+    return labelledBy(el) || norm(el.getAttribute("aria-label"));
+  }
+
+  function outlineValue(el, role) {
+    if (!VALUE_ROLES.has(role)) return "";
+    if (el.tagName === "SELECT") return norm(el.selectedOptions[0]?.text);
+    if (el.isContentEditable) return norm(el.innerText);
+    return el.value != null ? String(el.value) : norm(el.getAttribute("aria-valuenow"));
+  }
+
+  function outlineStates(el, role) {
+    const props = [];
+    const add = (name, value) => props.push({ name, value: { value } });
+    const aria = (name) => el.getAttribute(`aria-${name}`);
+    if (["checkbox", "radio", "switch", "menuitemcheckbox", "menuitemradio"].includes(role)) {
+      if (aria("checked") === "mixed" || el.indeterminate) add("checked", "mixed");
+      else add("checked", "checked" in el && !aria("checked") ? el.checked : aria("checked") === "true");
+    }
+    if (aria("pressed") != null) add("pressed", aria("pressed"));
+    if (aria("expanded") != null) add("expanded", aria("expanded"));
+    if (role === "option" || role === "tab")
+      add("selected", el.tagName === "OPTION" ? el.selected : aria("selected") === "true");
+    if (el.disabled || aria("disabled") === "true") add("disabled", true);
+    if (el.required || aria("required") === "true") add("required", true);
+    if (el.readOnly || aria("readonly") === "true") add("readonly", true);
+    if (aria("invalid") && aria("invalid") !== "false") add("invalid", aria("invalid"));
+    if (el.tagName === "DIALOG" && el.matches(":modal")) add("modal", true);
+    if (document.activeElement === el && el !== document.body) add("focused", true);
+    if (role === "heading") add("level", Number(aria("level")) || Number(el.tagName[1]) || 2);
+    return props;
+  }
+
+  // The rendered page as AXNodes (nodeId/parentId/childIds, role, name,
+  // value, properties), interactive nodes tagged with refs keyed by their
+  // backendDOMNodeId: { nodes, refs }.
+  function axTree(roles, maxRefs) {
+    const nodes = [];
+    const refs = {};
+    let refCount = 0;
+    const add = (node, parent) => {
+      node.nodeId = String(nodes.length + 1);
+      node.childIds = [];
+      if (parent) {
+        node.parentId = parent.nodeId;
+        parent.childIds.push(node.nodeId);
+      }
+      nodes.push(node);
+      return node;
+    };
+    const walk = (dom, parent) => {
+      for (const child of kids(dom)) {
+        if (child.nodeType === Node.TEXT_NODE) {
+          const text = norm(child.textContent);
+          if (text) add({ role: { value: "StaticText" }, name: { value: text } }, parent);
+          continue;
+        }
+        if (child.nodeType !== Node.ELEMENT_NODE || OUTLINE_SKIP.has(child.tagName)) continue;
+        if (child.getAttribute("aria-hidden") === "true") continue;
+        const style = getComputedStyle(child);
+        if (style.display === "none") continue;
+        if (style.visibility === "hidden" || style.visibility === "collapse") {
+          // Hidden parents can still have visible children.
+          walk(child, parent);
+          continue;
+        }
+        const role = roleOf(child) || "generic";
+        const node = add({
+          role: { value: role },
+          name: { value: outlineName(child, role) },
+          value: { value: outlineValue(child, role) },
+          properties: outlineStates(child, role),
+        }, parent);
+        if (roles.includes(role) && refCount < maxRefs) {
+          node.backendDOMNodeId = nodes.length;
+          refs[node.backendDOMNodeId] = refFor(child);
+          refCount++;
+        }
+        // A closed <select>'s options aren't on screen; its value is.
+        if (child.tagName === "SELECT" && role === "combobox") continue;
+        if (child.tagName === "INPUT" || child.tagName === "TEXTAREA") continue;
+        walk(child, node);
+      }
+    };
+    const root = add({ role: { value: "RootWebArea" }, name: { value: norm(document.title) } }, null);
+    if (document.body) walk(document.body, root);
+    return { nodes, refs };
+  }
+
+  // Run an action, turning a thrown error into { error }.
+  function attempt(fn) {
+    try {
+      return fn();
+    } catch (e) {
+      return { error: e.message };
+    }
+  }
+
+  function describe(el) {
+    return (
+      el.innerText ||
+      el.value ||
+      el.placeholder ||
+      el.getAttribute("aria-label") ||
+      ""
+    )
+      .trim()
+      .substring(0, 80);
+  }
+
+  window.__zb = {
+    // Interactive elements, each tagged with a ref.
+    // Elements inside shadow roots carry `shadow`, the host path.
+    listElements(filter, limit = 100) {
+      const lower = (filter || "").toLowerCase();
+      const out = [];
+      for (const el of deepQueryAll(document, INTERACTIVE)) {
+        const text = describe(el);
+        if (lower && !text.toLowerCase().includes(lower)) continue;
+        const item = {
+          ref: refFor(el),
+          tag: el.tagName.toLowerCase(),
+          text,
+          type: el.type || el.getAttribute("role") || "",
+        };
+        const hosts = shadowPath(el);
+        if (hosts.length) item.shadow = hosts.map(tagOf).join(" >>> ");
+        out.push(item);
+        if (out.length >= limit) break;
+      }
+      return out;
+    },
+
+    // First element matching a (shadow-piercing) selector.
+    query(selector) {
+      return query(selector);
+    },
+
+    // name/src of the <iframe> matching `selector`, to pick its frame.
+    frameOwner(selector) {
+      const el = query(selector);
+      if (!el || !["IFRAME", "FRAME"].includes(el.tagName))
+        return { error: `No iframe matches '${selector}'` };
+      return { name: el.name || "", src: el.src || "" };
+    },
+
+    // This frame's viewport origin in top-level viewport coordinates, or
+    // null when a cross-origin parent hides it.
+    frameOffset() {
+      let x = 0;
+      let y = 0;
+      for (let w = window; w !== w.top; w = w.parent) {
+        let owner;
+        try {
+          owner = w.frameElement;
+        } catch {
+          owner = null;
+        }
+        if (!owner) return null;
+        const r = owner.getBoundingClientRect();
+        const style = w.parent.getComputedStyle(owner);
+        x += r.left + owner.clientLeft + parseFloat(style.paddingLeft);
+        y += r.top + owner.clientTop + parseFloat(style.paddingTop);
+      }
+      return { x, y };
+    },
+
+    // Form and interaction actions: each returns { ok, result } or { error }.
+    fill(selector, value, submit = false) {
+      return attempt(() => fill(selector, value, submit));
+    },
+
+    hover(selector) {
+      return attempt(() => hover(selector));
+    },
+
+    selectOption(selector, values) {
+      return attempt(() => selectOption(selector, values));
+    },
+
+    check(selector, checked = true) {
+      return attempt(() => check(selector, checked));
+    },
+
+    scroll(selector, opts) {
+      return attempt(() => scroll(selector, opts));
+    },
+
+    extract(spec) {
+      return attempt(() => ({ ok: true, result: JSON.stringify(extract(spec)) }));
+    },
+
+    markdown(selector, opts) {
+      return attempt(() => ({ ok: true, result: markdown(selector, opts) }));
+    },
+
+    storage(area, op) {
+      return attempt(() => ({ ok: true, result: JSON.stringify(storage(area, op)) }));
+    },
+
+    // AXNode-shaped outline of the page with refs, for backends without
+    // an accessibility tree: { nodes, refs }.
+    axTree(roles, maxRefs) {
+      return axTree(roles, maxRefs);
+    },
+
+    // Ref for a single element (used by browser_snapshot to link AX nodes).
+    ref(el) {
+      return el && el.nodeType === 1 ? refFor(el) : null;
+    },
+
+    // Resolve a ref to a unique CSS selector, or throw if it is stale.
+    refSelector(ref) {
+      const r = checkRef(ref);
+      if (r.error) throw new Error(r.error);
+      return r.selector;
+    },
+
+    // Resolve a locator to a unique selector: { selector } or { error, kind }.
+    locate(loc) {
+      const r = locate(loc || {});
+      return r.error ? { error: r.error, kind: r.kind } : { selector: r.selector };
+    },
+
+    // One actionability round: { ok, x, y } or { ok: false, check, error }.
+    actionable(selector, action) {
+      return actionable(selector, action).catch((e) => fail("error", e.message));
+    },
+
+    // Focus a field (or keep the focused one) before trusted typing, selecting
+    // its contents so the next key press replaces them.
+    focusField(selector, selectAll) {
+      const el = selector ? query(selector) : document.activeElement;
+      if (!el || el === document.body) return { error: "No field to type into" };
+      el.focus();
+      if (selectAll) {
+        if (typeof el.select === "function") el.select();
+        else getSelection().selectAllChildren(el);
+      }
+      return { ok: true, tag: tagOf(el) };
+    },
+
+    // Scroll an element into view and return its box in viewport
+    // coordinates, for clipping screenshots: { x, y, width, height } or { error }.
+    box(selector) {
+      return attempt(() => {
+        const el = target(selector);
+        el.scrollIntoView({ block: "center", inline: "center", behavior: "instant" });
+        const r = el.getBoundingClientRect();
+        if (!r.width || !r.height) throw new Error(`${tagOf(el)} has no size — is it visible?`);
+        return { x: r.left, y: r.top, width: r.width, height: r.height };
+      });
+    },
+
+    // The file input behind `selector`, for DOM.setFileInputFiles; throws
+    // if there is none.
+    fileInput(selector) {
+      return fileInput(selector);
+    },
+
+    // Resolve a ref to its element, or throw if it is stale.
+    resolve(ref) {
+      const r = checkRef(ref);
+      if (r.error) throw new Error(r.error);
+      return r.element;
+    },
+  };
+})();
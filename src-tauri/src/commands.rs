@@ -8,6 +8,8 @@ use zeptoclaw::agent::ZeptoAgent;
 
 use crate::services::agent::has_api_key;
 use crate::services::automation::AutomationService;
+use crate::services::recording::{self, RecordingConfig};
+use crate::services::session::{self, RunSummary};
 
 /// Shared agent state managed by Tauri. `None` when no API key is configured.
 pub struct AgentState(pub Option<ZeptoAgent>);
@@ -34,6 +36,8 @@ pub struct AgentStep {
 /// Conversation history is maintained across calls via the managed state.
 /// Emits `agent-step` events to the frontend for live progress updates.
 /// The request can be cancelled via `stop_generation`.
+/// Tool calls and screenshots are recorded under `~/.zeptobot/runs/`, plus a
+/// screen recording when `ZEPTOBOT_RECORD_FPS` is set.
 #[tauri::command]
 pub async fn send_message(
     message: String,
@@ -53,6 +57,18 @@ pub async fn send_message(
         *current = Some(token.clone());
     }
 
+    let recorder = match session::begin_run(&message) {
+        Ok(id) => RecordingConfig::from_env().and_then(|config| {
+            recording::start(&session::runs_dir().join(id), config)
+                .map_err(|e| eprintln!("Warning: Screen recording not started: {e}"))
+                .ok()
+        }),
+        Err(e) => {
+            eprintln!("Warning: Run not recorded: {e}");
+            None
+        }
+    };
+
     let result = tokio::select! {
         res = agent.chat_with_callback(&message, |tool, msg| {
             let _ = app.emit("agent-step", AgentStep {
@@ -77,6 +93,10 @@ pub async fn send_message(
         *current = None;
     }
 
+    if let Some(recorder) = recorder {
+        recorder.stop();
+    }
+    session::end_run(&result);
     result
 }
 
@@ -112,6 +132,26 @@ pub async fn get_status() -> Result<BotStatus, String> {
     })
 }
 
+/// List recorded `send_message` runs, newest first.
+#[tauri::command]
+pub async fn list_runs() -> Result<Vec<RunSummary>, String> {
+    tokio::task::spawn_blocking(session::list_runs)
+        .await
+        .map_err(|e| format!("Task panicked: {e}"))
+}
+
+/// Render a recorded run as a self-contained HTML report and return its path.
+///
+/// Exports the most recent run when `run_id` is omitted.
+#[tauri::command]
+pub async fn export_run_report(run_id: Option<String>) -> Result<String, String> {
+    recording::wait_finished().await;
+    tokio::task::spawn_blocking(move || session::export_report(run_id.as_deref()))
+        .await
+        .map_err(|e| format!("Task panicked: {e}"))?
+        .map(|path| path.display().to_string())
+}
+
 /// Execute a desktop automation action (mouse, keyboard, screen queries).
 #[tauri::command]
 pub async fn execute_automation(
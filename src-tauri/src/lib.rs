@@ -5,14 +5,15 @@ pub mod tools;
 use std::sync::Arc;
 
 use commands::{
-    clear_history, execute_automation, get_status, send_message, stop_generation, AgentState,
-    CancelState,
+    clear_history, execute_automation, export_run_report, get_status, list_runs, send_message,
+    stop_generation, AgentState, CancelState,
 };
 use services::agent::build_agent;
+use services::recording;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::TrayIconBuilder,
-    Manager,
+    Manager, RunEvent,
 };
 use tokio::sync::Mutex;
 
@@ -66,7 +67,15 @@ pub fn run() {
             get_status,
             execute_automation,
             stop_generation,
+            list_runs,
+            export_run_report,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Let a recording stopped by the last run finish its GIF.
+            if let RunEvent::Exit = event {
+                tauri::async_runtime::block_on(recording::wait_finished());
+            }
+        });
 }
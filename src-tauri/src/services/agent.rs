@@ -6,6 +6,7 @@
 use zeptoclaw::agent::ZeptoAgent;
 use zeptoclaw::{ClaudeProvider, OpenAIProvider};
 
+use crate::services::session::record_tools;
 use crate::tools::all_automation_tools;
 
 /// System prompt that tells the LLM what it can do.
@@ -23,18 +24,40 @@ PLANNING: For multi-step tasks, FIRST reply with a brief 1-3 line plan of what y
 (e.g. \"I'll open Gmail, compose a new email to X, and draft the body.\"). \
 Then immediately start executing. The user should see your plan before tools run.\n\n\
 TOOL TIERS (prefer higher tiers):\n\
-T1 — Browser CDP (web apps): browser_connect, browser_click, browser_type, browser_read, \
-browser_list_elements, browser_navigate, browser_js, browser_wait_for\n\
+T1 — Browser CDP (web apps): browser_connect, browser_click, browser_type, browser_fill_form, browser_read, browser_extract, \
+browser_list_elements, browser_snapshot, browser_screenshot, browser_navigate, browser_js, browser_wait_for, browser_wait_for_request, browser_wait_for_download, browser_console, browser_dialog, \
+browser_cookies, browser_storage, browser_session, browser_hover, browser_select_option, browser_check, browser_upload, browser_press_key, browser_scroll, \
+browser_go_back, browser_go_forward, browser_reload, browser_tabs, browser_switch_tab, browser_new_tab, browser_close_tab\n\
 T2 — AppleScript (native apps): open_app, activate_app, run_applescript\n\
 T3 — Accessibility API (native apps): find_element, click_element, set_value, read_value\n\
-T4 — Vision (last resort): take_screenshot\n\
+T4 — Vision (last resort): find_image, save_reference_image, wait_for_screen_change, \
+wait_until_stable, take_screenshot\n\
 T5 — Raw input (absolute last resort): move_mouse, click, type_text, key_press\n\n\
 SPEED RULES — VERY IMPORTANT:\n\
-- NEVER use take_screenshot for web apps. Use browser_read or browser_list_elements instead.\n\
+- NEVER use take_screenshot for web apps. Use browser_read or browser_list_elements instead (browser_screenshot when you must see the page).\n\
 - NEVER use move_mouse + click for web apps. Use browser_click instead.\n\
 - NEVER use type_text for web apps. Use browser_type instead.\n\
 - Use browser_wait_for instead of blind wait + screenshot to confirm page loaded.\n\
 - Use browser_js for complex multi-step actions in a single call.\n\
+- Unfamiliar page or custom widgets? browser_snapshot interactive_only=true gives the full outline with refs.\n\
+- Prefer ref='...' from browser_list_elements over guessed CSS selectors; if a ref is stale, list elements again.\n\
+- Hover menus, <select> dropdowns, checkboxes, shortcuts, scrolling and back/forward have their own tools \
+(browser_hover, browser_select_option, browser_check, browser_press_key, browser_scroll, browser_go_back) — \
+don't reach for browser_js for these.\n\
+- Reading an article, docs or search results? browser_read format='markdown' — when it says there is more, \
+call again with the offset it gives.\n\
+- Need a list, table or search results as data? browser_extract (table=..., or items + fields) instead of bespoke browser_js.\n\
+- Several fields on one form? One browser_fill_form call instead of a browser_type per field.\n\
+- No ref yet? Use locators: role='button' name='Send', label='Email', placeholder='Search'. If several match, pick from the listed candidates.\n\
+- Elements inside an iframe are listed under their frame: pass frame='N' together with their ref. Reach into shadow DOM with `>>>`, e.g. selector='my-app >>> button.save'.\n\
+- To work in another tab, get its id from browser_tabs and pass tab='<id>' (or browser_switch_tab).\n\
+- Single-page app still loading data? browser_navigate wait_until='networkidle', or browser_wait_for network_idle=true after a click.\n\
+- Need the data behind a click or search? browser_wait_for_request url='/api/...' capture_body=true reads the JSON response directly.\n\
+- An action seemed to do nothing? Read the \"Page errors\" in its result, or browser_console level='error'.\n\
+- A result says a dialog opened? The page is frozen until you answer it with browser_dialog — read its text before accepting.\n\
+- Attach files with browser_upload (never click the file picker); after clicking a download link, browser_wait_for_download gives the saved path.\n\
+- Need to see a chart, image or layout on a web page? browser_screenshot (selector=... for one element), not take_screenshot.\n\
+- Is the user logged in? browser_cookies (action='check', name='<login cookie>') instead of loading the page and looking for a sign-in button.\n\
 - Skip unnecessary waits. CDP tools wait for the DOM automatically.\n\
 - Only take_screenshot if you truly cannot see the page any other way (native app debugging).\n\n\
 CRITICAL — NATIVE APPS vs WEB APPS:\n\
@@ -45,9 +68,10 @@ STANDARD WORKFLOW (native apps):\n\
 1. open_app or activate_app\n\
 2. wait 500-1000ms\n\
 3. find_element to locate the target\n\
-4. click_element or set_value to interact\n\n\
+4. click_element or set_value to interact\n\
+5. wait_for_screen_change or wait_until_stable (not blind wait) if the UI needs time to react\n\n\
 STANDARD WORKFLOW (web apps — FAST PATH):\n\
-1. browser_connect (one-time, ensures Chrome is connected)\n\
+1. browser_connect (one-time, ensures Chrome is connected; browser='firefox' if the user wants Firefox)\n\
 2. browser_navigate url='...'\n\
 3. browser_wait_for selector='...' or text='...' (confirm page loaded)\n\
 4. browser_click / browser_type as needed (NO mouse, NO screenshot)\n\
@@ -60,7 +84,8 @@ FALLBACK CHAIN:\n\
 - browser_click fails? → Try browser_js with document.querySelector().click()\n\
 - browser_wait_for times out? → Try browser_list_elements to see what's on the page\n\
 - browser_connect fails? → Chrome will be relaunched automatically\n\
-- For native apps: click_element fails? → try key_press shortcut\n\n\
+- For native apps: click_element fails? → try key_press shortcut\n\
+- Unlabeled icon (untitled AXButton)? → find_image with a saved reference, then move_mouse + click\n\n\
 KEY RULES:\n\
 - After open_app, ALWAYS activate_app before interacting\n\
 - Each tool call executes one at a time\n\
@@ -79,10 +104,9 @@ Gmail (WEB APP — CDP, fast path):\n\
 3. browser_wait_for text='Compose'\n\
 4. browser_click text='Compose'\n\
 5. browser_wait_for selector='input[name=to],textarea[name=to],[aria-label*=To]'\n\
-6. browser_type selector='input[name=to]' value='recipient@example.com'\n\
-7. browser_type selector='input[name=subjectbox]' value='Subject'\n\
-8. browser_type selector='div[aria-label=\"Message Body\"]' value='Body text'\n\
-9. browser_click text='Send' (or just close for draft)\n\n\
+6. browser_fill_form fields=[{selector:'input[name=to]', value:'recipient@example.com'}, \
+{selector:'input[name=subjectbox]', value:'Subject'}, {selector:'div[aria-label=\"Message Body\"]', value:'Body text'}]\n\
+7. browser_click text='Send' (or just close for draft)\n\n\
 Google Search (WEB APP — CDP):\n\
 1. browser_connect → browser_navigate url='https://google.com'\n\
 2. browser_wait_for selector='textarea[name=q]'\n\
@@ -91,15 +115,16 @@ Google Search (WEB APP — CDP):\n\
 Perplexity / AI search (WEB APP — CDP):\n\
 1. browser_connect → browser_navigate url='https://www.perplexity.ai'\n\
 2. browser_click selector='textarea' (focus the search box)\n\
-3. browser_type selector='textarea' value='YOUR QUERY HERE'\n\
-4. browser_js code='document.querySelector(\"textarea\").dispatchEvent(new KeyboardEvent(\"keydown\",{key:\"Enter\",code:\"Enter\",keyCode:13,bubbles:true}))'\n\
-5. browser_wait_for text='Sources' timeout_ms=15000 (wait for answer)\n\
-6. browser_read page_text=true (read the answer)\n\n\
+3. browser_type selector='textarea' value='YOUR QUERY HERE' submit=true trusted=true\n\
+4. browser_wait_for text='Sources' timeout_ms=15000 (wait for answer)\n\
+5. browser_read page_text=true (read the answer)\n\n\
 REACT / MODERN SPA RULE — CRITICAL:\n\
 Most modern web apps (Perplexity, Gmail, Twitter, etc.) use React/Vue/Angular. \
 Setting element.value via browser_js does NOT work — the framework ignores direct DOM mutations. \
-ALWAYS use browser_type for text input (it sends real keystrokes). Only use browser_js for clicks, \
-reading data, or submitting forms. For submit, dispatch a keyboard Enter event or click the button.\n\n\
+ALWAYS use browser_type for text input. If the page ignores what you typed or clicked (React state \
+not updating, canvas editors, buttons that check for real clicks), repeat with trusted=true to send \
+real input events. Only use browser_js for reading data or submitting forms; otherwise submit with \
+browser_type submit=true or by clicking the button.\n\n\
 General web interaction:\n\
 1. browser_connect → browser_navigate\n\
 2. browser_wait_for (confirm loaded)\n\
@@ -122,7 +147,7 @@ const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
 /// Model can be overridden via `ZEPTOBOT_MODEL` env var.
 pub fn build_agent() -> Result<ZeptoAgent, String> {
     let mut builder = ZeptoAgent::builder()
-        .tools(all_automation_tools())
+        .tools(record_tools(all_automation_tools()))
         .system_prompt(SYSTEM_PROMPT)
         .max_iterations(20);
 
@@ -1,2 +1,4 @@
 pub mod agent;
 pub mod automation;
+pub mod recording;
+pub mod session;
@@ -0,0 +1,247 @@
+//! Opt-in screen recorder for agent runs.
+//!
+//! Enabled by setting `ZEPTOBOT_RECORD_FPS` (e.g. `2`). While a
+//! `send_message` run is active, frames are captured at that rate — with
+//! privacy redaction applied and the current tool name and cursor drawn on
+//! top — and written next to the run's session log:
+//!
+//! ```text
+//! ~/.zeptobot/runs/<run-id>/recording/
+//!   frames/0001.png ...
+//!   manifest.json    fps + per-frame offset, tool and cursor position
+//!   recording.gif    unless ZEPTOBOT_RECORD_FORMAT=frames
+//! ```
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{imageops, Delay, Frame, RgbaImage};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::services::session;
+use crate::tools::{imaging, redact, screen};
+
+/// Frames wider than this are downscaled to keep recordings small.
+const MAX_FRAME_WIDTH: u32 = 1280;
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct RecordingConfig {
+    pub fps: f64,
+    /// Also encode an animated GIF (otherwise only frames + manifest).
+    pub gif: bool,
+}
+
+impl RecordingConfig {
+    /// Read `ZEPTOBOT_RECORD_FPS` / `ZEPTOBOT_RECORD_FORMAT`.
+    /// Returns `None` when recording is off.
+    pub fn from_env() -> Option<Self> {
+        let fps: f64 = std::env::var("ZEPTOBOT_RECORD_FPS").ok()?.trim().parse().ok()?;
+        if fps <= 0.0 {
+            return None;
+        }
+        let gif = !matches!(
+            std::env::var("ZEPTOBOT_RECORD_FORMAT").as_deref(),
+            Ok("frames")
+        );
+        Some(Self {
+            fps: fps.clamp(0.2, 10.0),
+            gif,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Manifest
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize)]
+struct FrameEntry {
+    file: String,
+    /// Milliseconds since the recording started.
+    offset_ms: u64,
+    tool: Option<String>,
+    /// Cursor position in screen points.
+    cursor: (f64, f64),
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    fps: f64,
+    frames: Vec<FrameEntry>,
+}
+
+// ---------------------------------------------------------------------------
+// Recorder
+// ---------------------------------------------------------------------------
+
+/// Recordings still writing their manifest and GIF after [`Recorder::stop`].
+static FINISHING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// A running recorder. Call [`Recorder::stop`] to finish and encode.
+pub struct Recorder {
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+/// Start recording into `<run_dir>/recording`.
+pub fn start(run_dir: &Path, config: RecordingConfig) -> Result<Recorder, String> {
+    let dir = run_dir.join("recording");
+    std::fs::create_dir_all(dir.join("frames"))
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    let cancel = CancellationToken::new();
+    let task = tokio::spawn(record_loop(dir, config, cancel.clone()));
+    Ok(Recorder { cancel, task })
+}
+
+impl Recorder {
+    /// Stop capturing. The manifest (and GIF) are written in the background;
+    /// use [`wait_finished`] where they must be on disk.
+    pub fn stop(self) {
+        self.cancel.cancel();
+        let mut finishing = FINISHING.lock().unwrap_or_else(|e| e.into_inner());
+        finishing.retain(|task| !task.is_finished());
+        finishing.push(self.task);
+    }
+}
+
+/// Wait until every stopped recording has been written.
+pub async fn wait_finished() {
+    let tasks = std::mem::take(&mut *FINISHING.lock().unwrap_or_else(|e| e.into_inner()));
+    for task in tasks {
+        if let Err(e) = task.await {
+            warn!("[Recorder] Task failed: {e}");
+        }
+    }
+}
+
+async fn record_loop(dir: PathBuf, config: RecordingConfig, cancel: CancellationToken) {
+    let started = Instant::now();
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / config.fps));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut frames: Vec<FrameEntry> = Vec::new();
+    let mut warned = false;
+
+    info!("[Recorder] Recording at {} fps to {}", config.fps, dir.display());
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        let file = format!("{:04}.png", frames.len() + 1);
+        let path = dir.join("frames").join(&file);
+        let tool = session::current_tool();
+        let label = tool.clone();
+        let captured =
+            tokio::task::spawn_blocking(move || capture_frame(&path, label.as_deref()))
+                .await
+                .unwrap_or_else(|e| Err(format!("Task panicked: {e}")));
+        match captured {
+            Ok(cursor) => frames.push(FrameEntry {
+                file,
+                offset_ms: started.elapsed().as_millis() as u64,
+                tool,
+                cursor,
+            }),
+            Err(e) if !warned => {
+                warn!("[Recorder] Frame skipped: {e}");
+                warned = true;
+            }
+            Err(_) => {}
+        }
+    }
+
+    let fps = config.fps;
+    let gif = config.gif;
+    let count = frames.len();
+    let finished = tokio::task::spawn_blocking(move || finish(&dir, fps, frames, gif))
+        .await
+        .unwrap_or_else(|e| Err(format!("Task panicked: {e}")));
+    match finished {
+        Ok(()) => info!("[Recorder] Saved {count} frame(s)"),
+        Err(e) => warn!("[Recorder] {e}"),
+    }
+}
+
+/// Capture one redacted frame with overlays and save it as PNG.
+/// Returns the cursor position in screen points.
+fn capture_frame(path: &Path, tool: Option<&str>) -> Result<(f64, f64), String> {
+    let (capture, _) = redact::capture_redacted(None)?;
+    let cursor = autopilot::mouse::location();
+
+    let mut image = capture.image;
+    let mut scale = capture.scale;
+    if image.width() > MAX_FRAME_WIDTH {
+        let factor = MAX_FRAME_WIDTH as f64 / image.width() as f64;
+        let height = (image.height() as f64 * factor).round().max(1.0) as u32;
+        image = imageops::resize(&image, MAX_FRAME_WIDTH, height, imageops::FilterType::Triangle);
+        scale *= factor;
+    }
+
+    let cx = ((cursor.x - capture.origin.0) * scale).round() as i64;
+    let cy = ((cursor.y - capture.origin.1) * scale).round() as i64;
+    imaging::draw_cursor(&mut image, cx, cy, 10);
+    let label = match tool {
+        Some(t) => format!("{t}  ({:.0},{:.0})", cursor.x, cursor.y),
+        None => format!("thinking  ({:.0},{:.0})", cursor.x, cursor.y),
+    };
+    imaging::draw_label(&mut image, 8, 8, &label, 2);
+
+    std::fs::write(path, screen::encode_png(&image)?)
+        .map_err(|e| format!("Failed to save frame: {e}"))?;
+    Ok((cursor.x, cursor.y))
+}
+
+/// Write the manifest and, if requested, the animated GIF.
+fn finish(dir: &Path, fps: f64, frames: Vec<FrameEntry>, gif: bool) -> Result<(), String> {
+    let manifest = Manifest { fps, frames };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join("manifest.json"), json)
+        .map_err(|e| format!("Failed to write manifest: {e}"))?;
+
+    if gif && !manifest.frames.is_empty() {
+        encode_gif(dir, &manifest)?;
+    }
+    Ok(())
+}
+
+fn encode_gif(dir: &Path, manifest: &Manifest) -> Result<(), String> {
+    let path = dir.join("recording.gif");
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| format!("GIF encode failed: {e}"))?;
+
+    let default_ms = (1000.0 / manifest.fps).round() as u64;
+    for (i, entry) in manifest.frames.iter().enumerate() {
+        let image: RgbaImage = image::open(dir.join("frames").join(&entry.file))
+            .map_err(|e| format!("Failed to read frame {}: {e}", entry.file))?
+            .to_rgba8();
+        // Show each frame until the next one was captured, so playback
+        // runs at real time even when captures were slower than the fps.
+        let delay_ms = manifest
+            .frames
+            .get(i + 1)
+            .map(|next| next.offset_ms.saturating_sub(entry.offset_ms))
+            .unwrap_or(default_ms)
+            .max(20);
+        let frame = Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(delay_ms as u32, 1));
+        encoder
+            .encode_frame(frame)
+            .map_err(|e| format!("GIF encode failed: {e}"))?;
+    }
+    Ok(())
+}
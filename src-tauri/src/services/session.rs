@@ -0,0 +1,676 @@
+//! Per-run session archive and HTML report.
+//!
+//! Every `send_message` run gets a directory under `~/.zeptobot/runs/`:
+//!
+//! ```text
+//! ~/.zeptobot/runs/<run-id>/
+//!   events.jsonl   one JSON event per line (start, tool, image, end)
+//!   images/        screenshots attached during the run (0001.png, ...)
+//!   report.html    rendered on demand by `export_run_report`
+//!   recording/     optional screen recording (see `recording`)
+//! ```
+//!
+//! Events are appended as they happen, so a run that crashes or is stopped
+//! still leaves a usable log. Tools are recorded by wrapping them in
+//! [`RecordedTool`]; tools that capture images call [`record_image`].
+//!
+//! Reports get attached to bug reports, so the log never holds what the
+//! user typed or the browser's logins: typed values are replaced by their
+//! length, and results that can hold secrets (cookies, storage, page script
+//! output, captured response bodies, field values read from apps) are left
+//! out (see `scrub_args` and `result_unrecorded`).
+//! Run directories and files are readable by the user only.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+use zeptoclaw::tools::ToolOutput;
+use zeptoclaw::{Result as ZeptoResult, Tool, ToolCategory, ToolContext};
+
+/// Tool results longer than this are truncated in the log.
+const MAX_RESULT_CHARS: usize = 64 * 1024;
+
+/// Arguments holding text the user types into apps and pages, per tool.
+/// `browser_fill_form` nests its values in `fields[].value`.
+const TYPED_ARGS: &[(&str, &str)] = &[
+    ("type_text", "text"),
+    ("set_value", "value"),
+    ("browser_type", "value"),
+    ("browser_select_option", "values"),
+    ("browser_dialog", "prompt_text"),
+];
+
+/// Tools whose results can carry secrets: cookies, storage, whatever page
+/// script returns, and field values read back from apps.
+/// `browser_wait_for_request` only with `capture_body` (see `result_unrecorded`).
+const UNRECORDED_RESULTS: &[&str] = &[
+    "browser_cookies",
+    "browser_storage",
+    "browser_js",
+    "read_value",
+];
+
+/// The run currently being recorded, if any.
+static ACTIVE: Mutex<Option<Arc<RunLog>>> = Mutex::new(None);
+
+// ---------------------------------------------------------------------------
+// Events
+// ---------------------------------------------------------------------------
+
+/// One line of `events.jsonl`. Offsets are milliseconds since the run started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RunEvent {
+    Start {
+        message: String,
+        /// Wall-clock start, milliseconds since the Unix epoch.
+        started_at: u64,
+    },
+    Tool {
+        tool: String,
+        args: Value,
+        result: String,
+        is_error: bool,
+        offset_ms: u64,
+        duration_ms: u64,
+    },
+    Image {
+        file: String,
+        label: String,
+        offset_ms: u64,
+    },
+    End {
+        ok: bool,
+        /// Final response, or the error message when `ok` is false.
+        output: String,
+        offset_ms: u64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// Recording
+// ---------------------------------------------------------------------------
+
+struct RunLog {
+    dir: PathBuf,
+    started: Instant,
+    file: Mutex<File>,
+    image_seq: AtomicU32,
+    /// Tool currently executing, for the screen recorder's overlay.
+    current_tool: Mutex<Option<String>>,
+}
+
+impl RunLog {
+    fn offset_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn append(&self, event: &RunEvent) {
+        let Ok(line) = serde_json::to_string(event) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{line}") {
+            warn!("[Session] Failed to write event: {e}");
+        }
+    }
+}
+
+/// Create `dir` (and missing parents) readable by the user only.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+/// Open `path` for writing, creating it readable by the user only.
+fn open_private(path: &Path, append: bool) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn active() -> Option<Arc<RunLog>> {
+    ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Directory holding all recorded runs.
+pub fn runs_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    PathBuf::from(home).join(".zeptobot").join("runs")
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Start recording a new run. Any run still active is ended first.
+///
+/// Returns the run id (the directory name under [`runs_dir`]).
+pub fn begin_run(message: &str) -> Result<String, String> {
+    let started_at = unix_ms();
+    let id = format!("run-{started_at}");
+    let dir = runs_dir().join(&id);
+    create_private_dir(&dir.join("images"))
+        .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let file = open_private(&dir.join("events.jsonl"), true)
+        .map_err(|e| format!("Failed to open run log: {e}"))?;
+
+    let log = Arc::new(RunLog {
+        dir,
+        started: Instant::now(),
+        file: Mutex::new(file),
+        image_seq: AtomicU32::new(1),
+        current_tool: Mutex::new(None),
+    });
+    log.append(&RunEvent::Start {
+        message: message.to_string(),
+        started_at,
+    });
+
+    let previous = ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(log);
+    if let Some(prev) = previous {
+        prev.append(&RunEvent::End {
+            ok: false,
+            output: "Superseded by a new run".into(),
+            offset_ms: prev.offset_ms(),
+        });
+    }
+    info!("[Session] Recording {id}");
+    Ok(id)
+}
+
+/// Finish the active run with its final response or error.
+pub fn end_run(result: &Result<String, String>) {
+    let Some(log) = ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    let (ok, output) = match result {
+        Ok(r) => (true, r.clone()),
+        Err(e) => (false, e.clone()),
+    };
+    log.append(&RunEvent::End {
+        ok,
+        output,
+        offset_ms: log.offset_ms(),
+    });
+}
+
+/// Name of the tool the active run is currently executing, if any.
+pub fn current_tool() -> Option<String> {
+    active()?
+        .current_tool
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Attach a PNG to the active run and return where it was saved. No-op
+/// when nothing is being recorded.
+pub fn record_image(png: &[u8], label: &str) -> Option<PathBuf> {
+    let log = active()?;
+    let file = format!("{:04}.png", log.image_seq.fetch_add(1, Ordering::Relaxed));
+    let path = log.dir.join("images").join(&file);
+    if let Err(e) = open_private(&path, false).and_then(|mut f| f.write_all(png)) {
+        warn!("[Session] Failed to save image: {e}");
+        return None;
+    }
+    log.append(&RunEvent::Image {
+        file,
+        label: label.to_string(),
+        offset_ms: log.offset_ms(),
+    });
+    Some(path)
+}
+
+/// Wraps a tool so each call is written to the active run.
+pub struct RecordedTool {
+    inner: Box<dyn Tool>,
+}
+
+/// Wrap every tool for recording.
+pub fn record_tools(tools: Vec<Box<dyn Tool>>) -> Vec<Box<dyn Tool>> {
+    tools
+        .into_iter()
+        .map(|inner| Box::new(RecordedTool { inner }) as Box<dyn Tool>)
+        .collect()
+}
+
+#[async_trait]
+impl Tool for RecordedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters(&self) -> Value {
+        self.inner.parameters()
+    }
+
+    fn category(&self) -> ToolCategory {
+        self.inner.category()
+    }
+
+    async fn execute(&self, args: Value, ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let Some(log) = active() else {
+            return self.inner.execute(args, ctx).await;
+        };
+
+        let offset_ms = log.offset_ms();
+        let started = Instant::now();
+        *log.current_tool.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(self.inner.name().to_string());
+        let output = self.inner.execute(args.clone(), ctx).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        log.current_tool
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+
+        let (mut result, is_error) = match &output {
+            Ok(out) => (out.for_llm.clone(), out.is_error),
+            Err(e) => (format!("{e}"), true),
+        };
+        let tool = self.inner.name();
+        if !is_error && result_unrecorded(tool, &args) {
+            result = "[not recorded: may contain secrets]".into();
+        }
+        log.append(&RunEvent::Tool {
+            tool: tool.to_string(),
+            args: scrub_args(tool, args),
+            result: truncate(result, MAX_RESULT_CHARS),
+            is_error,
+            offset_ms,
+            duration_ms,
+        });
+        output
+    }
+}
+
+/// Whether a successful `tool` call's result is left out of the log.
+/// Captured response bodies can hold tokens and account data.
+fn result_unrecorded(tool: &str, args: &Value) -> bool {
+    UNRECORDED_RESULTS.contains(&tool)
+        || (tool == "browser_wait_for_request"
+            && args.get("capture_body").and_then(Value::as_bool) == Some(true))
+}
+
+/// `args` with the text the user typed replaced by its length.
+fn scrub_args(tool: &str, mut args: Value) -> Value {
+    fn scrub(value: &mut Value) {
+        *value = match value {
+            Value::String(s) => Value::String(format!("[{} chars]", s.chars().count())),
+            Value::Array(items) => Value::String(format!("[{} values]", items.len())),
+            Value::Null | Value::Bool(_) => return,
+            _ => Value::String("[redacted]".into()),
+        };
+    }
+    if tool == "browser_fill_form" {
+        for field in args.get_mut("fields").and_then(Value::as_array_mut).into_iter().flatten() {
+            if let Some(value) = field.get_mut("value") {
+                scrub(value);
+            }
+        }
+    }
+    for (name, key) in TYPED_ARGS {
+        if *name == tool {
+            if let Some(value) = args.get_mut(*key) {
+                scrub(value);
+            }
+        }
+    }
+    args
+}
+
+fn truncate(mut s: String, max: usize) -> String {
+    if s.len() > max {
+        let mut cut = max;
+        while !s.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let total = s.len();
+        s.truncate(cut);
+        s.push_str(&format!("\n… [truncated, {total} bytes total]"));
+    }
+    s
+}
+
+// ---------------------------------------------------------------------------
+// Listing and reports
+// ---------------------------------------------------------------------------
+
+/// Summary of a recorded run for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub id: String,
+    pub message: String,
+    pub started_at: u64,
+    pub tool_calls: usize,
+    /// `None` while the run is still in progress (or if it crashed).
+    pub ok: Option<bool>,
+}
+
+fn read_events(dir: &Path) -> Result<Vec<RunEvent>, String> {
+    let text = std::fs::read_to_string(dir.join("events.jsonl"))
+        .map_err(|e| format!("Failed to read run log in {}: {e}", dir.display()))?;
+    // Skip unparsable lines — the last one may be half-written after a crash.
+    Ok(text
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+fn run_dir(id: &str) -> Result<PathBuf, String> {
+    let valid = id.starts_with("run-") && id[4..].chars().all(|c| c.is_ascii_digit());
+    if !valid {
+        return Err(format!("Invalid run id '{id}'"));
+    }
+    let dir = runs_dir().join(id);
+    if !dir.is_dir() {
+        return Err(format!("Run '{id}' not found"));
+    }
+    Ok(dir)
+}
+
+/// Recorded runs, newest first.
+pub fn list_runs() -> Vec<RunSummary> {
+    let mut runs: Vec<RunSummary> = std::fs::read_dir(runs_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let id = e.file_name().to_str()?.to_string();
+                    let events = read_events(&e.path()).ok()?;
+                    Some(summarize(id, &events))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+    runs
+}
+
+fn summarize(id: String, events: &[RunEvent]) -> RunSummary {
+    let mut summary = RunSummary {
+        id,
+        message: String::new(),
+        started_at: 0,
+        tool_calls: 0,
+        ok: None,
+    };
+    for event in events {
+        match event {
+            RunEvent::Start {
+                message,
+                started_at,
+            } => {
+                summary.message = message.clone();
+                summary.started_at = *started_at;
+            }
+            RunEvent::Tool { .. } => summary.tool_calls += 1,
+            RunEvent::End { ok, .. } => summary.ok = Some(*ok),
+            RunEvent::Image { .. } => {}
+        }
+    }
+    summary
+}
+
+/// Render a run (the most recent one when `id` is `None`) to a
+/// self-contained `report.html` in its directory and return the path.
+pub fn export_report(id: Option<&str>) -> Result<PathBuf, String> {
+    let id = match id {
+        Some(id) => id.to_string(),
+        None => list_runs()
+            .first()
+            .map(|r| r.id.clone())
+            .ok_or("No recorded runs yet")?,
+    };
+    let dir = run_dir(&id)?;
+    let events = read_events(&dir)?;
+    let html = render_html(&id, &dir, &events);
+    let path = dir.join("report.html");
+    open_private(&path, false)
+        .and_then(|mut f| f.write_all(html.as_bytes()))
+        .map_err(|e| format!("Failed to write report: {e}"))?;
+    info!("[Session] Report written to {}", path.display());
+    Ok(path)
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+fn image_html(dir: &Path, file: &str, label: &str) -> String {
+    // Only plain sequence names are ever written; refuse anything else.
+    if !file.ends_with(".png") || !file[..file.len() - 4].chars().all(|c| c.is_ascii_digit()) {
+        return String::new();
+    }
+    match std::fs::read(dir.join("images").join(file)) {
+        Ok(bytes) => {
+            let b64 = base64::engine::general_purpose::STANDARD.encode(bytes);
+            format!(
+                "<figure><img src=\"data:image/png;base64,{b64}\" alt=\"{label}\">\
+                 <figcaption>{label}</figcaption></figure>",
+                label = escape(label)
+            )
+        }
+        Err(_) => format!("<p class=\"muted\">Missing image {}</p>", escape(file)),
+    }
+}
+
+const REPORT_CSS: &str = "\
+body{font:14px -apple-system,BlinkMacSystemFont,sans-serif;margin:2em auto;max-width:960px;color:#222}\
+h1{font-size:1.3em}.muted{color:#888}\
+.event{border-left:3px solid #4a7;padding:.4em .8em;margin:.6em 0}\
+.event.error{border-color:#c44}.event.end{border-color:#555}\
+.time{color:#888;font-variant-numeric:tabular-nums;margin-right:.6em}\
+pre{background:#f5f5f5;padding:.6em;overflow:auto;white-space:pre-wrap;word-break:break-word}\
+summary{cursor:pointer}\
+figure{display:inline-block;margin:.4em .4em 0 0}\
+figure img{max-width:280px;max-height:180px;border:1px solid #ccc;cursor:zoom-in}\
+figure.zoomed{display:block}figure.zoomed img{max-width:100%;max-height:none;cursor:zoom-out}\
+figcaption{font-size:.85em;color:#666}";
+
+fn render_html(id: &str, dir: &Path, events: &[RunEvent]) -> String {
+    let summary = summarize(id.to_string(), events);
+
+    // Images are shown inside the tool call that was running when they were
+    // captured; anything else gets its own row.
+    let tool_spans: Vec<(u64, u64)> = events
+        .iter()
+        .filter_map(|e| match e {
+            RunEvent::Tool {
+                offset_ms,
+                duration_ms,
+                ..
+            } => Some((*offset_ms, offset_ms + duration_ms)),
+            _ => None,
+        })
+        .collect();
+    let owner = |at: u64| tool_spans.iter().position(|&(s, e)| s <= at && at <= e);
+    let mut nested: Vec<Vec<(&str, &str)>> = vec![Vec::new(); tool_spans.len()];
+
+    // Rows sorted by start offset: (offset, html)
+    let mut rows: Vec<(u64, String)> = Vec::new();
+    for event in events {
+        if let RunEvent::Image {
+            file,
+            label,
+            offset_ms,
+        } = event
+        {
+            match owner(*offset_ms) {
+                Some(i) => nested[i].push((file, label)),
+                None => rows.push((
+                    *offset_ms,
+                    format!(
+                        "<div class=\"event\"><span class=\"time\">{}</span>screenshot{}</div>",
+                        seconds(*offset_ms),
+                        image_html(dir, file, label)
+                    ),
+                )),
+            }
+        }
+    }
+
+    let mut tool_index = 0;
+    for event in events {
+        match event {
+            RunEvent::Tool {
+                tool,
+                args,
+                result,
+                is_error,
+                offset_ms,
+                duration_ms,
+            } => {
+                let args = serde_json::to_string_pretty(args).unwrap_or_default();
+                let images: String = nested[tool_index]
+                    .iter()
+                    .map(|(f, l)| image_html(dir, f, l))
+                    .collect();
+                tool_index += 1;
+                rows.push((
+                    *offset_ms,
+                    format!(
+                        "<div class=\"event{cls}\"><span class=\"time\">{at}</span>\
+                         <b>{tool}</b> <span class=\"muted\">{took}{status}</span>\
+                         <details><summary>arguments</summary><pre>{args}</pre></details>\
+                         <details{open}><summary>result</summary><pre>{result}</pre></details>\
+                         {images}</div>",
+                        cls = if *is_error { " error" } else { "" },
+                        at = seconds(*offset_ms),
+                        tool = escape(tool),
+                        took = seconds(*duration_ms),
+                        status = if *is_error { " · error" } else { "" },
+                        args = escape(&args),
+                        open = if *is_error { " open" } else { "" },
+                        result = escape(result),
+                    ),
+                ));
+            }
+            RunEvent::End {
+                ok,
+                output,
+                offset_ms,
+            } => rows.push((
+                *offset_ms,
+                format!(
+                    "<div class=\"event end{cls}\"><span class=\"time\">{at}</span>\
+                     <b>{label}</b><pre>{output}</pre></div>",
+                    cls = if *ok { "" } else { " error" },
+                    at = seconds(*offset_ms),
+                    label = if *ok { "Response" } else { "Failed" },
+                    output = escape(output),
+                ),
+            )),
+            RunEvent::Start { .. } | RunEvent::Image { .. } => {}
+        }
+    }
+    rows.sort_by_key(|(at, _)| *at);
+
+    let status = match summary.ok {
+        Some(true) => "completed",
+        Some(false) => "failed",
+        None => "did not finish",
+    };
+    let body: String = rows.into_iter().map(|(_, html)| html).collect();
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>ZeptoBot {id}</title>\
+         <style>{REPORT_CSS}</style></head><body>\
+         <h1>{message}</h1>\
+         <p class=\"muted\">{id} · started <span id=\"started\">{started}</span> · \
+         {calls} tool call(s) · {status}</p>{body}\
+         <script>var s=document.getElementById('started');\
+         s.textContent=new Date({started}).toLocaleString();\
+         document.addEventListener('click',function(e){{\
+         if(e.target.matches('figure img'))e.target.parentNode.classList.toggle('zoomed');}});</script>\
+         </body></html>\n",
+        id = escape(id),
+        message = escape(&summary.message),
+        started = summary.started_at,
+        calls = summary.tool_calls,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn scrub_args_keeps_only_the_length_of_typed_text() {
+        let typed = scrub_args("browser_type", json!({ "selector": "#pw", "value": "hunter2" }));
+        assert_eq!(typed, json!({ "selector": "#pw", "value": "[7 chars]" }));
+
+        let form = json!({ "fields": [
+            { "label": "Password", "value": "s3cret" },
+            { "label": "Remember me", "value": true },
+            { "label": "Tags", "value": ["a", "b"] },
+        ] });
+        assert_eq!(
+            scrub_args("browser_fill_form", form),
+            json!({ "fields": [
+                { "label": "Password", "value": "[6 chars]" },
+                { "label": "Remember me", "value": true },
+                { "label": "Tags", "value": "[2 values]" },
+            ] })
+        );
+
+        let click = json!({ "text": "Sign in" });
+        assert_eq!(scrub_args("browser_click", click.clone()), click);
+    }
+
+    #[test]
+    fn secret_bearing_results_are_not_recorded() {
+        assert!(result_unrecorded("browser_js", &json!({ "code": "document.cookie" })));
+        assert!(result_unrecorded("read_value", &json!({})));
+        let wait = json!({ "url": "/api/me" });
+        assert!(!result_unrecorded("browser_wait_for_request", &wait));
+        let capture = json!({ "url": "/api/me", "capture_body": true });
+        assert!(result_unrecorded("browser_wait_for_request", &capture));
+        assert!(!result_unrecorded("browser_click", &json!({ "text": "Sign in" })));
+    }
+}
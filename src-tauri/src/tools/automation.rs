@@ -716,6 +716,10 @@ pub fn all_automation_tools() -> Vec<Box<dyn Tool>> {
         Box::new(RunAppleScriptTool),
         // Vision (Tier 3 — screenshot + GPT-4o-mini)
         Box::new(super::screenshot::ScreenshotTool),
+        Box::new(super::screen_tools::FindImageTool),
+        Box::new(super::screen_tools::SaveReferenceImageTool),
+        Box::new(super::screen_tools::WaitForScreenChangeTool),
+        Box::new(super::screen_tools::WaitUntilStableTool),
         // Accessibility API (Tier 2 — programmatic UI interaction)
         Box::new(super::ax_tools::GetUIElementsTool),
         Box::new(super::ax_tools::FindElementTool),
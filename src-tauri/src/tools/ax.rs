@@ -289,8 +289,14 @@ fn ax_get_size(element: AXUIElementRef) -> Option<(f64, f64)> {
 
 /// Get children array from an AX element. Returns retained refs — caller must CFRelease each.
 fn ax_get_children(element: AXUIElementRef) -> Vec<AXUIElementRef> {
+    ax_get_elements(element, "AXChildren")
+}
+
+/// Get an element-array attribute (e.g. "AXChildren", "AXWindows").
+/// Returns retained refs — caller must CFRelease each.
+fn ax_get_elements(element: AXUIElementRef, attr: &str) -> Vec<AXUIElementRef> {
     unsafe {
-        let attr_cf = cfstring("AXChildren");
+        let attr_cf = cfstring(attr);
         let mut value: CFTypeRef = ptr::null();
         let err = AXUIElementCopyAttributeValue(element, attr_cf, &mut value);
         CFRelease(attr_cf);
@@ -368,6 +374,17 @@ impl fmt::Display for UIElement {
     }
 }
 
+/// `(position, size)` of an element, in screen points.
+pub type Frame = ((f64, f64), (f64, f64));
+
+/// Screen frame of a top-level window.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WindowFrame {
+    pub title: Option<String>,
+    pub position: (f64, f64),
+    pub size: (f64, f64),
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -627,6 +644,44 @@ pub fn element_at_position(pid: Pid, x: f32, y: f32) -> Option<UIElement> {
     })
 }
 
+/// List the visible (non-minimized) windows of an application.
+pub fn window_frames(pid: Pid) -> Vec<WindowFrame> {
+    let app = unsafe { AXUIElementCreateApplication(pid) };
+    if app.is_null() {
+        return vec![];
+    }
+    let windows = ax_get_elements(app, "AXWindows");
+    unsafe { CFRelease(app) };
+
+    let mut frames = Vec::with_capacity(windows.len());
+    for w in windows {
+        if ax_get_bool(w, "AXMinimized") != Some(true) {
+            if let (Some(position), Some(size)) = (ax_get_position(w), ax_get_size(w)) {
+                frames.push(WindowFrame {
+                    title: ax_get_string(w, "AXTitle"),
+                    position,
+                    size,
+                });
+            }
+        }
+        unsafe { CFRelease(w) };
+    }
+    frames
+}
+
+/// Frames of secure text fields (password inputs) in an application.
+pub fn secure_field_frames(pid: Pid, max_depth: usize) -> Vec<Frame> {
+    let app = unsafe { AXUIElementCreateApplication(pid) };
+    if app.is_null() {
+        return vec![];
+    }
+    let mut out = Vec::new();
+    let mut visited = 0usize;
+    collect_secure_fields(app, 0, max_depth, &mut visited, &mut out);
+    unsafe { CFRelease(app) };
+    out
+}
+
 // ---------------------------------------------------------------------------
 // Internal helpers
 // ---------------------------------------------------------------------------
@@ -661,3 +716,34 @@ fn find_recursive(
     }
     result
 }
+
+fn collect_secure_fields(
+    element: AXUIElementRef,
+    depth: usize,
+    max_depth: usize,
+    visited: &mut usize,
+    out: &mut Vec<Frame>,
+) {
+    // Cap traversal — this runs before every screenshot.
+    if depth > max_depth || *visited >= 3000 {
+        return;
+    }
+    *visited += 1;
+
+    let secure = ax_get_string(element, "AXSubrole").as_deref() == Some("AXSecureTextField")
+        || ax_get_string(element, "AXRole").as_deref() == Some("AXSecureTextField");
+    if secure {
+        if let (Some(pos), Some(size)) = (ax_get_position(element), ax_get_size(element)) {
+            out.push((pos, size));
+        }
+        return;
+    }
+
+    let children = ax_get_children(element);
+    for child in &children {
+        collect_secure_fields(*child, depth + 1, max_depth, visited, out);
+    }
+    for child in children {
+        unsafe { CFRelease(child) };
+    }
+}
@@ -1,4 +1,4 @@
-//! Browser automation tools with triple backend:
+//! Browser automation tools with four backends:
 //!
 //! **Primary**: Chrome extension WebSocket bridge (port 3847).
 //!   Works with the user's existing Chrome — no restart, all logins preserved.
@@ -9,30 +9,289 @@
 //! **Fallback**: Chrome DevTools Protocol via chromiumoxide.
 //!   Launches a dedicated Chrome instance with `--remote-debugging-port=9222`.
 //!   Requires a separate profile (`~/.zeptobot/chrome-profile`).
+//!
+//! **Firefox**: WebDriver BiDi (see [`web_bidi`]), chosen with
+//!   `browser_connect` `browser='firefox'`. Attaches to a Firefox remote agent
+//!   on port 9223 or launches one with `~/.zeptobot/firefox-profile`.
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use base64::Engine;
 use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::accessibility::GetFullAxTreeParams;
+use chromiumoxide::cdp::browser_protocol::browser::{
+    EventDownloadProgress, EventDownloadWillBegin, SetDownloadBehaviorBehavior,
+    SetDownloadBehaviorParams,
+};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    BackendNodeId, GetContentQuadsParams, GetFrameOwnerParams, ResolveNodeParams,
+    SetFileInputFilesParams,
+};
+use chromiumoxide::cdp::browser_protocol::input::InsertTextParams;
+use chromiumoxide::cdp::browser_protocol::log::{EnableParams as LogEnableParams, EventEntryAdded};
+use chromiumoxide::cdp::browser_protocol::network::{
+    CookieParam, DeleteCookiesParams, EventLoadingFailed, EventLoadingFinished,
+    EventRequestWillBeSent, EventResponseReceived, GetResponseBodyParams,
+};
+use chromiumoxide::cdp::browser_protocol::page::{
+    CaptureScreenshotFormat, CaptureScreenshotParams, EventDomContentEventFired,
+    EventJavascriptDialogClosed, EventJavascriptDialogOpening, EventLoadEventFired, FrameId,
+    FrameTree, GetFrameTreeParams, GetLayoutMetricsParams, GetNavigationHistoryParams,
+    HandleJavaScriptDialogParams, NavigateParams, NavigateToHistoryEntryParams, Viewport,
+};
+use chromiumoxide::cdp::js_protocol::runtime::{
+    CallArgument, CallFunctionOnParams, EvaluateParams, EventConsoleApiCalled, EventExceptionThrown,
+    ExecutionContextId, RemoteObject, RemoteObjectSubtype,
+};
 use chromiumoxide::Page;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
 use tokio::net::TcpListener;
 use tokio::sync::{oneshot, Mutex};
 use tokio_tungstenite::tungstenite::Message;
-use tracing::info;
+use tracing::{info, warn};
 use zeptoclaw::tools::ToolOutput;
 use zeptoclaw::{Result as ZeptoResult, Tool, ToolCategory, ToolContext};
 
+use super::screenshot::describe_image;
+use super::web_bidi;
+use super::web_console;
+use super::web_dialog::{self, DialogPolicy};
+use super::web_files::{self, DownloadLog};
+use super::web_input;
+use super::web_network::{self, NetworkLog, RequestEntry};
+use super::web_page::{
+    self, cookie_domain_arg, describe_files, files_arg, frame_arg, markdown_opts, page_result,
+    storage_args, submit_arg, tab_arg, timeout_arg, wait_until_arg, FrameInfo, PageDriver, PageLogs,
+    DOM_JS, JS_BOX, JS_STORAGE, MAX_CAPTURE_HEIGHT, REF_ACTIONS,
+};
+use super::web_session;
+use super::web_snapshot::{self, SnapshotOptions};
+
 const WS_PORT: u16 = 3847;
+
+/// Actions that interact with the page: they may start requests and raise
+/// errors. `browser_wait_for_request`, `browser_wait_for_download` and the
+/// error report on each result look at what happened since the latest one.
+const INTERACTIONS: [&str; 14] = [
+    "navigate", "click", "type", "fill", "hover", "select_option", "check", "press_key",
+    "scroll", "execute_js", "go_back", "go_forward", "reload", "upload",
+];
+
+/// Actions that don't run script in the page, so they work while a dialog
+/// blocks it.
+const PAGE_FREE_ACTIONS: [&str; 9] = [
+    "get_tabs", "switch_tab", "new_tab", "close_tab", "dialog", "console", "wait_for_request",
+    "wait_for_download", "cookies",
+];
+
+/// Environment variable with the Chrome or Chromium binary to launch.
+pub const CHROME_ENV: &str = "ZEPTOBOT_CHROME";
+
+/// Environment variable that makes the dedicated Chrome (and Firefox)
+/// headless (`1`/`true`).
+pub const HEADLESS_ENV: &str = "ZEPTOBOT_CHROME_HEADLESS";
+
+/// Environment variable with extra flags for the dedicated Chrome, e.g.
+/// `--no-sandbox` inside a container.
+pub const CHROME_ARGS_ENV: &str = "ZEPTOBOT_CHROME_ARGS";
+
+/// How long a launched Chrome gets to open its DevTools endpoint.
+const CHROME_STARTUP: std::time::Duration = std::time::Duration::from_secs(20);
+
+/// Why `export_session` / `import_session` fail on the other backends.
+const SESSION_DEDICATED_ONLY: &str = "Session bundles belong to the dedicated Chrome or \
+    Firefox profile (~/.zeptobot/chrome-profile, ~/.zeptobot/firefox-profile) — connect with \
+    CDP or browser='firefox' to export or import one";
+
 static REQUEST_ID: AtomicU64 = AtomicU64::new(1);
 
 fn next_id() -> String {
     format!("req_{}", REQUEST_ID.fetch_add(1, Ordering::Relaxed))
 }
 
+/// The Chrome binary to launch: [`CHROME_ENV`], then the usual macOS app
+/// bundles, then Chrome or Chromium on the `PATH` (Linux).
+pub fn find_chrome() -> Option<std::path::PathBuf> {
+    if let Some(bin) = std::env::var_os(CHROME_ENV).filter(|b| !b.is_empty()) {
+        return Some(bin.into());
+    }
+    let apps = [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Google Chrome Canary.app/Contents/MacOS/Google Chrome Canary",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    ];
+    if let Some(app) = apps.iter().map(std::path::PathBuf::from).find(|p| p.exists()) {
+        return Some(app);
+    }
+    let names = ["google-chrome-stable", "google-chrome", "chromium", "chromium-browser", "chrome"];
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs: Vec<std::path::PathBuf> = std::env::split_paths(&path)
+        .chain(["/usr/bin", "/snap/bin"].map(std::path::PathBuf::from))
+        .collect();
+    names
+        .iter()
+        .flat_map(|name| dirs.iter().map(move |dir| dir.join(name)))
+        .find(|p| p.is_file())
+}
+
+/// [`HEADLESS_ENV`] as a flag.
+fn headless_default() -> bool {
+    std::env::var(HEADLESS_ENV).is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"))
+}
+
+/// Cut `text` to `max_chars` characters starting at `offset`, with a note
+/// saying where the chunk sits and how to get the next one.
+fn paginate(text: &str, offset: usize, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if offset == 0 && total <= max_chars {
+        return text.to_string();
+    }
+    if offset >= total {
+        return format!("[offset {offset} is past the end — {total} characters in total]");
+    }
+    let chunk: String = text.chars().skip(offset).take(max_chars).collect();
+    let end = offset + chunk.chars().count();
+    let more = if end < total {
+        format!(" — pass offset={end} for the next part")
+    } else {
+        String::new()
+    };
+    format!("{chunk}\n\n[characters {offset}–{end} of {total}{more}]")
+}
+
+/// The `window.__zb.extract` spec for `browser_extract` arguments: a table,
+/// a JSON Schema, or `items` + `fields` turned into an array schema.
+pub fn extract_spec(args: &Value) -> Result<Value, String> {
+    let limit = args.get("limit").and_then(Value::as_u64).unwrap_or(100);
+    if let Some(table) = args.get("table").filter(|v| !v.is_null()) {
+        return Ok(json!({ "table": table, "limit": limit }));
+    }
+    if let Some(schema) = args.get("schema").filter(|v| v.is_object()) {
+        return Ok(json!({ "schema": schema, "limit": limit }));
+    }
+    let Some(items) = args.get("items").filter(|v| !v.is_null()) else {
+        return Err("Provide 'table', 'schema', or 'items' with 'fields'".into());
+    };
+    let mut array = match items {
+        Value::String(sel) => json!({ "selector": sel }),
+        Value::Object(_) => items.clone(),
+        _ => return Err("'items' must be a CSS selector or a locator object".into()),
+    };
+    let fields = args.get("fields").and_then(Value::as_object)
+        .ok_or("'items' needs 'fields': { name: selector or { selector, attribute, type } }")?;
+    let properties: serde_json::Map<String, Value> = fields
+        .iter()
+        .map(|(name, field)| {
+            let node = match field {
+                Value::String(sel) => json!({ "type": "string", "selector": sel }),
+                other => other.clone(),
+            };
+            (name.clone(), node)
+        })
+        .collect();
+    array["type"] = json!("array");
+    array["items"] = json!({ "type": "object", "properties": properties });
+    Ok(json!({ "schema": array, "limit": limit }))
+}
+
+/// A console argument as text: strings as is, other values as JSON, plain
+/// objects and arrays by their preview (`{code: 42}`), anything else by its
+/// description (`Error: …`, `HTMLDivElement`).
+fn remote_text(arg: &RemoteObject) -> String {
+    match &arg.value {
+        Some(Value::String(s)) => return s.clone(),
+        Some(v) => return v.to_string(),
+        None => {}
+    }
+    let array = matches!(arg.subtype, Some(RemoteObjectSubtype::Array));
+    if let Some(preview) = arg.preview.as_ref().filter(|_| array || arg.subtype.is_none()) {
+        let items: Vec<String> = preview
+            .properties
+            .iter()
+            .map(|p| {
+                let value = p.value.as_deref().unwrap_or("…");
+                if array { value.to_string() } else { format!("{}: {value}", p.name) }
+            })
+            .collect();
+        let more = if preview.overflow { ", …" } else { "" };
+        return if array {
+            format!("[{}{more}]", items.join(", "))
+        } else {
+            format!("{{{}{more}}}", items.join(", "))
+        };
+    }
+    arg.description.clone().unwrap_or_else(|| arg.r#type.as_ref().to_string())
+}
+
+/// Build a `locator` object (role/name, label, placeholder, text + exact/nth)
+/// from an action's arguments. Resolved page-side by `window.__zb.locate`.
+fn locator_from(action: &str, params: &Value) -> Option<Value> {
+    if !REF_ACTIONS.contains(&action) || params.get("selector").is_some() || params.get("ref").is_some() {
+        return None;
+    }
+    let mut loc = serde_json::Map::new();
+    for key in ["role", "name", "label", "placeholder"] {
+        if let Some(v) = params.get(key).filter(|v| !v.is_null()) {
+            loc.insert(key.into(), v.clone());
+        }
+    }
+    // `text` locates by visible text for click/read, keeps its old meaning
+    // (input label) for type, and stays a plain text search for wait_for.
+    if let Some(t) = params.get("text").filter(|v| v.is_string()) {
+        match action {
+            "click" | "read" | "hover" | "scroll" | "upload" | "screenshot" => {
+                loc.insert("text".into(), t.clone());
+            }
+            "type" | "fill" | "select_option" | "check" | "press_key" => {
+                loc.entry("label").or_insert_with(|| t.clone());
+            }
+            _ => {}
+        }
+    }
+    if !["role", "label", "placeholder", "text"].iter().any(|k| loc.contains_key(*k)) {
+        return None;
+    }
+    for key in ["exact", "nth"] {
+        if let Some(v) = params.get(key).filter(|v| !v.is_null()) {
+            loc.insert(key.into(), v.clone());
+        }
+    }
+    Some(Value::Object(loc))
+}
+
+/// Add the locator parameters to a tool's JSON schema.
+fn with_locator(mut schema: Value) -> Value {
+    let props = &mut schema["properties"];
+    props["role"] = json!({ "type": "string", "description": "ARIA role, e.g. button, link, textbox, checkbox (use with name)" });
+    props["name"] = json!({ "type": "string", "description": "Accessible name for role, e.g. role='button' name='Send'" });
+    props["label"] = json!({ "type": "string", "description": "Form control by its label text" });
+    props["placeholder"] = json!({ "type": "string", "description": "Input by placeholder text" });
+    props["exact"] = json!({ "type": "boolean", "description": "Match name/label/placeholder/text exactly (default: case-insensitive substring)" });
+    props["nth"] = json!({ "type": "integer", "description": "Pick the nth visible match (0-based, -1 = last) when several match" });
+    schema
+}
+
+/// JSON schema for the `frame` parameter of tools that act on page content.
+fn frame_param() -> Value {
+    json!({
+        "type": "string",
+        "description": "Act inside an iframe: its index from browser_list_elements, its name, \
+                        part of its URL, or a CSS selector for the <iframe> (default: the main page)"
+    })
+}
+
+/// JSON schema for the `tab` parameter shared by every browser tool.
+fn tab_param() -> Value {
+    json!({
+        "type": "string",
+        "description": "Tab id from browser_tabs (default: the current tab)"
+    })
+}
+
 // ===========================================================================
 // Active backend
 // ===========================================================================
@@ -42,6 +301,7 @@ enum Backend {
     Extension,
     AgentBrowser,
     Cdp,
+    Bidi,
 }
 
 // ===========================================================================
@@ -63,6 +323,32 @@ pub struct BrowserState {
 
     // ---- CDP state ----
     cdp: Mutex<Option<CdpConn>>,
+    /// Target id of the tab selected via `browser_switch_tab` / `browser_new_tab`.
+    cdp_tab: Mutex<Option<String>>,
+    /// Network activity and console output per tab (target id).
+    cdp_logs: Mutex<HashMap<String, Arc<PageLogs>>>,
+    /// Downloads of the whole browser, fed by `Browser.download*` events.
+    cdp_downloads: Arc<Mutex<DownloadLog>>,
+    // ---- WebDriver BiDi (Firefox) state ----
+    bidi: web_bidi::Firefox,
+
+    /// [`web_network::mark`], [`web_console::mark`] and [`web_files::mark`]
+    /// when the latest page interaction started.
+    network_mark: AtomicU64,
+    console_mark: AtomicU64,
+    download_mark: AtomicU64,
+
+    /// What to do with JavaScript dialogs; the extension gets it with every command.
+    dialog_policy: Arc<Mutex<DialogPolicy>>,
+}
+
+/// Where CDP page functions run: a tab and one of its frames.
+struct CdpTarget {
+    page: Page,
+    context: ExecutionContextId,
+    /// The frame viewport's origin in main-frame viewport coordinates, for
+    /// turning element positions into `Input` event coordinates.
+    offset: (f64, f64),
 }
 
 struct CdpConn {
@@ -72,6 +358,7 @@ struct CdpConn {
 
 impl BrowserState {
     pub fn new() -> Self {
+        let dialog_policy = Arc::new(Mutex::new(DialogPolicy::from_env()));
         Self {
             active_backend: Mutex::new(None),
             ws_tx: Arc::new(Mutex::new(None)),
@@ -81,9 +368,25 @@ impl BrowserState {
             ws_notify: Arc::new(tokio::sync::Notify::new()),
             ab_session: Mutex::new(None),
             cdp: Mutex::new(None),
+            cdp_tab: Mutex::new(None),
+            cdp_logs: Mutex::new(HashMap::new()),
+            cdp_downloads: Arc::new(Mutex::new(DownloadLog::default())),
+            bidi: web_bidi::Firefox::new(dialog_policy.clone()),
+            network_mark: AtomicU64::new(0),
+            console_mark: AtomicU64::new(0),
+            download_mark: AtomicU64::new(0),
+            dialog_policy,
         }
     }
 
+    pub async fn dialog_policy(&self) -> DialogPolicy {
+        *self.dialog_policy.lock().await
+    }
+
+    pub async fn set_dialog_policy(&self, policy: DialogPolicy) {
+        *self.dialog_policy.lock().await = policy;
+    }
+
     // -------------------------------------------------------------------
     // Extension helpers
     // -------------------------------------------------------------------
@@ -166,14 +469,25 @@ impl BrowserState {
         };
 
         let id = next_id();
-        let cmd = json!({ "id": id, "action": action, "params": params });
+        let mut cmd = json!({ "id": id, "action": action });
+        if let Some(tab) = tab_arg(&params) {
+            let tab_id: i64 = tab
+                .parse()
+                .map_err(|_| format!("Invalid tab id '{tab}' — use an id from browser_tabs"))?;
+            cmd["tabId"] = json!(tab_id);
+        }
+        let params_timeout = params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(0).min(30_000);
+        cmd["params"] = params;
+        cmd["dialogPolicy"] = json!(self.dialog_policy().await.as_str());
 
         let (resp_tx, resp_rx) = oneshot::channel();
         self.pending.lock().await.insert(id.clone(), resp_tx);
 
         tx.send(cmd.to_string()).map_err(|_| "WS send failed".to_string())?;
 
-        match tokio::time::timeout(std::time::Duration::from_secs(15), resp_rx).await {
+        // Waiting actions get their own timeout on top.
+        let secs = 15 + params_timeout / 1000;
+        match tokio::time::timeout(std::time::Duration::from_secs(secs), resp_rx).await {
             Ok(Ok(val)) => {
                 if val.get("success").and_then(Value::as_bool) == Some(true) {
                     Ok(val)
@@ -187,7 +501,7 @@ impl BrowserState {
             }
             Err(_) => {
                 self.pending.lock().await.remove(&id);
-                Err("Extension timeout (15s)".into())
+                Err(format!("Extension timeout ({secs}s)"))
             }
         }
     }
@@ -281,21 +595,169 @@ impl BrowserState {
         }
     }
 
+    /// List agent-browser tabs as `[{id, title, url, active}]`, ids being tab indexes.
+    async fn ab_tabs(&self) -> Result<Vec<Value>, String> {
+        let out = self.ab_exec(&["--json", "tab"]).await?;
+        if let Ok(val) = serde_json::from_str::<Value>(&out) {
+            let list = val
+                .pointer("/data/tabs")
+                .or_else(|| val.get("tabs"))
+                .unwrap_or(&val);
+            if let Some(tabs) = list.as_array() {
+                return Ok(tabs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| {
+                        json!({
+                            "id": t.get("index").cloned().unwrap_or(json!(i)),
+                            "title": t.get("title").cloned().unwrap_or(json!("")),
+                            "url": t.get("url").cloned().unwrap_or(json!("")),
+                            "active": t.get("active").cloned().unwrap_or(json!(false)),
+                        })
+                    })
+                    .collect());
+            }
+        }
+        // Plain-text fallback: one tab per line, `>` or `*` marking the active one.
+        Ok(out
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                let active = line.starts_with('>') || line.starts_with('*');
+                let title = line.trim_start_matches(['>', '*', ' ']);
+                json!({ "id": i, "title": title, "url": "", "active": active })
+            })
+            .collect())
+    }
+
+    /// Run a locator through agent-browser's semantic `find` command.
+    async fn ab_find(&self, action: &str, loc: &Value, params: &Value) -> Result<String, String> {
+        if loc.get("nth").is_some() {
+            return Err("nth is not supported on the agent-browser backend — use a ref from browser_list_elements".into());
+        }
+        let (kind, target) = ["role", "label", "placeholder", "text"]
+            .iter()
+            .find_map(|k| Some((*k, loc.get(*k)?.as_str()?)))
+            .ok_or("Locator needs role, label, placeholder or text")?;
+        let mut args = vec!["find", kind, target];
+        match (action, params.get("value")) {
+            ("click", _) => args.push("click"),
+            ("hover", _) => args.push("hover"),
+            ("check", _) => args.push(if params.get("checked") == Some(&json!(false)) { "uncheck" } else { "check" }),
+            ("fill", Some(Value::Bool(checked))) => args.push(if *checked { "check" } else { "uncheck" }),
+            ("type" | "fill", value) => {
+                args.push("fill");
+                args.push(value.and_then(Value::as_str).ok_or("Missing 'value'")?);
+            }
+            _ => return Err(format!("Locators are not supported for {action} on the agent-browser backend")),
+        }
+        if let Some(name) = loc.get("name").and_then(Value::as_str) {
+            args.push("--name");
+            args.push(name);
+        }
+        if loc.get("exact").and_then(Value::as_bool) == Some(true) {
+            args.push("--exact");
+        }
+        let out = self.ab_exec(&args).await?;
+        if matches!(action, "type" | "fill") && submit_arg(params) {
+            self.ab_exec(&["press", "Enter"]).await?;
+        }
+        Ok(out)
+    }
+
+    /// Call a `window.__zb` action that returns `{ ok, result }` / `{ error }`
+    /// through agent-browser's `evaluate`. Arguments go in as JSON literals,
+    /// which are inert JS.
+    async fn ab_zb(&self, method: &str, args: &[Value]) -> Result<String, String> {
+        let code = format!(
+            "(() => {{\n{DOM_JS}\n;return JSON.stringify(window.__zb.{method}(...{}));\n}})()",
+            Value::Array(args.to_vec())
+        );
+        let out = self.ab_exec(&["evaluate", &code]).await?;
+        // agent-browser may print the returned string JSON-encoded.
+        let decoded = serde_json::from_str::<String>(out.trim()).unwrap_or(out);
+        page_result(serde_json::from_str(&decoded).map_err(|e| format!("{method}: {e}"))?)
+    }
+
     /// Execute an action using agent-browser CLI.
-    async fn run_ab(&self, action: &str, params: Value) -> Result<String, String> {
+    async fn run_ab(&self, action: &str, mut params: Value) -> Result<String, String> {
+        // agent-browser refs (`@e3` from `snapshot -i`) are accepted wherever a selector is.
+        if REF_ACTIONS.contains(&action) {
+            if let Some(r) = params.get("ref").and_then(Value::as_str) {
+                params["selector"] = json!(format!("@{}", r.trim_start_matches('@')));
+            }
+        }
+
+        // Playwright's CSS engine pierces open shadow roots by itself.
+        if let Some(sel) = params.get("selector").and_then(Value::as_str) {
+            params["selector"] = json!(sel.replace(">>>", " "));
+        }
+
+        // agent-browser acts on its current tab, so targeting a tab means switching to it.
+        let tab = tab_arg(&params);
+        if let (Some(tab), false) = (&tab, action.ends_with("_tab")) {
+            self.ab_exec(&["tab", tab]).await?;
+        }
+
+        // Likewise for frames: enter it, act, and return to the main frame.
+        let Some(frame) = frame_arg(&params) else {
+            return self.ab_action(action, &params, tab).await;
+        };
+        if frame.parse::<usize>().is_ok() {
+            return Err("The agent-browser backend needs frame as a name, URL or <iframe> selector, not an index".into());
+        }
+        self.ab_exec(&["frame", &frame]).await?;
+        let out = self.ab_action(action, &params, tab).await;
+        let _ = self.ab_exec(&["frame", "main"]).await;
+        out
+    }
+
+    async fn ab_action(&self, action: &str, params: &Value, tab: Option<String>) -> Result<String, String> {
+        // Playwright underneath already waits for click/fill targets to be actionable.
+        if let Some(loc) = params.get("locator") {
+            return self.ab_find(action, loc, params).await;
+        }
+
         match action {
             "get_tabs" => {
-                // agent-browser doesn't have a tabs list — return session info
-                let _ = self.ab_exec(&["screenshot"]).await?;
-                Ok(r#"[{"id":0,"title":"agent-browser session","url":""}]"#.to_string())
+                let tabs = self.ab_tabs().await?;
+                Ok(serde_json::to_string(&tabs).unwrap_or_default())
+            }
+            "switch_tab" => {
+                let tab = tab.ok_or("Missing 'tab'")?;
+                self.ab_exec(&["tab", &tab]).await?;
+                Ok(format!("Switched to tab {tab}"))
+            }
+            "new_tab" => {
+                let url = params.get("url").and_then(Value::as_str);
+                let mut args = vec!["tab", "new"];
+                args.extend(url);
+                self.ab_exec(&args).await?;
+                let tabs = self.ab_tabs().await.unwrap_or_default();
+                Ok(format!(
+                    "Opened new tab {} {}",
+                    tabs.len().saturating_sub(1),
+                    url.unwrap_or("")
+                ))
+            }
+            "close_tab" => {
+                let mut args = vec!["tab", "close"];
+                args.extend(tab.as_deref());
+                self.ab_exec(&args).await?;
+                Ok(format!("Closed tab {}", tab.as_deref().unwrap_or("(current)")))
             }
             "navigate" => {
                 let url = params.get("url").and_then(Value::as_str)
                     .ok_or("Missing 'url'")?;
+                let wait_until = wait_until_arg(params)?;
                 self.ab_exec(&["navigate", url]).await?;
-                // Give it a moment to load
-                tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
-                Ok(format!("Navigated to {url}"))
+                let note = match self.ab_exec(&["wait", "--load", wait_until]).await {
+                    Ok(_) => String::new(),
+                    Err(e) => format!(" (not {wait_until} yet: {e})"),
+                };
+                Ok(format!("Navigated to {url}{note}"))
             }
             "click" => {
                 if let Some(sel) = params.get("selector").and_then(Value::as_str) {
@@ -311,19 +773,191 @@ impl BrowserState {
                     .ok_or("Missing 'value'")?;
                 if let Some(sel) = params.get("selector").and_then(Value::as_str) {
                     self.ab_exec(&["click", sel]).await?;
-                    self.ab_exec(&["type", value]).await
-                } else {
-                    self.ab_exec(&["type", value]).await
                 }
+                let out = self.ab_exec(&["type", value]).await?;
+                if submit_arg(params) {
+                    self.ab_exec(&["press", "Enter"]).await?;
+                }
+                Ok(out)
+            }
+            "fill" => {
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref', 'selector', 'role', 'label' or 'placeholder'")?;
+                let out = match params.get("value") {
+                    Some(Value::Bool(true)) => self.ab_exec(&["check", sel]).await?,
+                    Some(Value::Bool(false)) => self.ab_exec(&["uncheck", sel]).await?,
+                    Some(Value::Array(values)) => {
+                        let mut args = vec!["select", sel];
+                        args.extend(values.iter().filter_map(Value::as_str));
+                        self.ab_exec(&args).await?
+                    }
+                    Some(Value::String(value)) => match self.ab_exec(&["fill", sel, value]).await {
+                        Ok(out) => out,
+                        // Playwright's fill refuses <select>; pick the option instead.
+                        Err(_) => self.ab_exec(&["select", sel, value]).await?,
+                    },
+                    _ => return Err("Missing 'value'".into()),
+                };
+                if submit_arg(params) {
+                    self.ab_exec(&["press", "Enter"]).await?;
+                }
+                Ok(out)
             }
+            "hover" => {
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref', 'selector', 'text', 'role', 'label' or 'placeholder'")?;
+                self.ab_exec(&["hover", sel]).await
+            }
+            "select_option" => {
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref' or 'selector'")?;
+                let mut args = vec!["select", sel];
+                match params.get("values") {
+                    Some(Value::Array(values)) => args.extend(values.iter().filter_map(Value::as_str)),
+                    Some(Value::String(value)) => args.push(value),
+                    _ => return Err("Missing 'values'".into()),
+                }
+                self.ab_exec(&args).await
+            }
+            "check" => {
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref' or 'selector'")?;
+                let checked = params.get("checked").and_then(Value::as_bool).unwrap_or(true);
+                self.ab_exec(&[if checked { "check" } else { "uncheck" }, sel]).await
+            }
+            "press_key" => {
+                let key = params.get("key").and_then(Value::as_str).ok_or("Missing 'key'")?;
+                if let Some(sel) = params.get("selector").and_then(Value::as_str) {
+                    self.ab_exec(&["focus", sel]).await?;
+                }
+                self.ab_exec(&["press", key]).await
+            }
+            "scroll" => {
+                let selector = params.get("selector").and_then(Value::as_str);
+                if params.get("into_view").and_then(Value::as_bool) == Some(true) {
+                    let sel = selector.ok_or("Scrolling into view needs an element")?;
+                    return self.ab_exec(&["scrollintoview", sel]).await;
+                }
+                if selector.is_some() {
+                    return Err("The agent-browser backend scrolls the page only — use into_view=true for elements".into());
+                }
+                let (direction, amount) = match params.get("to").and_then(Value::as_str) {
+                    Some("top") => ("up", 1_000_000.0),
+                    Some("bottom") => ("down", 1_000_000.0),
+                    Some("left") => ("left", 1_000_000.0),
+                    Some("right") => ("right", 1_000_000.0),
+                    Some(other) => return Err(format!("Unknown scroll target '{other}' — use top, bottom, left or right")),
+                    None => (
+                        params.get("direction").and_then(Value::as_str).unwrap_or("down"),
+                        params.get("amount").and_then(Value::as_f64).unwrap_or(600.0),
+                    ),
+                };
+                self.ab_exec(&["scroll", direction, &amount.round().to_string()]).await
+            }
+            "extract" => {
+                let spec = params.get("spec").cloned().ok_or("Missing 'spec'")?;
+                self.ab_zb("extract", &[spec]).await
+            }
+            "console" => {
+                let errors = self.ab_exec(&["errors"]).await?;
+                if params.get("level").and_then(Value::as_str) == Some("error") {
+                    return Ok(errors);
+                }
+                let console = self.ab_exec(&["console"]).await?;
+                Ok(format!("{console}\n\nPage errors:\n{errors}"))
+            }
+            "dialog" => {
+                if params.get("action").and_then(Value::as_str) == Some("dismiss") {
+                    return self.ab_exec(&["dialog", "dismiss"]).await;
+                }
+                match params.get("prompt_text").and_then(Value::as_str) {
+                    Some(text) => self.ab_exec(&["dialog", "accept", text]).await,
+                    None => self.ab_exec(&["dialog", "accept"]).await,
+                }
+            }
+            "upload" => {
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref' or 'selector'")?;
+                let files = files_arg(params)?;
+                let mut args = vec!["upload", sel];
+                args.extend(files.iter().map(String::as_str));
+                self.ab_exec(&args).await?;
+                Ok(format!("Attached {}", describe_files(&files)))
+            }
+            "screenshot" => {
+                if params.get("selector").is_some() {
+                    return Err("The agent-browser backend captures whole pages only — \
+                                connect through the extension or CDP for element screenshots"
+                        .into());
+                }
+                let path = std::env::temp_dir().join(format!("zeptobot-{}.png", next_id()));
+                let path_str = path.to_string_lossy().into_owned();
+                let mut args = vec!["screenshot", path_str.as_str()];
+                if params.get("full_page").and_then(Value::as_bool) == Some(true) {
+                    args.push("--full");
+                }
+                self.ab_exec(&args).await?;
+                let png = std::fs::read(&path).map_err(|e| format!("screenshot: {e}"))?;
+                let _ = std::fs::remove_file(&path);
+                let data = base64::engine::general_purpose::STANDARD.encode(png);
+                Ok(json!({ "data": data }).to_string())
+            }
+            "wait_for_download" => Err(
+                "agent-browser doesn't report downloads — connect through the extension or CDP \
+                 for browser_wait_for_download"
+                    .into(),
+            ),
+            "cookies" => {
+                if params.get("clear").and_then(Value::as_bool) == Some(true) {
+                    return Err("agent-browser can only clear every cookie at once — connect \
+                                through the extension or CDP to clear one site's cookies"
+                        .into());
+                }
+                let url = self.ab_exec(&["get", "url"]).await.unwrap_or_default();
+                let domain = cookie_domain_arg(params, url.trim())?;
+                let name = params.get("name").and_then(Value::as_str);
+                let out = self.ab_exec(&["--json", "cookies"]).await?;
+                let val: Value = serde_json::from_str(&out).map_err(|e| format!("cookies: {e}"))?;
+                let list = val.pointer("/data/cookies").or_else(|| val.get("cookies")).unwrap_or(&val);
+                let cookies: Vec<Value> = list
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(web_session::from_cdp)
+                    .filter(|c| web_session::cookie_matches(c, &domain, name))
+                    .collect();
+                Ok(json!({ "domain": domain, "cookies": cookies }).to_string())
+            }
+            "storage" => self.ab_zb("storage", &storage_args(params)?).await,
+            "export_session" | "import_session" => Err(SESSION_DEDICATED_ONLY.into()),
+            "wait_for_request" => Err(
+                "agent-browser doesn't report individual requests — connect through the \
+                 extension or CDP for browser_wait_for_request"
+                    .into(),
+            ),
+            "go_back" => self.ab_exec(&["back"]).await,
+            "go_forward" => self.ab_exec(&["forward"]).await,
+            "reload" => self.ab_exec(&["reload"]).await,
             "read" => {
-                let out = self.ab_exec(&["content"]).await?;
-                let truncated = if out.len() > 5000 { &out[..5000] } else { &out };
-                Ok(truncated.to_string())
+                let selector = params.get("selector").and_then(Value::as_str);
+                if let Some(opts) = markdown_opts(params) {
+                    return self.ab_zb("markdown", &[json!(selector), opts]).await;
+                }
+                if let Some(sel) = selector {
+                    return self.ab_exec(&["get", "text", sel]).await;
+                }
+                self.ab_exec(&["content"]).await
+            }
+            "snapshot" => {
+                let mut args = vec!["snapshot"];
+                if params.get("interactive_only").and_then(Value::as_bool) == Some(true) {
+                    args.push("-i");
+                }
+                self.ab_exec(&args).await
             }
             "list_elements" => {
-                // Use observe to get accessibility tree
-                let out = self.ab_exec(&["observe"]).await?;
+                // Interactive snapshot: accessibility tree with `[ref=eN]` handles
+                let out = self.ab_exec(&["snapshot", "-i"]).await?;
                 let truncated = if out.len() > 5000 { &out[..5000] } else { &out };
                 Ok(truncated.to_string())
             }
@@ -334,6 +968,16 @@ impl BrowserState {
             }
             "wait_for" => {
                 let timeout = params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(5000).min(15000);
+                if params.get("network_idle").and_then(Value::as_bool) == Some(true) {
+                    self.ab_exec(&["wait", "--load", "networkidle"]).await?;
+                    if params.get("selector").is_none() && params.get("text").is_none() {
+                        return Ok("Network idle".into());
+                    }
+                }
+                if params.get("ref").is_some() {
+                    let sel = params["selector"].as_str().unwrap_or_default();
+                    return self.ab_exec(&["wait", sel]).await.map(|_| format!("Found: {sel}"));
+                }
                 let deadline = tokio::time::Instant::now()
                     + std::time::Duration::from_millis(timeout);
 
@@ -365,12 +1009,15 @@ impl BrowserState {
     // -------------------------------------------------------------------
 
     async fn cdp_connect(&self) -> Result<Arc<Browser>, String> {
+        self.cdp_connect_to("http://127.0.0.1:9222").await
+    }
+
+    async fn cdp_connect_to(&self, url: &str) -> Result<Arc<Browser>, String> {
         let mut guard = self.cdp.lock().await;
         if let Some(conn) = guard.as_ref() {
             return Ok(conn.browser.clone());
         }
 
-        let url = "http://127.0.0.1:9222";
         info!("[CDP] Connecting to Chrome at {url}...");
 
         match Browser::connect(url).await {
@@ -381,50 +1028,343 @@ impl BrowserState {
                 let browser = Arc::new(browser);
                 *guard = Some(CdpConn { browser: browser.clone(), _handler: h });
                 info!("[CDP] Connected");
+                if let Err(e) = self.cdp_track_downloads(&browser).await {
+                    warn!("[CDP] Download tracking unavailable: {e}");
+                }
                 Ok(browser)
             }
             Err(e) => Err(format!("CDP connect failed: {e}")),
         }
     }
 
-    async fn cdp_launch_and_connect(&self) -> Result<(Arc<Browser>, usize), String> {
+    async fn cdp_launch_and_connect(&self, headless: bool) -> Result<(Arc<Browser>, usize), String> {
         let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
         let profile = format!("{home}/.zeptobot/chrome-profile");
         let _ = std::fs::create_dir_all(&profile);
 
-        let bins = [
-            "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
-            "/Applications/Google Chrome Canary.app/Contents/MacOS/Google Chrome Canary",
-        ];
-        let bin = bins.iter().find(|p| std::path::Path::new(p).exists())
-            .ok_or("Google Chrome not found")?;
+        let bin = find_chrome().ok_or(
+            "Chrome not found — install Google Chrome or Chromium, or set ZEPTOBOT_CHROME to its binary",
+        )?;
 
-        info!("[CDP] Launching dedicated Chrome at {bin}");
-        std::process::Command::new(bin)
-            .arg("--remote-debugging-port=9222")
+        info!("[CDP] Launching dedicated Chrome at {} (headless: {headless})", bin.display());
+        let mut cmd = std::process::Command::new(&bin);
+        cmd.arg("--remote-debugging-port=9222")
             .arg(format!("--user-data-dir={profile}"))
             .arg("--no-first-run")
-            .arg("--no-default-browser-check")
+            .arg("--no-default-browser-check");
+        if headless {
+            cmd.arg("--headless=new");
+        }
+        if let Ok(extra) = std::env::var(CHROME_ARGS_ENV) {
+            cmd.args(extra.split_whitespace());
+        }
+        let mut child = cmd
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
             .spawn()
             .map_err(|e| format!("Chrome launch failed: {e}"))?;
 
-        tokio::time::sleep(std::time::Duration::from_secs(4)).await;
+        // Poll the DevTools endpoint instead of guessing how long startup takes.
+        let deadline = tokio::time::Instant::now() + CHROME_STARTUP;
+        let http = reqwest::Client::new();
+        loop {
+            let ready = http
+                .get("http://127.0.0.1:9222/json/version")
+                .timeout(std::time::Duration::from_millis(500))
+                .send()
+                .await
+                .is_ok_and(|r| r.status().is_success());
+            if ready {
+                break;
+            }
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(format!(
+                    "Chrome exited ({status}) before DevTools came up — is another Chrome \
+                     already using {profile}?"
+                ));
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(format!(
+                    "Chrome's DevTools endpoint didn't come up within {}s",
+                    CHROME_STARTUP.as_secs()
+                ));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
 
         let browser = self.cdp_connect().await?;
         let pages = browser.pages().await.unwrap_or_default();
         Ok((browser, pages.len()))
     }
 
-    async fn cdp_active_page(&self) -> Result<Page, String> {
+    /// Resolve the page to act on (see [`web_page::pick_tab`]), opening one
+    /// if there are no tabs.
+    async fn cdp_page(&self, params: &Value) -> Result<Page, String> {
         let browser = self.cdp_connect().await?;
-        let pages = browser.pages().await.map_err(|e| format!("pages: {e}"))?;
-        if pages.is_empty() {
-            info!("[CDP] No tabs, creating one...");
-            let page = browser.new_page("about:blank").await
-                .map_err(|e| format!("new_page: {e}"))?;
-            return Ok(page);
+        let mut pages = browser.pages().await.map_err(|e| format!("pages: {e}"))?;
+        let ids: Vec<String> = pages.iter().map(|p| p.target_id().as_ref().to_string()).collect();
+        let selected = self.cdp_tab.lock().await.clone();
+
+        let page = match web_page::pick_tab(&ids, params, selected.as_deref())? {
+            Some(i) => pages.swap_remove(i),
+            None => {
+                info!("[CDP] No tabs, creating one...");
+                browser.new_page("about:blank").await
+                    .map_err(|e| format!("new_page: {e}"))?
+            }
+        };
+        // Start watching as soon as a tab is used, so requests and errors
+        // an action causes are seen by whatever looks next.
+        self.cdp_track(&page).await?;
+        Ok(page)
+    }
+
+    /// The network, console and dialog logs of `page`, subscribing to its
+    /// events the first time.
+    async fn cdp_track(&self, page: &Page) -> Result<Arc<PageLogs>, String> {
+        let id = page.target_id().as_ref().to_string();
+        let mut tracked = self.cdp_logs.lock().await;
+        if let Some(logs) = tracked.get(&id) {
+            return Ok(logs.clone());
+        }
+        let err = |e| format!("Page events: {e}");
+        let mut sent = page.event_listener::<EventRequestWillBeSent>().await.map_err(err)?;
+        let mut responded = page.event_listener::<EventResponseReceived>().await.map_err(err)?;
+        let mut finished = page.event_listener::<EventLoadingFinished>().await.map_err(err)?;
+        let mut failed = page.event_listener::<EventLoadingFailed>().await.map_err(err)?;
+        let mut console = page.event_listener::<EventConsoleApiCalled>().await.map_err(err)?;
+        let mut thrown = page.event_listener::<EventExceptionThrown>().await.map_err(err)?;
+        let mut logged = page.event_listener::<EventEntryAdded>().await.map_err(err)?;
+        let mut dialog_opening = page.event_listener::<EventJavascriptDialogOpening>().await.map_err(err)?;
+        let mut dialog_closed = page.event_listener::<EventJavascriptDialogClosed>().await.map_err(err)?;
+        // Browser-side messages: failed loads, CSP and mixed-content errors.
+        page.execute(LogEnableParams::default()).await.map_err(|e| format!("Log.enable: {e}"))?;
+
+        let logs = Arc::new(PageLogs::default());
+        let events = logs.clone();
+        let dialog_page = page.clone();
+        let policy = self.dialog_policy.clone();
+        tokio::spawn(async move {
+            // The streams end when the tab closes.
+            loop {
+                tokio::select! {
+                    Some(ev) = sent.next() => events.network.lock().await.request_started(
+                        ev.request_id.as_ref(),
+                        &ev.request.url,
+                        &ev.request.method,
+                        ev.r#type.as_ref().map(|t| t.as_ref()).unwrap_or("Other"),
+                    ),
+                    Some(ev) = responded.next() => events.network.lock().await.response_received(
+                        ev.request_id.as_ref(),
+                        ev.response.status,
+                        &ev.response.mime_type,
+                    ),
+                    Some(ev) = finished.next() => {
+                        events.network.lock().await.request_finished(ev.request_id.as_ref(), None)
+                    }
+                    Some(ev) = failed.next() => events.network.lock().await.request_finished(
+                        ev.request_id.as_ref(),
+                        Some(ev.error_text.clone()),
+                    ),
+                    Some(ev) = console.next() => {
+                        let text: Vec<String> = ev.args.iter().map(remote_text).collect();
+                        let frame = ev.stack_trace.as_ref().and_then(|t| t.call_frames.first());
+                        events.console.lock().await.push(
+                            ev.r#type.as_ref(),
+                            "console",
+                            &text.join(" "),
+                            frame.map(|f| f.url.clone()),
+                            frame.map(|f| f.line_number + 1),
+                        );
+                    }
+                    Some(ev) = thrown.next() => {
+                        let details = &ev.exception_details;
+                        let text = details.exception.as_ref()
+                            .and_then(|e| e.description.as_deref())
+                            .and_then(|d| d.lines().next())
+                            .unwrap_or(&details.text);
+                        events.console.lock().await.push(
+                            "error",
+                            "exception",
+                            text,
+                            details.url.clone(),
+                            Some(details.line_number + 1),
+                        );
+                    }
+                    Some(ev) = logged.next() => {
+                        let entry = &ev.entry;
+                        events.console.lock().await.push(
+                            entry.level.as_ref(),
+                            entry.source.as_ref(),
+                            &entry.text,
+                            entry.url.clone(),
+                            entry.line_number.map(|n| n + 1),
+                        );
+                    }
+                    Some(ev) = dialog_opening.next() => {
+                        let dialog = web_dialog::to_json(
+                            ev.r#type.as_ref(),
+                            &ev.message,
+                            ev.default_prompt.as_deref(),
+                            &ev.url,
+                        );
+                        let policy = *policy.lock().await;
+                        if policy == DialogPolicy::Report {
+                            *events.dialog.lock().await = Some(dialog);
+                            events.dialog_opened.notify_waiters();
+                            continue;
+                        }
+                        let mut answer = HandleJavaScriptDialogParams::new(policy == DialogPolicy::Accept);
+                        if policy == DialogPolicy::Accept {
+                            answer.prompt_text = ev.default_prompt.clone();
+                        }
+                        // Noted first: the action may return as soon as the page unblocks.
+                        events.dialog_notes.lock().await.push(web_dialog::auto_note(policy, &dialog));
+                        if let Err(e) = dialog_page.execute(answer).await {
+                            events.dialog_notes.lock().await
+                                .push(format!("Could not answer {}: {e}", web_dialog::describe(&dialog)));
+                        }
+                    }
+                    Some(_) = dialog_closed.next() => *events.dialog.lock().await = None,
+                    else => break,
+                }
+            }
+        });
+        tracked.insert(id, logs.clone());
+        Ok(logs)
+    }
+
+    /// Save downloads to the configured directory and log them as they progress.
+    async fn cdp_track_downloads(&self, browser: &Browser) -> Result<(), String> {
+        let dir = web_files::FilesConfig::load()?.download_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        let behavior = SetDownloadBehaviorParams::builder()
+            .behavior(SetDownloadBehaviorBehavior::Allow)
+            .download_path(dir.to_string_lossy())
+            .events_enabled(true)
+            .build()?;
+        browser.execute(behavior).await.map_err(|e| format!("Browser.setDownloadBehavior: {e}"))?;
+
+        let err = |e| format!("Download events: {e}");
+        let mut began = browser.event_listener::<EventDownloadWillBegin>().await.map_err(err)?;
+        let mut progress = browser.event_listener::<EventDownloadProgress>().await.map_err(err)?;
+        let log = self.cdp_downloads.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(ev) = began.next() => {
+                        log.lock().await.started(&ev.guid, &ev.url, &ev.suggested_filename);
+                    }
+                    Some(ev) = progress.next() => log.lock().await.progress(
+                        &ev.guid,
+                        ev.state.as_ref(),
+                        ev.received_bytes as u64,
+                        ev.file_path.clone().filter(|p| !p.is_empty()),
+                    ),
+                    else => break,
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Wait for the first request after the latest interaction matching the
+    /// `url` pattern (and `method`) in `network`. On timeout, lists the
+    /// latest requests.
+    async fn wait_for_request(&self, network: &Mutex<NetworkLog>, params: &Value) -> Result<RequestEntry, String> {
+        let pattern = params.get("url").and_then(Value::as_str)
+            .filter(|u| !u.is_empty())
+            .ok_or("Missing 'url' pattern")?;
+        let method = params.get("method").and_then(Value::as_str);
+        let timeout = timeout_arg(params, 10_000);
+        let since = self.network_mark.load(Ordering::Relaxed);
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout);
+        loop {
+            if let Some(entry) = network.lock().await.find(pattern, method, since).cloned() {
+                return Ok(entry);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let recent: Vec<String> = network.lock().await.recent(10).iter()
+                    .map(|e| format!("  {}", web_network::summary(&e.to_json())))
+                    .collect();
+                return Err(format!(
+                    "No request matching '{pattern}' within {timeout}ms. Latest requests:\n{}",
+                    if recent.is_empty() { "  (none)".to_string() } else { recent.join("\n") }
+                ));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Wait for the first download after the latest interaction (matching
+    /// `filename`) in `downloads` to finish, as JSON with its saved path.
+    async fn wait_for_download(&self, downloads: &Mutex<DownloadLog>, params: &Value) -> Result<String, String> {
+        let pattern = params.get("filename").and_then(Value::as_str).filter(|p| !p.is_empty());
+        let timeout = timeout_arg(params, 30_000);
+        let since = self.download_mark.load(Ordering::Relaxed);
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout);
+        loop {
+            let found = downloads.lock().await.find(pattern, since).cloned();
+            match found {
+                Some(download) if download.finished() => {
+                    let mut out = download.to_json();
+                    if download.state == "completed" && download.path.is_none() {
+                        let dir = web_files::FilesConfig::load()?.download_dir();
+                        out["path"] = json!(dir.join(&download.filename).to_string_lossy());
+                    }
+                    return Ok(out.to_string());
+                }
+                _ if tokio::time::Instant::now() >= deadline => {
+                    return Err(match found {
+                        Some(d) => format!(
+                            "Timeout after {timeout}ms: {}",
+                            web_files::summary(&d.to_json())
+                        ),
+                        None => format!(
+                            "No download started within {timeout}ms{}",
+                            pattern.map(|p| format!(" matching '{p}'")).unwrap_or_default()
+                        ),
+                    });
+                }
+                _ => {}
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
         }
-        Ok(pages.into_iter().last().unwrap())
+    }
+
+    /// The body of a finished request as text.
+    async fn cdp_response_body(&self, page: &Page, request_id: &str) -> Result<String, String> {
+        let body = page
+            .execute(GetResponseBodyParams::new(request_id.to_string()))
+            .await
+            .map_err(|e| format!("Response body unavailable: {e}"))?
+            .result;
+        let text = if body.base64_encoded {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(&body.body)
+                .map_err(|e| format!("Response body: {e}"))?;
+            String::from_utf8(bytes)
+                .map_err(|e| format!("Binary response body ({} bytes)", e.as_bytes().len()))?
+        } else {
+            body.body
+        };
+        Ok(text)
+    }
+
+    /// Every cookie in the browser, in [`web_session`]'s shape.
+    async fn cdp_cookies(&self) -> Result<Vec<Value>, String> {
+        let browser = self.cdp_connect().await?;
+        let cookies = browser
+            .get_cookies()
+            .await
+            .map_err(|e| format!("Storage.getCookies: {e}"))?;
+        Ok(cookies
+            .iter()
+            .filter_map(|c| serde_json::to_value(c).ok())
+            .map(|c| web_session::from_cdp(&c))
+            .collect())
     }
 
     // -------------------------------------------------------------------
@@ -435,198 +1375,721 @@ impl BrowserState {
         *self.active_backend.lock().await
     }
 
+    /// Attach to Chrome's DevTools endpoint at `url` (e.g. a headless
+    /// instance) and make CDP the active backend. Returns the tab count.
+    pub async fn connect_cdp(&self, url: &str) -> Result<usize, String> {
+        let browser = self.cdp_connect_to(url).await?;
+        *self.active_backend.lock().await = Some(Backend::Cdp);
+        Ok(browser.pages().await.map(|p| p.len()).unwrap_or(0))
+    }
+
+    /// Launch the dedicated Chrome (`~/.zeptobot/chrome-profile`), headless
+    /// or not, and make CDP the active backend. Returns the tab count.
+    pub async fn launch_cdp(&self, headless: bool) -> Result<usize, String> {
+        let (_, tabs) = self.cdp_launch_and_connect(headless).await?;
+        *self.active_backend.lock().await = Some(Backend::Cdp);
+        Ok(tabs)
+    }
+
+    /// Attach to a Firefox remote agent's WebDriver BiDi endpoint at `url`
+    /// (`ws://127.0.0.1:<port>/session`) and make it the active backend.
+    /// Returns the tab count.
+    pub async fn connect_bidi(&self, url: &str) -> Result<usize, String> {
+        let tabs = self.bidi.connect(url).await?;
+        *self.active_backend.lock().await = Some(Backend::Bidi);
+        Ok(tabs)
+    }
+
+    /// Launch the dedicated Firefox (`~/.zeptobot/firefox-profile`), headless
+    /// or not, and make it the active backend. Returns the tab count.
+    pub async fn launch_bidi(&self, headless: bool) -> Result<usize, String> {
+        let tabs = self.bidi.launch_and_connect(headless).await?;
+        *self.active_backend.lock().await = Some(Backend::Bidi);
+        Ok(tabs)
+    }
+
     /// Run a command through whichever backend is active.
-    async fn run(&self, action: &str, params: Value) -> Result<String, String> {
-        match self.backend().await {
+    pub async fn run(&self, action: &str, mut params: Value) -> Result<String, String> {
+        if let Some(loc) = locator_from(action, &params) {
+            params["locator"] = loc;
+        }
+        let interaction = INTERACTIONS.contains(&action);
+        if interaction {
+            self.network_mark.store(web_network::mark(), Ordering::Relaxed);
+            self.console_mark.store(web_console::mark(), Ordering::Relaxed);
+            self.download_mark.store(web_files::mark(), Ordering::Relaxed);
+        }
+        let tab = params.get("tab").cloned();
+        let result = match self.backend().await {
             Some(Backend::Extension) => self.ws_cmd(action, params).await,
             Some(Backend::AgentBrowser) => self.run_ab(action, params).await,
-            Some(Backend::Cdp) => self.run_cdp(action, params).await,
+            Some(Backend::Cdp) => self.run_cdp_watched(action, params).await,
+            Some(Backend::Bidi) => self.run_bidi_watched(action, params).await,
             None => Err("Not connected. Call browser_connect first.".into()),
+        };
+        if !interaction {
+            return result;
+        }
+        // A click handler that throws, or a save request that fails, leaves
+        // no trace in the DOM: report it with the action.
+        let errors = self.new_page_errors(tab).await;
+        match result {
+            Ok(r) => Ok(r + &errors),
+            Err(e) => Err(e + &errors),
+        }
+    }
+
+    /// Run a CDP action through [`web_page::watched`].
+    async fn run_cdp_watched(&self, action: &str, params: Value) -> Result<String, String> {
+        if PAGE_FREE_ACTIONS.contains(&action) {
+            return self.run_cdp(action, params).await;
+        }
+        let page = self.cdp_page(&params).await?;
+        let logs = self.cdp_track(&page).await?;
+        web_page::watched(action, &logs, self.run_cdp(action, params)).await
+    }
+
+    /// Run a Firefox action through [`web_page::watched`].
+    async fn run_bidi_watched(&self, action: &str, params: Value) -> Result<String, String> {
+        if PAGE_FREE_ACTIONS.contains(&action) {
+            return self.run_bidi(action, params).await;
+        }
+        let tab = self.bidi.page(&params).await?;
+        let logs = self.bidi.tab_logs(&tab).await;
+        web_page::watched(action, &logs, self.run_bidi(action, params)).await
+    }
+
+    /// Firefox backend: [`web_bidi::Firefox`], plus the actions that look
+    /// back to the latest interaction.
+    async fn run_bidi(&self, action: &str, params: Value) -> Result<String, String> {
+        match action {
+            "console" => self.bidi.console(&params, self.console_since(&params)).await,
+            "wait_for_request" => {
+                let tab = self.bidi.page(&params).await?;
+                let logs = self.bidi.tab_logs(&tab).await;
+                let entry = self.wait_for_request(&logs.network, &params).await?;
+                let mut out = entry.to_json();
+                if params.get("capture_body").and_then(Value::as_bool) == Some(true) {
+                    match self.bidi.response_body(&entry.id).await {
+                        Ok(body) => out["body"] = json!(body),
+                        Err(e) => out["body_error"] = json!(e),
+                    }
+                }
+                Ok(out.to_string())
+            }
+            "wait_for_download" => self.wait_for_download(self.bidi.downloads(), &params).await,
+            _ => self.bidi.run(action, params).await,
+        }
+    }
+
+    /// Where `browser_console` starts: the latest interaction with
+    /// `since_action`, else the beginning.
+    fn console_since(&self, params: &Value) -> u64 {
+        match params.get("since_action").and_then(Value::as_bool) {
+            Some(true) => self.console_mark.load(Ordering::Relaxed),
+            _ => 0,
+        }
+    }
+
+    /// Errors logged since the latest interaction, as a note to append to
+    /// its result (empty when there are none).
+    async fn new_page_errors(&self, tab: Option<Value>) -> String {
+        let mut params = json!({ "level": "error", "since_action": true, "limit": 5 });
+        if let Some(tab) = tab {
+            params["tab"] = tab;
+        }
+        let raw = match self.backend().await {
+            Some(Backend::Extension) => self.ws_cmd("console", params).await,
+            Some(Backend::Cdp) => self.run_cdp("console", params).await,
+            Some(Backend::Bidi) => self.run_bidi("console", params).await,
+            // agent-browser keeps its own error list; see browser_console.
+            _ => return String::new(),
+        };
+        match raw.ok().and_then(|r| serde_json::from_str::<Vec<Value>>(&r).ok()) {
+            Some(entries) if !entries.is_empty() => {
+                format!("\n\nPage errors since this action:\n{}", web_console::render(&entries))
+            }
+            _ => String::new(),
         }
     }
 
+    /// Frames of `page`, depth-first in document order (main frame first).
+    async fn cdp_frames(&self, page: &Page) -> Result<Vec<FrameInfo>, String> {
+        fn flatten(tree: &FrameTree, out: &mut Vec<FrameInfo>) {
+            out.push(FrameInfo {
+                id: tree.frame.id.inner().clone(),
+                name: tree.frame.name.clone().unwrap_or_default(),
+                url: tree.frame.url.clone(),
+            });
+            for child in tree.child_frames.iter().flatten() {
+                flatten(child, out);
+            }
+        }
+        let tree = page
+            .execute(GetFrameTreeParams::default())
+            .await
+            .map_err(|e| format!("Page.getFrameTree: {e}"))?;
+        let mut frames = Vec::new();
+        flatten(&tree.result.frame_tree, &mut frames);
+        Ok(frames)
+    }
+
+    /// Top-left of a child frame's content box in main-frame viewport coordinates.
+    async fn cdp_frame_offset(&self, page: &Page, frame: &FrameId) -> Result<(f64, f64), String> {
+        let owner = page
+            .execute(GetFrameOwnerParams::new(frame.clone()))
+            .await
+            .map_err(|e| format!("DOM.getFrameOwner: {e}"))?;
+        let quads = page
+            .execute(
+                GetContentQuadsParams::builder()
+                    .backend_node_id(owner.result.backend_node_id)
+                    .build(),
+            )
+            .await
+            .map_err(|e| format!("DOM.getContentQuads: {e}"))?;
+        let points = quads.result.quads.first().map(|q| q.inner().clone()).unwrap_or_default();
+        let min = |start: usize| {
+            points.iter().skip(start).step_by(2).copied().fold(f64::INFINITY, f64::min)
+        };
+        match (min(0), min(1)) {
+            (x, y) if x.is_finite() && y.is_finite() => Ok((x, y)),
+            _ => Err("The frame is not visible".into()),
+        }
+    }
+
+    /// [`PageDriver::call`] over `Runtime.callFunctionOn`.
+    async fn cdp_call(&self, target: &CdpTarget, function: &str, args: &[Value]) -> Result<Value, String> {
+        let result = self.cdp_call_object(target, function, args, true).await?;
+        Ok(result.value.unwrap_or(Value::Null))
+    }
+
+    /// [`Self::cdp_call`], returning the raw result: a handle to a page object
+    /// (e.g. an element) unless `by_value`.
+    async fn cdp_call_object(
+        &self,
+        target: &CdpTarget,
+        function: &str,
+        args: &[Value],
+        by_value: bool,
+    ) -> Result<RemoteObject, String> {
+        let call = CallFunctionOnParams::builder()
+            .function_declaration(format!(
+                "function() {{\n{DOM_JS}\n;return ({function}).apply(this, arguments);\n}}"
+            ))
+            .arguments(
+                args.iter()
+                    .map(|v| CallArgument::builder().value(v.clone()).build())
+                    .collect::<Vec<_>>(),
+            )
+            .execution_context_id(target.context)
+            .await_promise(true)
+            .return_by_value(by_value)
+            .build()?;
+        let resp = target.page.execute(call).await.map_err(|e| format!("js: {e}"))?;
+        if let Some(ex) = &resp.result.exception_details {
+            let msg = ex
+                .exception
+                .as_ref()
+                .and_then(|e| e.description.clone())
+                .unwrap_or_else(|| ex.text.clone());
+            return Err(format!("js: {msg}"));
+        }
+        Ok(resp.result.result.clone())
+    }
+
+    /// Full accessibility tree of the page plus refs for its interactive nodes,
+    /// as `{"nodes": [AXNode...], "refs": {"<backendNodeId>": "e3:k3f9"}}`.
+    async fn cdp_snapshot(&self, target: &CdpTarget) -> Result<Value, String> {
+        let page = &target.page;
+        let tree = page
+            .execute(GetFullAxTreeParams::default())
+            .await
+            .map_err(|e| format!("Accessibility.getFullAXTree: {e}"))?;
+        let nodes: Vec<Value> = tree
+            .result
+            .nodes
+            .iter()
+            .filter_map(|n| serde_json::to_value(n).ok())
+            .collect();
+
+        // Load window.__zb, then tag each interactive node's element with a ref.
+        self.cdp_call(target, "() => null", &[]).await?;
+        let mut refs = serde_json::Map::new();
+        for id in web_snapshot::ref_candidates(&nodes) {
+            let resolve = ResolveNodeParams::builder()
+                .backend_node_id(BackendNodeId::new(id))
+                .build();
+            let Some(object_id) = page
+                .execute(resolve)
+                .await
+                .ok()
+                .and_then(|r| r.result.object.object_id.clone())
+            else {
+                continue;
+            };
+            let call = CallFunctionOnParams::builder()
+                .function_declaration(
+                    "function() { return window.__zb ? window.__zb.ref(this) : null; }",
+                )
+                .object_id(object_id)
+                .return_by_value(true)
+                .build()?;
+            if let Ok(resp) = page.execute(call).await {
+                if let Some(Value::String(r)) = resp.result.result.value.clone() {
+                    refs.insert(id.to_string(), json!(r));
+                }
+            }
+        }
+        Ok(json!({ "nodes": nodes, "refs": refs }))
+    }
+
     /// CDP fallback: execute an action using chromiumoxide.
-    async fn run_cdp(&self, action: &str, params: Value) -> Result<String, String> {
+    async fn run_cdp(&self, action: &str, mut params: Value) -> Result<String, String> {
+        let cdp = Cdp(self);
+        web_page::resolve_args(&cdp, action, &mut params).await?;
         match action {
             "get_tabs" => {
                 let browser = self.cdp_connect().await?;
                 let pages = browser.pages().await.unwrap_or_default();
-                let tabs: Vec<Value> = pages.iter().enumerate().map(|(i, _)| {
-                    json!({ "id": i, "title": "(cdp tab)", "url": "" })
-                }).collect();
+                let current = self.cdp_page(&json!({})).await.ok()
+                    .map(|p| p.target_id().as_ref().to_string());
+                let mut tabs = Vec::new();
+                for page in &pages {
+                    let id = page.target_id().as_ref().to_string();
+                    tabs.push(json!({
+                        "title": page.get_title().await.ok().flatten().unwrap_or_default(),
+                        "url": page.url().await.ok().flatten().unwrap_or_default(),
+                        "active": current.as_deref() == Some(id.as_str()),
+                        "id": id,
+                    }));
+                }
                 Ok(serde_json::to_string(&tabs).unwrap_or_default())
             }
+            "switch_tab" => {
+                if tab_arg(&params).is_none() {
+                    return Err("Missing 'tab'".into());
+                }
+                let page = self.cdp_page(&params).await?;
+                page.bring_to_front().await.map_err(|e| format!("bring_to_front: {e}"))?;
+                let id = page.target_id().as_ref().to_string();
+                *self.cdp_tab.lock().await = Some(id.clone());
+                let title = page.get_title().await.ok().flatten().unwrap_or_default();
+                Ok(format!("Switched to tab {id} — title: \"{title}\""))
+            }
+            "new_tab" => {
+                let browser = self.cdp_connect().await?;
+                let url = params.get("url").and_then(Value::as_str).unwrap_or("about:blank");
+                let page = browser.new_page(url).await.map_err(|e| format!("new_page: {e}"))?;
+                let id = page.target_id().as_ref().to_string();
+                *self.cdp_tab.lock().await = Some(id.clone());
+                Ok(format!("Opened new tab {id} at {url}"))
+            }
+            "close_tab" => {
+                let page = self.cdp_page(&params).await?;
+                let id = page.target_id().as_ref().to_string();
+                page.close().await.map_err(|e| format!("close: {e}"))?;
+                self.cdp_logs.lock().await.remove(&id);
+                let mut current = self.cdp_tab.lock().await;
+                if current.as_deref() == Some(id.as_str()) {
+                    *current = None;
+                }
+                Ok(format!("Closed tab {id}"))
+            }
             "navigate" => {
                 let url = params.get("url").and_then(Value::as_str)
                     .ok_or("Missing 'url'")?;
-                let page = self.cdp_active_page().await?;
-                page.goto(url).await.map_err(|e| format!("goto: {e}"))?;
-                tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-                let title = page.evaluate("document.title").await
-                    .ok().and_then(|v| v.into_value::<String>().ok())
-                    .unwrap_or_default();
-                Ok(format!("Navigated to {url} — title: \"{title}\""))
-            }
-            "click" => {
-                let page = self.cdp_active_page().await?;
-                let selector = params.get("selector").and_then(Value::as_str);
-                let text = params.get("text").and_then(Value::as_str);
-                if let Some(sel) = selector {
-                    let el = page.find_element(sel).await.map_err(|e| format!("not found: {e}"))?;
-                    el.click().await.map_err(|e| format!("click: {e}"))?;
-                    Ok(format!("clicked: {sel}"))
-                } else if let Some(txt) = text {
-                    let js = format!(
-                        r#"(() => {{
-                            const lower = '{lower}';
-                            const xpath = `//*[contains(translate(text(),'ABCDEFGHIJKLMNOPQRSTUVWXYZ','abcdefghijklmnopqrstuvwxyz'), '${{lower}}')]`;
-                            const r = document.evaluate(xpath, document, null, XPathResult.FIRST_ORDERED_NODE_TYPE, null);
-                            if (r.singleNodeValue) {{ r.singleNodeValue.click(); return 'clicked text: {txt}'; }}
-                            const a = document.querySelector('[aria-label*="{txt}" i]');
-                            if (a) {{ a.click(); return 'clicked aria: {txt}'; }}
-                            return 'not_found: {txt}';
-                        }})()"#,
-                        lower = txt.to_lowercase().replace('\'', "\\'"),
-                        txt = txt.replace('"', "\\\"")
-                    );
-                    let val = page.evaluate(js).await.map_err(|e| format!("js: {e}"))?;
-                    let r = val.into_value::<String>().unwrap_or_default();
-                    if r.starts_with("not_found") { Err(r) } else { Ok(r) }
-                } else {
-                    Err("Provide 'selector' or 'text'".into())
+                let wait_until = wait_until_arg(&params)?;
+                let timeout = timeout_arg(&params, 15_000);
+                let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout);
+                let page = self.cdp_page(&params).await?;
+                let logs = self.cdp_track(&page).await?;
+                let err = |e| format!("navigate: {e}");
+                let mut dom_ready = page.event_listener::<EventDomContentEventFired>().await.map_err(err)?;
+                let mut loaded = page.event_listener::<EventLoadEventFired>().await.map_err(err)?;
+
+                let nav = page.execute(NavigateParams::new(url)).await.map_err(err)?.result;
+                if let Some(e) = nav.error_text.filter(|e| !e.is_empty()) {
+                    return Err(format!("navigate: {e}"));
                 }
-            }
-            "type" => {
-                let page = self.cdp_active_page().await?;
-                let value = params.get("value").and_then(Value::as_str)
-                    .ok_or("Missing 'value'")?;
-                if let Some(sel) = params.get("selector").and_then(Value::as_str) {
-                    let el = page.find_element(sel).await.map_err(|e| format!("not found: {e}"))?;
-                    let _ = el.click().await;
-                    if params.get("clear_first").and_then(Value::as_bool).unwrap_or(true) {
-                        let _ = page.evaluate(format!(
-                            "document.querySelector('{}').value = ''",
-                            sel.replace('\'', "\\'")
-                        )).await;
+                let mut note = String::new();
+                // Same-document navigations (#hash) have no loader and fire no load events.
+                if nav.loader_id.is_some() {
+                    let fired = match wait_until {
+                        "domcontentloaded" => tokio::time::timeout_at(deadline, dom_ready.next()).await.map(drop),
+                        _ => tokio::time::timeout_at(deadline, loaded.next()).await.map(drop),
+                    };
+                    if fired.is_err() {
+                        note = format!(" (still loading after {timeout}ms)");
+                    }
+                }
+                if wait_until == "networkidle" && note.is_empty() {
+                    if let Err(busy) = web_page::network_idle(&logs.network, deadline).await {
+                        note = format!(" ({busy} after {timeout}ms)");
                     }
-                    el.type_str(value).await.map_err(|e| format!("type: {e}"))?;
-                    Ok(format!("typed into {sel}"))
-                } else {
-                    // Type into focused element using native setter (React-compatible)
-                    let js = format!(
-                        r#"(() => {{
-                            const el = document.activeElement;
-                            if (!el || !(el.tagName==='INPUT'||el.tagName==='TEXTAREA'||el.isContentEditable)) {{
-                                return 'no focused input';
-                            }}
-                            if (el.isContentEditable) {{
-                                el.textContent = '{val}';
-                                el.dispatchEvent(new Event('input', {{bubbles:true}}));
-                            }} else {{
-                                // Use native setter to trigger React/Vue/Angular state updates
-                                const proto = el.tagName === 'TEXTAREA'
-                                    ? HTMLTextAreaElement.prototype
-                                    : HTMLInputElement.prototype;
-                                const setter = Object.getOwnPropertyDescriptor(proto, 'value').set;
-                                setter.call(el, '{val}');
-                                el.dispatchEvent(new Event('input', {{bubbles:true}}));
-                                el.dispatchEvent(new Event('change', {{bubbles:true}}));
-                            }}
-                            return 'typed';
-                        }})()"#,
-                        val = value.replace('\'', "\\'").replace('\\', "\\\\")
-                    );
-                    let val = page.evaluate(js).await.map_err(|e| format!("js: {e}"))?;
-                    Ok(val.into_value::<String>().unwrap_or_default())
                 }
+                let title = page.get_title().await.ok().flatten().unwrap_or_default();
+                Ok(format!("Navigated to {url} — title: \"{title}\"{note}"))
             }
-            "read" => {
-                let page = self.cdp_active_page().await?;
-                let selector = params.get("selector").and_then(Value::as_str);
-                let full = params.get("page_text").and_then(Value::as_bool).unwrap_or(false);
-                let js = if let Some(sel) = selector {
-                    format!(
-                        "(() => {{ const e = document.querySelector('{}'); return e ? (e.innerText||e.textContent||'').substring(0,3000) : 'not found'; }})()",
-                        sel.replace('\'', "\\'")
-                    )
-                } else if full {
-                    "(document.body.innerText||'').substring(0,5000)".into()
+            "screenshot" => {
+                let target = web_page::target(&cdp, &params).await?;
+                let element = match params.get("selector").and_then(Value::as_str) {
+                    Some(sel) => {
+                        let b = self.cdp_call(&target, JS_BOX, &[json!(sel)]).await?;
+                        if let Some(e) = b.get("error").and_then(Value::as_str) {
+                            return Err(e.to_string());
+                        }
+                        Some(b)
+                    }
+                    None => None,
+                };
+                let metrics = target.page
+                    .execute(GetLayoutMetricsParams::default())
+                    .await
+                    .map_err(|e| format!("Page.getLayoutMetrics: {e}"))?
+                    .result;
+                // Clips are in document coordinates of the main frame.
+                let scroll = &metrics.css_visual_viewport;
+                let clip = if let Some(b) = element {
+                    let n = |k: &str| b.get(k).and_then(Value::as_f64).unwrap_or(0.0);
+                    Some(Viewport {
+                        x: scroll.page_x + target.offset.0 + n("x"),
+                        y: scroll.page_y + target.offset.1 + n("y"),
+                        width: n("width"),
+                        height: n("height"),
+                        scale: 1.0,
+                    })
+                } else if params.get("full_page").and_then(Value::as_bool) == Some(true) {
+                    let size = &metrics.css_content_size;
+                    Some(Viewport {
+                        x: 0.0,
+                        y: 0.0,
+                        width: size.width,
+                        height: size.height.min(MAX_CAPTURE_HEIGHT),
+                        scale: 1.0,
+                    })
                 } else {
-                    r#"JSON.stringify({title:document.title,url:location.href})"#.into()
+                    None
                 };
-                let val = page.evaluate(js).await.map_err(|e| format!("js: {e}"))?;
-                Ok(val.into_value::<String>().unwrap_or_default())
+                let mut capture = CaptureScreenshotParams::builder().format(CaptureScreenshotFormat::Png);
+                if let Some(clip) = clip {
+                    capture = capture.clip(clip).capture_beyond_viewport(true);
+                }
+                let shot = target.page
+                    .execute(capture.build())
+                    .await
+                    .map_err(|e| format!("Page.captureScreenshot: {e}"))?;
+                let data: &str = shot.result.data.as_ref();
+                Ok(json!({ "data": data }).to_string())
+            }
+            "upload" => {
+                let target = web_page::target(&cdp, &params).await?;
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref', 'selector', 'label' or 'text'")?;
+                let files = files_arg(&params)?;
+                let input = self
+                    .cdp_call_object(&target, "(sel) => window.__zb.fileInput(sel)", &[json!(sel)], false)
+                    .await?
+                    .object_id
+                    .ok_or("No file input found")?;
+                let set = SetFileInputFilesParams::builder()
+                    .files(files.clone())
+                    .object_id(input)
+                    .build()?;
+                target.page.execute(set).await.map_err(|e| format!("DOM.setFileInputFiles: {e}"))?;
+                Ok(format!("Attached {}", describe_files(&files)))
+            }
+            "go_back" | "go_forward" => {
+                let page = self.cdp_page(&params).await?;
+                let history = page
+                    .execute(GetNavigationHistoryParams::default())
+                    .await
+                    .map_err(|e| format!("Page.getNavigationHistory: {e}"))?;
+                let step = if action == "go_back" { -1 } else { 1 };
+                let entry = usize::try_from(history.result.current_index + step)
+                    .ok()
+                    .and_then(|i| history.result.entries.get(i))
+                    .ok_or(if step < 0 { "No previous page in this tab's history" } else { "No next page in this tab's history" })?;
+                page.execute(NavigateToHistoryEntryParams::new(entry.id))
+                    .await
+                    .map_err(|e| format!("Page.navigateToHistoryEntry: {e}"))?;
+                // Back/forward cache restores don't fire a fresh load; don't wait forever.
+                let _ = tokio::time::timeout(std::time::Duration::from_secs(15), page.wait_for_navigation()).await;
+                Ok(format!("Went {} to {} — title: \"{}\"",
+                    if step < 0 { "back" } else { "forward" }, entry.url, entry.title))
+            }
+            "reload" => {
+                let page = self.cdp_page(&params).await?;
+                page.reload().await.map_err(|e| format!("reload: {e}"))?;
+                let title = page.get_title().await.ok().flatten().unwrap_or_default();
+                let url = page.url().await.ok().flatten().unwrap_or_default();
+                Ok(format!("Reloaded {url} — title: \"{title}\""))
+            }
+            "snapshot" => {
+                let target = web_page::target(&cdp, &params).await?;
+                Ok(self.cdp_snapshot(&target).await?.to_string())
             }
             "list_elements" => {
-                let page = self.cdp_active_page().await?;
+                let target = web_page::target(&cdp, &params).await?;
                 let filter = params.get("filter").and_then(Value::as_str).unwrap_or("");
-                let js = format!(
-                    r#"(() => {{
-                        const els = []; const f = '{}';
-                        for (const el of document.querySelectorAll('a,button,input,textarea,select,[role="button"],[role="link"],[aria-label]')) {{
-                            const t = (el.innerText||el.value||el.placeholder||el.getAttribute('aria-label')||'').trim().substring(0,80);
-                            if (f && !t.toLowerCase().includes(f.toLowerCase())) continue;
-                            const tag = el.tagName.toLowerCase();
-                            const id = el.id ? '#'+el.id : '';
-                            const cls = el.className && typeof el.className==='string' ? '.'+el.className.split(' ').filter(Boolean).slice(0,2).join('.') : '';
-                            els.push({{ tag, text: t, type: el.type||el.getAttribute('role')||'', selector: tag+id+cls }});
-                            if (els.length >= 100) break;
-                        }}
-                        return JSON.stringify(els);
-                    }})()"#,
-                    filter.replace('\'', "\\'")
-                );
-                let val = page.evaluate(js).await.map_err(|e| format!("js: {e}"))?;
-                Ok(val.into_value::<String>().unwrap_or_default())
+                let list = "(filter) => window.__zb.listElements(filter)";
+                if frame_arg(&params).is_some() {
+                    let elements = self.cdp_call(&target, list, &[json!(filter)]).await?;
+                    return Ok(elements.to_string());
+                }
+
+                // Whole tab: every frame we can reach, tagged with its index.
+                let frames = self.cdp_frames(&target.page).await?;
+                let mut all = Vec::new();
+                for (i, frame) in frames.iter().enumerate() {
+                    let context = if i == 0 {
+                        Some(target.context)
+                    } else {
+                        target.page.frame_execution_context(FrameId::new(frame.id.clone())).await.ok().flatten()
+                    };
+                    let Some(context) = context else { continue };
+                    let in_frame = CdpTarget { page: target.page.clone(), context, offset: (0.0, 0.0) };
+                    let Ok(Value::Array(elements)) = self.cdp_call(&in_frame, list, &[json!(filter)]).await else {
+                        continue;
+                    };
+                    for mut el in elements {
+                        if i > 0 {
+                            el["frame"] = json!(i);
+                            el["frame_url"] = json!(frame.url);
+                        }
+                        all.push(el);
+                    }
+                }
+                Ok(Value::Array(all).to_string())
             }
             "execute_js" => {
-                let page = self.cdp_active_page().await?;
+                let target = web_page::target(&cdp, &params).await?;
                 let code = params.get("code").and_then(Value::as_str)
                     .ok_or("Missing 'code'")?;
-                let val = page.evaluate(code).await.map_err(|e| format!("js: {e}"))?;
+                let eval = EvaluateParams::builder()
+                    .expression(code)
+                    .context_id(target.context)
+                    .await_promise(true)
+                    .return_by_value(true)
+                    .build()?;
+                let val = target.page.evaluate_expression(eval).await.map_err(|e| format!("js: {e}"))?;
                 Ok(val.into_value::<serde_json::Value>()
                     .map(|v| match v { Value::String(s) => s, o => o.to_string() })
                     .unwrap_or_else(|_| "undefined".into()))
             }
-            "wait_for" => {
-                let page = self.cdp_active_page().await?;
-                let selector = params.get("selector").and_then(Value::as_str);
-                let text = params.get("text").and_then(Value::as_str);
-                let timeout = params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(5000).min(15000);
-
-                let js = if let Some(sel) = selector {
-                    format!(
-                        "(() => {{ const e = document.querySelector('{}'); return e ? (e.innerText||e.textContent||'found') : null; }})()",
-                        sel.replace('\'', "\\'")
-                    )
-                } else if let Some(txt) = text {
-                    format!(
-                        r#"(() => {{
-                            const w = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT);
-                            while (w.nextNode()) {{ if (w.currentNode.textContent.toLowerCase().includes('{}')) return w.currentNode.parentElement.innerText.substring(0,200); }}
-                            return null;
-                        }})()"#,
-                        txt.to_lowercase().replace('\'', "\\'")
-                    )
-                } else {
-                    return Err("Provide 'selector' or 'text'".into());
-                };
-
-                let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout);
-                loop {
-                    if let Ok(val) = page.evaluate(js.as_str()).await {
-                        if let Ok(result) = val.into_value::<String>() {
-                            return Ok(result);
+            "console" => {
+                let page = self.cdp_page(&params).await?;
+                let logs = self.cdp_track(&page).await?;
+                Ok(web_page::console(&logs, &params, self.console_since(&params)).await)
+            }
+            "dialog" => {
+                let page = self.cdp_page(&params).await?;
+                let logs = self.cdp_track(&page).await?;
+                let dialog = logs.dialog.lock().await.clone().ok_or("No dialog is open")?;
+                let accept = params.get("action").and_then(Value::as_str) != Some("dismiss");
+                let mut answer = HandleJavaScriptDialogParams::new(accept);
+                answer.prompt_text = params.get("prompt_text").and_then(Value::as_str).map(String::from);
+                page.execute(answer)
+                    .await
+                    .map_err(|e| format!("Page.handleJavaScriptDialog: {e}"))?;
+                *logs.dialog.lock().await = None;
+                Ok(format!(
+                    "{} the {}",
+                    if accept { "Accepted" } else { "Dismissed" },
+                    web_dialog::describe(&dialog)
+                ))
+            }
+            "wait_for_download" => {
+                self.cdp_connect().await?;
+                self.wait_for_download(&self.cdp_downloads, &params).await
+            }
+            "cookies" => {
+                let page = self.cdp_page(&params).await?;
+                let url = page.url().await.ok().flatten().unwrap_or_default();
+                let domain = cookie_domain_arg(&params, &url)?;
+                let name = params.get("name").and_then(Value::as_str);
+                let cookies: Vec<Value> = self.cdp_cookies().await?
+                    .into_iter()
+                    .filter(|c| web_session::cookie_matches(c, &domain, name))
+                    .collect();
+                if params.get("clear").and_then(Value::as_bool) == Some(true) {
+                    for cookie in &cookies {
+                        let mut delete = DeleteCookiesParams::new(cookie["name"].as_str().unwrap_or_default());
+                        delete.domain = cookie["domain"].as_str().map(String::from);
+                        delete.path = cookie["path"].as_str().map(String::from);
+                        page.execute(delete)
+                            .await
+                            .map_err(|e| format!("Network.deleteCookies: {e}"))?;
+                    }
+                }
+                Ok(json!({ "domain": domain, "cookies": cookies }).to_string())
+            }
+            "export_session" => {
+                let cookies = self.cdp_cookies().await?;
+                // localStorage can only be read from a page: take it from every open site.
+                let browser = self.cdp_connect().await?;
+                let mut local_storage = serde_json::Map::new();
+                for page in browser.pages().await.unwrap_or_default() {
+                    let url = page.url().await.ok().flatten().unwrap_or_default();
+                    if web_session::host_of(&url).is_none() {
+                        continue;
+                    }
+                    let tab = json!({ "tab": page.target_id().as_ref() });
+                    let read = async {
+                        let target = web_page::target(&cdp, &tab).await?;
+                        page_result(self.cdp_call(&target, JS_STORAGE, &[json!("local"), json!({})]).await?)
+                    };
+                    // A tab blocked by a dialog runs no script; leave it out.
+                    let Ok(Ok(raw)) = tokio::time::timeout(std::time::Duration::from_secs(3), read).await else {
+                        continue;
+                    };
+                    let Ok(found) = serde_json::from_str::<Value>(&raw) else { continue };
+                    if let (Some(origin), Some(items)) = (found["origin"].as_str(), found["items"].as_object()) {
+                        if !items.is_empty() {
+                            local_storage.insert(origin.to_string(), json!(items));
                         }
                     }
-                    if tokio::time::Instant::now() >= deadline {
-                        return Err(format!("Timeout after {timeout}ms"));
+                }
+                Ok(json!({ "cookies": cookies, "local_storage": local_storage }).to_string())
+            }
+            "import_session" => {
+                let bundle = params.get("bundle").ok_or("Missing 'bundle'")?;
+                let cookies: Vec<CookieParam> = bundle.get("cookies").and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .map(|c| serde_json::from_value(web_session::to_cdp(c))
+                        .map_err(|e| format!("Bad cookie in the session bundle: {e}")))
+                    .collect::<Result<_, _>>()?;
+                let count = cookies.len();
+                let browser = self.cdp_connect().await?;
+                if !cookies.is_empty() {
+                    browser.set_cookies(cookies).await.map_err(|e| format!("Storage.setCookies: {e}"))?;
+                }
+                // localStorage goes into an open tab of each origin.
+                let pages = browser.pages().await.unwrap_or_default();
+                let mut urls = Vec::new();
+                for page in &pages {
+                    urls.push(page.url().await.ok().flatten().unwrap_or_default());
+                }
+                let mut not_open = Vec::new();
+                for (origin, items) in bundle.get("local_storage").and_then(Value::as_object).into_iter().flatten() {
+                    let Some(page) = pages.iter().zip(&urls)
+                        .find(|(_, url)| *url == origin || url.starts_with(&format!("{origin}/")))
+                        .map(|(page, _)| page)
+                    else {
+                        not_open.push(origin.clone());
+                        continue;
+                    };
+                    let target = web_page::target(&cdp, &json!({ "tab": page.target_id().as_ref() })).await?;
+                    let op = json!({ "set": items });
+                    page_result(self.cdp_call(&target, JS_STORAGE, &[json!("local"), op]).await?)?;
+                }
+                Ok(json!({ "cookies": count, "not_open": not_open }).to_string())
+            }
+            "wait_for_request" => {
+                let page = self.cdp_page(&params).await?;
+                let logs = self.cdp_track(&page).await?;
+                let entry = self.wait_for_request(&logs.network, &params).await?;
+                let mut out = entry.to_json();
+                if params.get("capture_body").and_then(Value::as_bool) == Some(true) {
+                    match self.cdp_response_body(&page, &entry.id).await {
+                        Ok(body) => out["body"] = json!(body),
+                        Err(e) => out["body_error"] = json!(e),
                     }
-                    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
                 }
+                Ok(out.to_string())
             }
-            _ => Err(format!("Unknown CDP action: {action}")),
+            _ => web_page::run(&cdp, action, &params).await,
+        }
+    }
+}
+
+/// The CDP backend as a [`PageDriver`] for the shared page actions.
+struct Cdp<'a>(&'a BrowserState);
+
+#[async_trait]
+impl PageDriver for Cdp<'_> {
+    type Target = CdpTarget;
+
+    const NAME: &'static str = "CDP";
+
+    async fn main_target(&self, params: &Value) -> Result<CdpTarget, String> {
+        let page = self.0.cdp_page(params).await?;
+        let context = page
+            .execution_context()
+            .await
+            .map_err(|e| format!("js: {e}"))?
+            .ok_or("js: page has no JavaScript context")?;
+        Ok(CdpTarget { page, context, offset: (0.0, 0.0) })
+    }
+
+    async fn frames(&self, main: &CdpTarget) -> Result<Vec<FrameInfo>, String> {
+        self.0.cdp_frames(&main.page).await
+    }
+
+    async fn frame_target(
+        &self,
+        main: CdpTarget,
+        frames: &[FrameInfo],
+        index: usize,
+    ) -> Result<CdpTarget, String> {
+        let frame = &frames[index];
+        let id = FrameId::new(frame.id.clone());
+        let context = main
+            .page
+            .frame_execution_context(id.clone())
+            .await
+            .map_err(|e| format!("js: {e}"))?
+            .ok_or_else(|| {
+                format!(
+                    "Frame {index} ({}) has no script context here — cross-origin frames \
+                     run out of process; use the extension backend for them",
+                    frame.url
+                )
+            })?;
+        let offset = self.0.cdp_frame_offset(&main.page, &id).await?;
+        Ok(CdpTarget { page: main.page, context, offset })
+    }
+
+    async fn call(&self, target: &CdpTarget, function: &str, args: &[Value]) -> Result<Value, String> {
+        self.0.cdp_call(target, function, args).await
+    }
+
+    async fn logs(&self, target: &CdpTarget) -> Result<Arc<PageLogs>, String> {
+        self.0.cdp_track(&target.page).await
+    }
+
+    /// `Input` events take main-frame viewport coordinates.
+    fn input_origin(&self, target: &CdpTarget) -> (f64, f64) {
+        target.offset
+    }
+
+    async fn click(&self, target: &CdpTarget, x: f64, y: f64) -> Result<(), String> {
+        for ev in web_input::click_events(x, y)? {
+            target.page.execute(ev).await.map_err(|e| format!("Input.dispatchMouseEvent: {e}"))?;
+        }
+        Ok(())
+    }
+
+    async fn hover(&self, target: &CdpTarget, x: f64, y: f64) -> Result<(), String> {
+        target.page.execute(web_input::hover_event(x, y)?)
+            .await
+            .map_err(|e| format!("Input.dispatchMouseEvent: {e}"))?;
+        Ok(())
+    }
+
+    async fn press(&self, target: &CdpTarget, combo: &str) -> Result<(), String> {
+        for ev in web_input::key_events(combo)? {
+            target.page.execute(ev).await.map_err(|e| format!("Input.dispatchKeyEvent: {e}"))?;
         }
+        Ok(())
+    }
+
+    async fn insert_text(&self, target: &CdpTarget, text: &str) -> Result<(), String> {
+        target.page.execute(InsertTextParams::new(text))
+            .await
+            .map_err(|e| format!("Input.insertText: {e}"))?;
+        Ok(())
     }
 }
 
@@ -643,16 +2106,47 @@ impl Tool for BrowserConnectTool {
     fn description(&self) -> &str {
         "Connect to Chrome for web page automation. Tries: (1) ZeptoBot Bridge extension \
          (existing Chrome, all logins preserved), (2) agent-browser CLI (accessibility-tree \
-         based), (3) CDP with dedicated Chrome instance. Call this before other browser_ tools."
+         based), (3) CDP with dedicated Chrome instance. Call this before other browser_ tools. \
+         headless=true goes straight to the dedicated Chrome, without a window. \
+         browser='firefox' drives Firefox over WebDriver BiDi instead (a Firefox already \
+         listening on port 9223, else a dedicated one)."
     }
 
     fn parameters(&self) -> Value {
-        json!({ "type": "object", "properties": {}, "required": [] })
+        json!({
+            "type": "object",
+            "properties": {
+                "headless": {
+                    "type": "boolean",
+                    "description": "Run the dedicated Chrome or Firefox without a window (default: false, or ZEPTOBOT_CHROME_HEADLESS)"
+                },
+                "browser": {
+                    "type": "string",
+                    "enum": ["chrome", "firefox"],
+                    "description": "Which browser to drive (default: chrome)"
+                }
+            },
+            "required": []
+        })
     }
 
     fn category(&self) -> ToolCategory { ToolCategory::Shell }
 
-    async fn execute(&self, _args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let headless = args.get("headless").and_then(Value::as_bool).unwrap_or_else(headless_default);
+        match args.get("browser").and_then(Value::as_str).unwrap_or("chrome") {
+            "chrome" => {}
+            "firefox" => return self.connect_firefox(headless).await,
+            other => {
+                return Ok(ToolOutput::error(format!(
+                    "Unknown browser '{other}' — use 'chrome' or 'firefox'"
+                )))
+            }
+        }
+        if headless {
+            return self.connect_cdp(true).await;
+        }
+
         // ------- Try 1: Extension bridge -------
         let _ = self.state.ensure_ws_server().await;
 
@@ -703,6 +2197,14 @@ impl Tool for BrowserConnectTool {
             }
         }
 
+        self.connect_cdp(false).await
+    }
+}
+
+impl BrowserConnectTool {
+    /// Tries 3 and 4: a Chrome already listening on the DevTools port, else
+    /// the dedicated one.
+    async fn connect_cdp(&self, headless: bool) -> ZeptoResult<ToolOutput> {
         // ------- Try 3: CDP on existing debug Chrome -------
         info!("[CDP] Trying CDP fallback...");
         if let Ok(browser) = self.state.cdp_connect().await {
@@ -717,17 +2219,16 @@ impl Tool for BrowserConnectTool {
 
         // ------- Try 4: Launch dedicated Chrome with CDP -------
         info!("[CDP] Launching dedicated Chrome instance...");
-        match self.state.cdp_launch_and_connect().await {
-            Ok((_browser, n)) => {
-                *self.state.active_backend.lock().await = Some(Backend::Cdp);
-                Ok(ToolOutput::llm_only(format!(
-                    "Launched dedicated Chrome with CDP (fresh profile). {} tab(s). \
-                     Note: this is a separate profile — you may need to sign in. \
-                     For zero-setup: install the ZeptoBot Bridge extension. \
-                     Use browser_navigate to open URLs.",
-                    n
-                )))
-            }
+        match self.state.launch_cdp(headless).await {
+            Ok(n) => Ok(ToolOutput::llm_only(format!(
+                "Launched dedicated {}Chrome with CDP (fresh profile). {} tab(s). \
+                 Note: this is a separate profile — you may need to sign in. \
+                 For zero-setup: install the ZeptoBot Bridge extension. \
+                 Use browser_navigate to open URLs.",
+                if headless { "headless " } else { "" },
+                n
+            ))),
+            Err(e) if headless => Ok(ToolOutput::error(format!("Headless Chrome failed: {e}"))),
             Err(e) => Ok(ToolOutput::error(format!(
                 "All connection methods failed.\n\
                  Best: Install ZeptoBot Bridge extension (chrome://extensions → Load unpacked → zeptobot/extension/).\n\
@@ -736,6 +2237,32 @@ impl Tool for BrowserConnectTool {
             ))),
         }
     }
+
+    /// Firefox: one already listening for WebDriver BiDi, else the dedicated one.
+    async fn connect_firefox(&self, headless: bool) -> ZeptoResult<ToolOutput> {
+        let url = format!("ws://127.0.0.1:{}/session", web_bidi::PORT);
+        if let Ok(n) = self.state.connect_bidi(&url).await {
+            return Ok(ToolOutput::llm_only(format!(
+                "Connected to Firefox via WebDriver BiDi. {n} tab(s) open. \
+                 Use browser_navigate to open URLs."
+            )));
+        }
+
+        info!("[BiDi] Launching dedicated Firefox instance...");
+        match self.state.launch_bidi(headless).await {
+            Ok(n) => Ok(ToolOutput::llm_only(format!(
+                "Launched dedicated {}Firefox with WebDriver BiDi (separate profile). {n} tab(s). \
+                 Note: this is a separate profile — you may need to sign in. \
+                 Use browser_navigate to open URLs.",
+                if headless { "headless " } else { "" },
+            ))),
+            Err(e) => Ok(ToolOutput::error(format!(
+                "Firefox connection failed: {e}\n\
+                 To use your own Firefox, start it with --remote-debugging-port={}.",
+                web_bidi::PORT
+            ))),
+        }
+    }
 }
 
 // ===========================================================================
@@ -748,18 +2275,27 @@ pub struct BrowserClickTool { pub state: Arc<BrowserState> }
 impl Tool for BrowserClickTool {
     fn name(&self) -> &str { "browser_click" }
     fn description(&self) -> &str {
-        "Click an element by CSS selector or text content. \
-         Examples: selector='button.compose', text='Compose'"
+        "Click an element by ref (from browser_list_elements), locator or CSS selector. \
+         Locators match visible elements only; if several match you get the candidates back \
+         instead of a guess. Examples: role='button' name='Send', label='Remember me', \
+         text='Compose', ref='e12:k3f9', selector='button.compose'. \
+         Waits until the element is visible, enabled, not moving and not covered, \
+         and says which check failed if it never is."
     }
     fn parameters(&self) -> Value {
-        json!({
+        with_locator(json!({
             "type": "object",
             "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
                 "selector": { "type": "string", "description": "CSS selector" },
-                "text": { "type": "string", "description": "Visible text to click" }
+                "text": { "type": "string", "description": "Visible text to click" },
+                "timeout_ms": { "type": "integer", "description": "Max wait for the element to become clickable (default: 5000)" },
+                "trusted": { "type": "boolean", "description": "Click with real mouse events via the DevTools Input domain — for sites that ignore synthetic clicks (default: false; the CDP backend always does)" },
+                "frame": frame_param(),
+                "tab": tab_param()
             },
             "required": []
-        })
+        }))
     }
     fn category(&self) -> ToolCategory { ToolCategory::Shell }
 
@@ -777,20 +2313,28 @@ pub struct BrowserTypeTool { pub state: Arc<BrowserState> }
 impl Tool for BrowserTypeTool {
     fn name(&self) -> &str { "browser_type" }
     fn description(&self) -> &str {
-        "Type text into an input field. Find by CSS selector, label text, or focused element. \
-         Examples: selector='input[name=to]' value='john@example.com'"
+        "Type text into an input field. Find by ref, label, placeholder, role/name, CSS selector, \
+         or the focused element. Examples: label='To' value='john@example.com', \
+         placeholder='Search' value='invoices'. Waits until the field is visible, \
+         enabled and editable."
     }
     fn parameters(&self) -> Value {
-        json!({
+        with_locator(json!({
             "type": "object",
             "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
                 "selector": { "type": "string", "description": "CSS selector for input" },
                 "text": { "type": "string", "description": "Find input by label text" },
                 "value": { "type": "string", "description": "Text to type" },
-                "clear_first": { "type": "boolean", "description": "Clear before typing (default: true)" }
+                "clear_first": { "type": "boolean", "description": "Clear before typing (default: true)" },
+                "timeout_ms": { "type": "integer", "description": "Max wait for the field to become editable (default: 5000)" },
+                "submit": { "type": "boolean", "description": "Press Enter after typing (default: false)" },
+                "trusted": { "type": "boolean", "description": "Type with real key events via the DevTools Input domain — for React apps, canvas editors and sites that ignore synthetic input (default: false)" },
+                "frame": frame_param(),
+                "tab": tab_param()
             },
             "required": ["value"]
-        })
+        }))
     }
     fn category(&self) -> ToolCategory { ToolCategory::Shell }
 
@@ -802,29 +2346,229 @@ impl Tool for BrowserTypeTool {
     }
 }
 
+pub struct BrowserFillFormTool { pub state: Arc<BrowserState> }
+
+/// Short description of a form field's target for the per-field report.
+fn describe_field(field: &Value) -> String {
+    ["ref", "selector", "role", "name", "label", "placeholder", "text"]
+        .iter()
+        .filter_map(|k| Some(format!("{k}={}", field.get(*k)?)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[async_trait]
+impl Tool for BrowserFillFormTool {
+    fn name(&self) -> &str { "browser_fill_form" }
+    fn description(&self) -> &str {
+        "Fill several form fields in one call, each found by ref, locator or selector. \
+         Handles text inputs, textareas, contenteditable, <select> (option value or label; \
+         an array for multi-selects), checkboxes (true/false), radio groups (target any radio \
+         of the group or its fieldset, value = option value or label) and date inputs \
+         (YYYY-MM-DD). Reports each field; submits only if every field succeeded. \
+         Example: fields=[{label:'To', value:'a@b.com'}, {label:'Priority', value:'High'}, \
+         {label:'Urgent', value:true}] submit={role:'button', name:'Send'}"
+    }
+    fn parameters(&self) -> Value {
+        let field = with_locator(json!({
+            "type": "object",
+            "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
+                "selector": { "type": "string", "description": "CSS selector" },
+                "text": { "type": "string", "description": "Find the field by its label text" },
+                "value": {
+                    "description": "Text, option value/label, true/false for checkboxes, or an array for multi-selects",
+                    "type": ["string", "number", "boolean", "array"],
+                    "items": { "type": "string" }
+                }
+            },
+            "required": ["value"]
+        }));
+        json!({
+            "type": "object",
+            "properties": {
+                "fields": { "type": "array", "items": field, "description": "Fields to fill, in order" },
+                "submit": {
+                    "description": "true submits the form of the last field; an object is a locator \
+                                    (role/name, label, text, selector or ref) for the button to click",
+                    "type": ["boolean", "object"]
+                },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": ["fields"]
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let fields = args.get("fields").and_then(Value::as_array).cloned().unwrap_or_default();
+        if fields.is_empty() {
+            return Ok(ToolOutput::error("Provide at least one entry in 'fields'"));
+        }
+        // Carry the frame/tab over to every per-field call.
+        let scoped = |mut call: Value| {
+            for key in ["frame", "tab"] {
+                if let Some(v) = args.get(key).filter(|v| !v.is_null()) {
+                    call[key] = v.clone();
+                }
+            }
+            call
+        };
+        let submit = args.get("submit").filter(|v| !v.is_null() && **v != json!(false));
+
+        let mut lines = Vec::new();
+        let mut failed = 0;
+        for (i, field) in fields.iter().enumerate() {
+            if !field.is_object() {
+                lines.push(format!("✗ field {}: expected an object", i + 1));
+                failed += 1;
+                continue;
+            }
+            let mut call = scoped(field.clone());
+            // `submit: true` rides along with the last field so the page
+            // submits the form that field belongs to.
+            if i + 1 == fields.len() && failed == 0 && submit == Some(&json!(true)) {
+                call["submit"] = json!(true);
+            }
+            match self.state.run("fill", call).await {
+                Ok(done) => lines.push(format!("✓ {}: {done}", describe_field(field))),
+                Err(e) => {
+                    lines.push(format!("✗ {}: {e}", describe_field(field)));
+                    failed += 1;
+                }
+            }
+        }
+
+        if let Some(button) = submit.filter(|v| v.is_object()) {
+            if failed == 0 {
+                match self.state.run("click", scoped(button.clone())).await {
+                    Ok(done) => lines.push(format!("✓ submit: {done}")),
+                    Err(e) => {
+                        lines.push(format!("✗ submit: {e}"));
+                        failed += 1;
+                    }
+                }
+            }
+        }
+        if failed > 0 {
+            let steps = lines.len();
+            if submit.is_some() {
+                lines.push("Form not submitted.".into());
+            }
+            lines.insert(0, format!("{failed} of {steps} step(s) failed:"));
+            return Ok(ToolOutput::error(lines.join("\n")));
+        }
+        lines.insert(0, format!("Filled {} field(s):", fields.len()));
+        Ok(ToolOutput::llm_only(lines.join("\n")))
+    }
+}
+
+pub struct BrowserExtractTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserExtractTool {
+    fn name(&self) -> &str { "browser_extract" }
+    fn description(&self) -> &str {
+        "Extract structured data from the page as typed JSON, instead of writing browser_js. \
+         Three ways: (1) table='#prices' turns an HTML table into row objects keyed by header; \
+         (2) items='li.result' fields={title:'h3', url:{selector:'a', type:'url'}, \
+         price:{selector:'.price', type:'number'}} returns one object per item; \
+         (3) schema = a JSON Schema whose nodes carry 'selector' (CSS relative to the parent) or \
+         role/label/text locators, plus optional 'attribute'. Types: string, number, integer, \
+         boolean (present/checked), url (absolute href/src). Fields missing from every item \
+         are reported — usually a wrong selector."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "table": {
+                    "description": "CSS selector, or a locator object like {role:'table', name:'Prices'}, of the table to convert",
+                    "type": ["string", "object"]
+                },
+                "items": {
+                    "description": "CSS selector, or locator object, matching each repeating item (use with fields)",
+                    "type": ["string", "object"]
+                },
+                "fields": {
+                    "type": "object",
+                    "description": "Field name → CSS selector inside the item ('' = the item itself), or { selector | role/name/label/text, attribute, type }"
+                },
+                "schema": { "type": "object", "description": "JSON Schema annotated with selector/attribute (alternative to items + fields)" },
+                "limit": { "type": "integer", "description": "Maximum items or rows (default: 100)" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match extract_spec(&args) {
+            Ok(spec) => args["spec"] = spec,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        }
+        let raw = match self.state.run("extract", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let Ok(data) = serde_json::from_str::<Value>(&raw) else {
+            return Ok(ToolOutput::llm_only(raw));
+        };
+        let count = data["count"].as_u64().unwrap_or(0);
+        let mut out = format!("Extracted {count} item(s).");
+        let missing: Vec<&str> = data["missing"].as_array().into_iter().flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        if !missing.is_empty() {
+            out.push_str(&format!(" Never found: {} — check those selectors.", missing.join(", ")));
+        }
+        let items = serde_json::to_string_pretty(&data["items"]).unwrap_or_default();
+        Ok(ToolOutput::llm_only(format!("{out}\n{items}")))
+    }
+}
+
 pub struct BrowserReadTool { pub state: Arc<BrowserState> }
 
 #[async_trait]
 impl Tool for BrowserReadTool {
     fn name(&self) -> &str { "browser_read" }
     fn description(&self) -> &str {
-        "Read text from the page — specific element by selector, full body text, or title/URL."
+        "Read text from the page — specific element by ref, locator or selector, full body text, \
+         or title/URL. format='markdown' keeps structure (headings, lists, links, tables) and \
+         picks out the main content. Long results come in chunks: read on with offset."
     }
     fn parameters(&self) -> Value {
-        json!({
+        with_locator(json!({
             "type": "object",
             "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
                 "selector": { "type": "string", "description": "CSS selector to read" },
-                "page_text": { "type": "boolean", "description": "Read full page text" }
+                "page_text": { "type": "boolean", "description": "Read full page text" },
+                "format": { "type": "string", "enum": ["text", "markdown"], "description": "text (default) or markdown" },
+                "main_content": { "type": "boolean", "description": "Markdown of the whole page: only the main content, without navigation and sidebars (default: true)" },
+                "offset": { "type": "integer", "description": "Character offset to start from, for reading on (default: 0)" },
+                "max_chars": { "type": "integer", "description": "Characters to return (default: 3000 for an element, 5000 for the page)" },
+                "frame": frame_param(),
+                "tab": tab_param()
             },
             "required": []
-        })
+        }))
     }
     fn category(&self) -> ToolCategory { ToolCategory::Shell }
 
     async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let element = ["ref", "selector", "role", "label", "placeholder", "text"]
+            .iter()
+            .any(|k| args.get(*k).is_some_and(|v| !v.is_null()));
+        let max_chars = args.get("max_chars").and_then(Value::as_u64)
+            .map(|n| n.clamp(200, 50_000) as usize)
+            .unwrap_or(if element { 3000 } else { 5000 });
+        let offset = args.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
         match self.state.run("read", args).await {
-            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Ok(r) => Ok(ToolOutput::llm_only(paginate(&r, offset, max_chars))),
             Err(e) => Ok(ToolOutput::error(e)),
         }
     }
@@ -836,13 +2580,19 @@ pub struct BrowserListElementsTool { pub state: Arc<BrowserState> }
 impl Tool for BrowserListElementsTool {
     fn name(&self) -> &str { "browser_list_elements" }
     fn description(&self) -> &str {
-        "List interactive elements (buttons, links, inputs) on the page."
+        "List interactive elements (buttons, links, inputs) on the page, each with a `ref`. \
+         Pass ref='...' to browser_click, browser_type, browser_read or browser_wait_for \
+         instead of a selector. Refs go stale after navigation — list again. \
+         Includes elements inside iframes (pass the listed frame='N' along with their refs) \
+         and open shadow roots."
     }
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
-                "filter": { "type": "string", "description": "Only show elements containing this text" }
+                "filter": { "type": "string", "description": "Only show elements containing this text" },
+                "frame": { "type": "string", "description": "Only this iframe (default: the main page and every iframe)" },
+                "tab": tab_param()
             },
             "required": []
         })
@@ -852,21 +2602,34 @@ impl Tool for BrowserListElementsTool {
     async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
         match self.state.run("list_elements", args).await {
             Ok(raw) => {
-                let elements: Vec<Value> = serde_json::from_str(&raw).unwrap_or_default();
+                let Ok(elements) = serde_json::from_str::<Vec<Value>>(&raw) else {
+                    // agent-browser returns its accessibility snapshot as text, refs inline
+                    return Ok(ToolOutput::llm_only(raw));
+                };
                 if elements.is_empty() {
                     return Ok(ToolOutput::llm_only("No interactive elements found."));
                 }
                 let mut lines = vec![format!("{} interactive elements:", elements.len())];
+                let mut frame = None;
                 for el in &elements {
+                    if let Some(i) = el["frame"].as_u64().filter(|i| frame != Some(*i)) {
+                        let url = el["frame_url"].as_str().unwrap_or("");
+                        lines.push(format!("frame {i} ({url}) — pass frame='{i}' with these refs:"));
+                        frame = Some(i);
+                    }
                     let tag = el["tag"].as_str().unwrap_or("");
                     let text = el["text"].as_str().unwrap_or("");
                     let typ = el["type"].as_str().unwrap_or("");
-                    let sel = el["selector"].as_str().unwrap_or("");
-                    if !text.is_empty() {
-                        lines.push(format!("  <{tag}> \"{text}\" type={typ} sel={sel}"));
+                    let r = el["ref"].as_str().unwrap_or("");
+                    let mut line = if !text.is_empty() {
+                        format!("  [{r}] <{tag}> \"{text}\" type={typ}")
                     } else {
-                        lines.push(format!("  <{tag}> type={typ} sel={sel}"));
+                        format!("  [{r}] <{tag}> type={typ}")
+                    };
+                    if let Some(host) = el["shadow"].as_str() {
+                        line.push_str(&format!(" (shadow: {host})"));
                     }
+                    lines.push(line);
                 }
                 Ok(ToolOutput::llm_only(lines.join("\n")))
             }
@@ -875,19 +2638,83 @@ impl Tool for BrowserListElementsTool {
     }
 }
 
+pub struct BrowserSnapshotTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserSnapshotTool {
+    fn name(&self) -> &str { "browser_snapshot" }
+    fn description(&self) -> &str {
+        "Outline of the page from Chrome's accessibility tree: landmarks, headings, \
+         form controls with their labels and states (checked, expanded, disabled, value), \
+         and a ref for every interactive element. Finds custom widgets that \
+         browser_list_elements misses. Use the refs with browser_click / browser_type."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "interactive_only": { "type": "boolean", "description": "Only controls plus landmarks and headings (default: false)" },
+                "max_lines": { "type": "integer", "description": "Maximum lines to return (default: 500)" },
+                "tab": tab_param()
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let defaults = SnapshotOptions::default();
+        let opts = SnapshotOptions {
+            interactive_only: args.get("interactive_only").and_then(Value::as_bool)
+                .unwrap_or(defaults.interactive_only),
+            max_lines: args.get("max_lines").and_then(Value::as_u64)
+                .map(|n| n.clamp(20, 2000) as usize)
+                .unwrap_or(defaults.max_lines),
+        };
+        // The extension tags refs page-side; tell it which roles qualify.
+        args["ref_roles"] = json!(web_snapshot::INTERACTIVE_ROLES);
+        args["max_refs"] = json!(web_snapshot::MAX_REFS);
+
+        match self.state.run("snapshot", args).await {
+            Ok(raw) => {
+                let Ok(snapshot) = serde_json::from_str::<Value>(&raw) else {
+                    // agent-browser returns its own text snapshot
+                    return Ok(ToolOutput::llm_only(raw));
+                };
+                let nodes = snapshot["nodes"].as_array().cloned().unwrap_or_default();
+                if nodes.is_empty() {
+                    return Ok(ToolOutput::error("Accessibility tree is empty (page still loading?)"));
+                }
+                let refs = web_snapshot::parse_refs(snapshot.get("refs"));
+                Ok(ToolOutput::llm_only(web_snapshot::render(&nodes, &refs, &opts)))
+            }
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
 pub struct BrowserNavigateTool { pub state: Arc<BrowserState> }
 
 #[async_trait]
 impl Tool for BrowserNavigateTool {
     fn name(&self) -> &str { "browser_navigate" }
     fn description(&self) -> &str {
-        "Navigate the active Chrome tab to a URL. Waits for load. Use instead of open_url for web apps."
+        "Navigate the current Chrome tab (or `tab`) to a URL. Waits for the load event by default; \
+         wait_until='networkidle' also waits until the page stops fetching (single-page apps). \
+         Use instead of open_url for web apps."
     }
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
-                "url": { "type": "string", "description": "URL to navigate to" }
+                "url": { "type": "string", "description": "URL to navigate to" },
+                "wait_until": {
+                    "type": "string",
+                    "enum": ["load", "domcontentloaded", "networkidle"],
+                    "description": "domcontentloaded (HTML parsed), load (default), or networkidle (load plus no requests for 500ms)"
+                },
+                "timeout_ms": { "type": "integer", "description": "Max wait ms (default: 15000)" },
+                "tab": tab_param()
             },
             "required": ["url"]
         })
@@ -915,7 +2742,9 @@ impl Tool for BrowserJsTool {
         json!({
             "type": "object",
             "properties": {
-                "code": { "type": "string", "description": "JavaScript to execute" }
+                "code": { "type": "string", "description": "JavaScript to execute" },
+                "frame": frame_param(),
+                "tab": tab_param()
             },
             "required": ["code"]
         })
@@ -936,19 +2765,25 @@ pub struct BrowserWaitForTool { pub state: Arc<BrowserState> }
 impl Tool for BrowserWaitForTool {
     fn name(&self) -> &str { "browser_wait_for" }
     fn description(&self) -> &str {
-        "Wait for an element to appear on the page. Returns its text when found. \
+        "Wait for an element to appear on the page — by ref, locator (role/name, label, \
+         placeholder), CSS selector or text. Returns its text when found. \
+         network_idle=true waits for the page to stop fetching first (or alone). \
          Much faster than wait + screenshot."
     }
     fn parameters(&self) -> Value {
-        json!({
+        with_locator(json!({
             "type": "object",
             "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
                 "selector": { "type": "string", "description": "CSS selector to wait for" },
                 "text": { "type": "string", "description": "Wait for text to appear" },
-                "timeout_ms": { "type": "integer", "description": "Max wait ms (default: 5000)" }
+                "network_idle": { "type": "boolean", "description": "Wait until no requests are in flight for 500ms" },
+                "timeout_ms": { "type": "integer", "description": "Max wait ms (default: 5000)" },
+                "frame": frame_param(),
+                "tab": tab_param()
             },
             "required": []
-        })
+        }))
     }
     fn category(&self) -> ToolCategory { ToolCategory::Shell }
 
@@ -960,6 +2795,1019 @@ impl Tool for BrowserWaitForTool {
     }
 }
 
+pub struct BrowserWaitForRequestTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserWaitForRequestTool {
+    fn name(&self) -> &str { "browser_wait_for_request" }
+    fn description(&self) -> &str {
+        "Wait for a network request the page makes after your last action (click, type, navigate…) \
+         and report its status. `url` is a substring or a glob like '*/api/search?*'. \
+         capture_body=true returns the response body — read JSON APIs directly instead of the rendered page."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "URL substring, or glob with * over the whole URL" },
+                "method": { "type": "string", "description": "HTTP method to match, e.g. POST" },
+                "capture_body": { "type": "boolean", "description": "Include the response body (default: false)" },
+                "timeout_ms": { "type": "integer", "description": "Max wait ms (default: 10000)" },
+                "offset": { "type": "integer", "description": "Body character offset, for reading on (default: 0)" },
+                "max_chars": { "type": "integer", "description": "Body characters to return (default: 5000)" },
+                "tab": tab_param()
+            },
+            "required": ["url"]
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let max_chars = args.get("max_chars").and_then(Value::as_u64)
+            .map(|n| n.clamp(200, 50_000) as usize)
+            .unwrap_or(5000);
+        let offset = args.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let raw = match self.state.run("wait_for_request", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let Ok(request) = serde_json::from_str::<Value>(&raw) else {
+            return Ok(ToolOutput::llm_only(raw));
+        };
+        let mut out = web_network::summary(&request);
+        if let Some(body) = request.get("body").and_then(Value::as_str) {
+            let body = serde_json::from_str::<Value>(body)
+                .ok()
+                .and_then(|v| serde_json::to_string_pretty(&v).ok())
+                .unwrap_or_else(|| body.to_string());
+            out.push_str(&format!("\n\n{}", paginate(&body, offset, max_chars)));
+        } else if let Some(e) = request.get("body_error").and_then(Value::as_str) {
+            out.push_str(&format!("\n\n(no body: {e})"));
+        }
+        Ok(ToolOutput::llm_only(out))
+    }
+}
+
+pub struct BrowserWaitForDownloadTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserWaitForDownloadTool {
+    fn name(&self) -> &str { "browser_wait_for_download" }
+    fn description(&self) -> &str {
+        "Wait for a download started by your last action (e.g. clicking 'Download invoice') to finish \
+         and return the path of the saved file. `filename` narrows it to a file name or URL \
+         substring or glob like '*.pdf'."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "filename": { "type": "string", "description": "File name or URL substring, or glob with *" },
+                "timeout_ms": { "type": "integer", "description": "Max wait ms (default: 30000)" }
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let raw = match self.state.run("wait_for_download", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let Ok(download) = serde_json::from_str::<Value>(&raw) else {
+            return Ok(ToolOutput::llm_only(raw));
+        };
+        let summary = web_files::summary(&download);
+        let Some(path) = download.get("path").and_then(Value::as_str)
+            .filter(|_| download["state"] == "completed")
+        else {
+            return Ok(ToolOutput::error(summary));
+        };
+        let checked = web_files::FilesConfig::load().and_then(|c| c.check_download(path));
+        match checked {
+            Ok(()) => Ok(ToolOutput::llm_only(summary)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserConsoleTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserConsoleTool {
+    fn name(&self) -> &str { "browser_console" }
+    fn description(&self) -> &str {
+        "Show the tab's console output: console messages, uncaught exceptions and failed requests, \
+         oldest first. Use when an action seemed to do nothing — level='error' shows just the errors. \
+         (Errors caused by an action are also appended to that action's result.)"
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "level": {
+                    "type": "string",
+                    "enum": ["debug", "log", "info", "warning", "error"],
+                    "description": "Minimum level to show (default: log)"
+                },
+                "since_last_action": { "type": "boolean", "description": "Only entries since the latest click, type, navigate… (default: false)" },
+                "limit": { "type": "integer", "description": "Newest entries to show (default: 50)" },
+                "clear": { "type": "boolean", "description": "Empty the log after reading (default: false)" },
+                "tab": tab_param()
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        args["since_action"] = json!(args.get("since_last_action").and_then(Value::as_bool).unwrap_or(false));
+        args["limit"] = json!(args.get("limit").and_then(Value::as_u64).unwrap_or(50).clamp(1, 500));
+        let raw = match self.state.run("console", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let Ok(entries) = serde_json::from_str::<Vec<Value>>(&raw) else {
+            return Ok(ToolOutput::llm_only(raw));
+        };
+        if entries.is_empty() {
+            return Ok(ToolOutput::llm_only("No console entries at this level."));
+        }
+        Ok(ToolOutput::llm_only(format!(
+            "{} entr{}:\n{}",
+            entries.len(),
+            if entries.len() == 1 { "y" } else { "ies" },
+            web_console::render(&entries)
+        )))
+    }
+}
+
+pub struct BrowserDialogTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserDialogTool {
+    fn name(&self) -> &str { "browser_dialog" }
+    fn description(&self) -> &str {
+        "Answer the JavaScript dialog (alert, confirm, prompt, or 'leave site?') blocking the tab: \
+         accept or dismiss, with text for a prompt. default_policy sets what happens to later \
+         dialogs: report them (default), or accept/dismiss them automatically."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["accept", "dismiss"],
+                    "description": "Answer for the open dialog"
+                },
+                "prompt_text": { "type": "string", "description": "Text to enter before accepting a prompt()" },
+                "default_policy": {
+                    "type": "string",
+                    "enum": ["report", "accept", "dismiss"],
+                    "description": "What to do with dialogs from now on (default: report, or ZEPTOBOT_DIALOG_POLICY)"
+                },
+                "tab": tab_param()
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let mut notes = Vec::new();
+        if let Some(policy) = args.get("default_policy").and_then(Value::as_str) {
+            let Some(policy) = DialogPolicy::parse(policy) else {
+                return Ok(ToolOutput::error(format!(
+                    "Unknown default_policy '{policy}' — use report, accept or dismiss"
+                )));
+            };
+            self.state.set_dialog_policy(policy).await;
+            notes.push(format!("Dialog policy: {}", policy.as_str()));
+        }
+        match args.get("action").and_then(Value::as_str) {
+            Some("accept" | "dismiss") => match self.state.run("dialog", args).await {
+                Ok(r) => notes.insert(0, r),
+                Err(e) => return Ok(ToolOutput::error(e)),
+            },
+            Some(other) => {
+                return Ok(ToolOutput::error(format!("Unknown action '{other}' — use accept or dismiss")))
+            }
+            None if notes.is_empty() => {
+                return Ok(ToolOutput::error("Pass 'action' (accept or dismiss) or 'default_policy'"))
+            }
+            None => {}
+        }
+        Ok(ToolOutput::llm_only(notes.join("\n")))
+    }
+}
+
+pub struct BrowserUploadTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserUploadTool {
+    fn name(&self) -> &str { "browser_upload" }
+    fn description(&self) -> &str {
+        "Attach local files to a file input (e.g. a Gmail attachment or a document upload form) \
+         without opening the file picker. Target the <input type=file>, its label, or the \
+         upload button or drop zone around it. Files must be in the allowed upload directories \
+         (~/.zeptobot/files.json)."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Absolute or ~ paths, or names relative to an upload directory"
+                },
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
+                "selector": { "type": "string", "description": "CSS selector" },
+                "text": { "type": "string", "description": "Visible text of the upload button" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": ["files"]
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let config = match web_files::FilesConfig::load() {
+            Ok(c) => c,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let requested: Vec<&str> = args.get("files").and_then(Value::as_array)
+            .map(|a| a.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        if requested.is_empty() {
+            return Ok(ToolOutput::error("Missing 'files'"));
+        }
+        let mut files = Vec::new();
+        for file in requested {
+            match config.upload_path(file) {
+                Ok(path) => files.push(path.to_string_lossy().into_owned()),
+                Err(e) => return Ok(ToolOutput::error(e)),
+            }
+        }
+        args["files"] = json!(files);
+        match self.state.run("upload", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserScreenshotTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserScreenshotTool {
+    fn name(&self) -> &str { "browser_screenshot" }
+    fn description(&self) -> &str {
+        "Screenshot a browser tab — the visible viewport, the full scrolling page, or one element \
+         (by ref, locator or selector) — and describe it with vision. Works on background tabs \
+         and without Chrome in front. Use it for charts, images and layout that text tools can't see."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "full_page": { "type": "boolean", "description": "Capture the whole page, not just the viewport (default: false)" },
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements — capture just that element" },
+                "selector": { "type": "string", "description": "CSS selector of the element to capture" },
+                "text": { "type": "string", "description": "Visible text of the element to capture" },
+                "context": { "type": "string", "description": "What you're looking for, to focus the description" },
+                "describe": { "type": "boolean", "description": "Describe the image with vision (default: true); false just saves it" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": []
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let element = ["ref", "selector", "role", "label", "placeholder", "text"]
+            .iter()
+            .any(|k| args.get(*k).is_some_and(|v| !v.is_null()));
+        let what = if element {
+            "element"
+        } else if args.get("full_page").and_then(Value::as_bool) == Some(true) {
+            "full page"
+        } else {
+            "viewport"
+        };
+        let context = args.get("context").and_then(Value::as_str).map(String::from);
+        let describe = args.get("describe").and_then(Value::as_bool).unwrap_or(true);
+
+        let raw = match self.state.run("screenshot", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let png = match serde_json::from_str::<Value>(&raw)
+            .ok()
+            .and_then(|v| v.get("data").and_then(Value::as_str).map(String::from))
+            .and_then(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        {
+            Some(png) => png,
+            None => return Ok(ToolOutput::error("The browser returned no image")),
+        };
+        let (width, height) = image::ImageReader::new(std::io::Cursor::new(&png))
+            .with_guessed_format()
+            .ok()
+            .and_then(|r| r.into_dimensions().ok())
+            .unwrap_or((0, 0));
+
+        let label = context.clone().unwrap_or_else(|| format!("browser_screenshot ({what})"));
+        let saved = crate::services::session::record_image(&png, &label).or_else(|| {
+            let path = std::env::temp_dir().join(format!("zeptobot-{}.png", next_id()));
+            std::fs::write(&path, &png).ok().map(|_| path)
+        });
+        let mut out = format!("Captured the {what} ({width}×{height})");
+        if let Some(path) = saved {
+            out.push_str(&format!(" — saved to {}", path.display()));
+        }
+        if !describe {
+            return Ok(ToolOutput::llm_only(out));
+        }
+
+        let prompt = format!(
+            "You are a screen reader for a browser automation assistant. \
+             Describe this screenshot of a web page ({what}) in detail. Include:\n\
+             - Headings, text, links, buttons and input fields with their approximate positions\n\
+             - Images, charts and icons, and what they show\n\
+             - The state of the UI (open menus, selected tabs, errors, loading indicators)\n\n\
+             User context: {}\n\n\
+             Be concise but thorough. Focus on actionable information.",
+            context.as_deref().unwrap_or("Describe everything visible")
+        );
+        match describe_image(&png, &prompt).await {
+            Ok(description) => Ok(ToolOutput::llm_only(format!("PAGE CONTENT:\n{description}\n\n{out}"))),
+            Err(e) => Ok(ToolOutput::llm_only(format!("{out}\n(No description: {e})"))),
+        }
+    }
+}
+
+pub struct BrowserCookiesTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserCookiesTool {
+    fn name(&self) -> &str { "browser_cookies" }
+    fn description(&self) -> &str {
+        "Check whether the user is logged into a site, or read or clear its cookies (default: the \
+         current tab's site). action='check' lists which cookies exist, for any site — pass `name` \
+         to test for one login cookie; 'read' adds their values for domains allowed in \
+         ~/.zeptobot/session.json; 'clear' deletes them, which logs the user out."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["check", "read", "clear"],
+                    "description": "What to do (default: check)"
+                },
+                "domain": { "type": "string", "description": "Site like 'github.com' (default: the tab's site)" },
+                "name": { "type": "string", "description": "Only this cookie" },
+                "tab": tab_param()
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("check").to_string();
+        if !matches!(action.as_str(), "check" | "read" | "clear") {
+            return Ok(ToolOutput::error(format!("Unknown action '{action}' — use check, read or clear")));
+        }
+        let config = match web_session::SessionConfig::load() {
+            Ok(c) => c,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let name = args.get("name").and_then(Value::as_str).map(String::from);
+        args["clear"] = json!(action == "clear");
+        let raw = match self.state.run("cookies", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let Ok(found) = serde_json::from_str::<Value>(&raw) else {
+            return Ok(ToolOutput::llm_only(raw));
+        };
+        let domain = found["domain"].as_str().unwrap_or_default();
+        let cookies = found["cookies"].as_array().cloned().unwrap_or_default();
+        if cookies.is_empty() {
+            return Ok(ToolOutput::llm_only(match name {
+                Some(n) => format!("No cookie '{n}' for {domain} — probably not logged in."),
+                None => format!("No cookies for {domain} — not logged in."),
+            }));
+        }
+        let count = format!("{} cookie{}", cookies.len(), if cookies.len() == 1 { "" } else { "s" });
+        if action == "clear" {
+            let names: Vec<&str> = cookies.iter().filter_map(|c| c["name"].as_str()).collect();
+            return Ok(ToolOutput::llm_only(format!(
+                "Deleted {count} for {domain}: {}",
+                names.join(", ")
+            )));
+        }
+        let mut hidden = false;
+        let lines: Vec<String> = cookies
+            .iter()
+            .map(|c| {
+                let line = web_session::describe_cookie(c);
+                if action == "check" {
+                    return line;
+                }
+                if config.allows(c["domain"].as_str().unwrap_or_default()) {
+                    format!("{line} = {}", c["value"])
+                } else {
+                    hidden = true;
+                    format!("{line} = [hidden]")
+                }
+            })
+            .collect();
+        let mut out = format!("{count} for {domain}:\n{}", lines.join("\n"));
+        if hidden {
+            out.push_str(&format!("\n{}", web_session::SessionConfig::hidden_note(domain)));
+        }
+        Ok(ToolOutput::llm_only(out))
+    }
+}
+
+pub struct BrowserStorageTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserStorageTool {
+    fn name(&self) -> &str { "browser_storage" }
+    fn description(&self) -> &str {
+        "Read or clear the page's localStorage or sessionStorage. Keys are listed for any site; \
+         values only for domains allowed in ~/.zeptobot/session.json."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["read", "clear"],
+                    "description": "What to do (default: read)"
+                },
+                "area": {
+                    "type": "string",
+                    "enum": ["local", "session"],
+                    "description": "localStorage or sessionStorage (default: local)"
+                },
+                "key": { "type": "string", "description": "Only this item" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let clear = match args.get("action").and_then(Value::as_str).unwrap_or("read") {
+            "read" => false,
+            "clear" => true,
+            other => return Ok(ToolOutput::error(format!("Unknown action '{other}' — use read or clear"))),
+        };
+        let config = match web_session::SessionConfig::load() {
+            Ok(c) => c,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        args["clear"] = json!(clear);
+        let raw = match self.state.run("storage", args).await {
+            Ok(raw) => raw,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let Ok(found) = serde_json::from_str::<Value>(&raw) else {
+            return Ok(ToolOutput::llm_only(raw));
+        };
+        let origin = found["origin"].as_str().unwrap_or_default();
+        let area = if found["area"] == "session" { "sessionStorage" } else { "localStorage" };
+        if let Some(n) = found["cleared"].as_u64() {
+            return Ok(ToolOutput::llm_only(format!(
+                "Removed {n} item{} from {area} of {origin}",
+                if n == 1 { "" } else { "s" }
+            )));
+        }
+        let items = found["items"].as_object().cloned().unwrap_or_default();
+        if items.is_empty() {
+            return Ok(ToolOutput::llm_only(format!("Nothing in {area} of {origin}.")));
+        }
+        let host = web_session::host_of(origin);
+        let show = host.as_deref().is_some_and(|h| config.allows(h));
+        let lines: Vec<String> = items
+            .iter()
+            .map(|(key, value)| {
+                let value = value.as_str().unwrap_or_default();
+                if show {
+                    let short: String = value.chars().take(200).collect();
+                    let more = if short.len() < value.len() { "…" } else { "" };
+                    format!("{key} = {}{more}", json!(short))
+                } else {
+                    format!("{key} ({} chars)", value.chars().count())
+                }
+            })
+            .collect();
+        let mut out = format!("{} item{} in {area} of {origin}:\n{}",
+            items.len(), if items.len() == 1 { "" } else { "s" }, lines.join("\n"));
+        if !show {
+            out.push_str(&format!(
+                "\n{}",
+                web_session::SessionConfig::hidden_note(host.as_deref().unwrap_or(origin))
+            ));
+        }
+        Ok(ToolOutput::llm_only(out))
+    }
+}
+
+pub struct BrowserSessionTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserSessionTool {
+    fn name(&self) -> &str { "browser_session" }
+    fn description(&self) -> &str {
+        "Save or restore the logins of ZeptoBot's own Chrome or Firefox profile \
+         (~/.zeptobot/chrome-profile or firefox-profile). 'export' writes the cookies and localStorage of the domains allowed in \
+         ~/.zeptobot/session.json to ~/.zeptobot/sessions/<name>.json; 'import' loads them back, \
+         e.g. after the profile was reset."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["export", "import"] },
+                "name": { "type": "string", "description": "Bundle name (default: 'default')" },
+                "domains": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only these sites (default: every allowed domain)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let config = match web_session::SessionConfig::load() {
+            Ok(c) => c,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        if config.allowed_domains.is_empty() {
+            return Ok(ToolOutput::error(format!(
+                "No domains are allowed — list the sites to keep in \"allowed_domains\" in {}",
+                web_session::SessionConfig::path().display()
+            )));
+        }
+        let name = args.get("name").and_then(Value::as_str).unwrap_or("default");
+        let path = match web_session::bundle_path(name) {
+            Ok(p) => p,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let domains: Vec<String> = args.get("domains").and_then(Value::as_array)
+            .map(|a| a.iter().filter_map(Value::as_str).map(String::from).collect())
+            .unwrap_or_default();
+
+        match args.get("action").and_then(Value::as_str) {
+            Some("export") => {
+                let raw = match self.state.run("export_session", json!({})).await {
+                    Ok(raw) => raw,
+                    Err(e) => return Ok(ToolOutput::error(e)),
+                };
+                let bundle = match serde_json::from_str::<Value>(&raw) {
+                    Ok(all) => web_session::filter_bundle(&all, &config, &domains),
+                    Err(e) => return Ok(ToolOutput::error(format!("export_session: {e}"))),
+                };
+                if let Err(e) = write_private(&path, &serde_json::to_string_pretty(&bundle).unwrap_or_default()) {
+                    return Ok(ToolOutput::error(format!("Could not save {}: {e}", path.display())));
+                }
+                Ok(ToolOutput::llm_only(format!(
+                    "Exported {} to {}",
+                    web_session::describe_bundle(&bundle),
+                    path.display()
+                )))
+            }
+            Some("import") => {
+                let saved = match std::fs::read_to_string(&path) {
+                    Ok(text) => serde_json::from_str::<Value>(&text)
+                        .map_err(|e| format!("Invalid session bundle {}: {e}", path.display())),
+                    Err(e) => Err(format!("No session '{name}' at {}: {e}", path.display())),
+                };
+                let bundle = match saved {
+                    Ok(all) => web_session::filter_bundle(&all, &config, &domains),
+                    Err(e) => return Ok(ToolOutput::error(e)),
+                };
+                args["bundle"] = bundle.clone();
+                let raw = match self.state.run("import_session", args).await {
+                    Ok(raw) => raw,
+                    Err(e) => return Ok(ToolOutput::error(e)),
+                };
+                let mut out = format!(
+                    "Imported {} from {}",
+                    web_session::describe_bundle(&bundle),
+                    path.display()
+                );
+                let result: Value = serde_json::from_str(&raw).unwrap_or_default();
+                let not_open: Vec<&str> = result["not_open"].as_array()
+                    .map(|a| a.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                if !not_open.is_empty() {
+                    out.push_str(&format!(
+                        "\nlocalStorage not restored for {} — no tab is open there; open them and import again.",
+                        not_open.join(", ")
+                    ));
+                }
+                Ok(ToolOutput::llm_only(out))
+            }
+            Some(other) => Ok(ToolOutput::error(format!("Unknown action '{other}' — use export or import"))),
+            None => Ok(ToolOutput::error("Missing 'action' (export or import)")),
+        }
+    }
+}
+
+/// Write a file only the user can read: session bundles hold live logins.
+fn write_private(path: &std::path::Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(path)?, contents.as_bytes())
+}
+
+pub struct BrowserHoverTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserHoverTool {
+    fn name(&self) -> &str { "browser_hover" }
+    fn description(&self) -> &str {
+        "Move the mouse over an element (by ref, locator or selector) to open hover menus \
+         and tooltips. Waits until the element is visible and uncovered."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
+                "selector": { "type": "string", "description": "CSS selector" },
+                "text": { "type": "string", "description": "Visible text of the element" },
+                "timeout_ms": { "type": "integer", "description": "Max wait for the element (default: 5000)" },
+                "trusted": { "type": "boolean", "description": "Extension backend: real mouse movement via the DevTools Input domain, needed for CSS :hover menus (default: false)" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": []
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("hover", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserSelectOptionTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserSelectOptionTool {
+    fn name(&self) -> &str { "browser_select_option" }
+    fn description(&self) -> &str {
+        "Choose option(s) in a <select> dropdown by option value or visible label. \
+         Example: label='Country' values='Singapore'. Pass an array for multi-selects. \
+         For custom (non-<select>) dropdowns, click the dropdown and then the option."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
+                "selector": { "type": "string", "description": "CSS selector for the <select>" },
+                "text": { "type": "string", "description": "Find the <select> by its label text" },
+                "values": {
+                    "description": "Option value or label, or an array of them for multi-selects",
+                    "type": ["string", "array"],
+                    "items": { "type": "string" }
+                },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": ["values"]
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("select_option", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserCheckTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserCheckTool {
+    fn name(&self) -> &str { "browser_check" }
+    fn description(&self) -> &str {
+        "Check or uncheck a checkbox or switch, or choose a radio button. \
+         Does nothing if it is already in the requested state. \
+         Example: label='Remember me' checked=false."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
+                "selector": { "type": "string", "description": "CSS selector" },
+                "text": { "type": "string", "description": "Find the control by its label text" },
+                "checked": { "type": "boolean", "description": "Desired state (default: true)" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": []
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("check", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserPressKeyTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserPressKeyTool {
+    fn name(&self) -> &str { "browser_press_key" }
+    fn description(&self) -> &str {
+        "Press a key or shortcut in the page as real keyboard input, optionally focusing an \
+         element first. Examples: key='Enter', key='Escape', key='Control+A', \
+         key='ArrowDown' label='Search'."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "key": { "type": "string", "description": "Key or combo: a character, Enter, Tab, Escape, Backspace, Delete, Space, Arrow*, Home, End, PageUp, PageDown, with Control/Alt/Shift/Meta+ modifiers" },
+                "ref": { "type": "string", "description": "Element ref to focus first" },
+                "selector": { "type": "string", "description": "CSS selector of the element to focus first" },
+                "text": { "type": "string", "description": "Focus the field with this label first" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": ["key"]
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, mut args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let key = args.get("key").and_then(Value::as_str).unwrap_or_default();
+        // The extension dispatches these as-is, so both backends share one key table.
+        match web_input::key_events(key) {
+            Ok(events) => args["key_events"] = json!(events),
+            Err(e) => return Ok(ToolOutput::error(e)),
+        }
+        match self.state.run("press_key", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserScrollTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserScrollTool {
+    fn name(&self) -> &str { "browser_scroll" }
+    fn description(&self) -> &str {
+        "Scroll the page, or the scrollable area around an element, by direction and amount \
+         or to an edge; or scroll an element into view. Reports the new scroll position. \
+         Examples: direction='down', to='bottom', selector='.chat-log' to='bottom', \
+         text='Load more' into_view=true."
+    }
+    fn parameters(&self) -> Value {
+        with_locator(json!({
+            "type": "object",
+            "properties": {
+                "ref": { "type": "string", "description": "Element ref from browser_list_elements" },
+                "selector": { "type": "string", "description": "CSS selector of the element (default: the page)" },
+                "text": { "type": "string", "description": "Visible text of the element" },
+                "direction": { "type": "string", "enum": ["up", "down", "left", "right"], "description": "Scroll direction (default: down)" },
+                "amount": { "type": "number", "description": "Pixels to scroll (default: most of a screen)" },
+                "to": { "type": "string", "enum": ["top", "bottom", "left", "right"], "description": "Scroll all the way to this edge instead" },
+                "into_view": { "type": "boolean", "description": "Scroll the element itself into view (default: false)" },
+                "frame": frame_param(),
+                "tab": tab_param()
+            },
+            "required": []
+        }))
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("scroll", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserGoBackTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserGoBackTool {
+    fn name(&self) -> &str { "browser_go_back" }
+    fn description(&self) -> &str { "Go back one page in the tab's history, like the Back button." }
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": { "tab": tab_param() }, "required": [] })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("go_back", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserGoForwardTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserGoForwardTool {
+    fn name(&self) -> &str { "browser_go_forward" }
+    fn description(&self) -> &str { "Go forward one page in the tab's history, like the Forward button." }
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": { "tab": tab_param() }, "required": [] })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("go_forward", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserReloadTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserReloadTool {
+    fn name(&self) -> &str { "browser_reload" }
+    fn description(&self) -> &str { "Reload the current page and wait for it to load." }
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": { "tab": tab_param() }, "required": [] })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("reload", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+// ===========================================================================
+// Tab management
+// ===========================================================================
+
+pub struct BrowserTabsTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserTabsTool {
+    fn name(&self) -> &str { "browser_tabs" }
+    fn description(&self) -> &str {
+        "List open browser tabs with their id, title and URL. The current tab is marked with *. \
+         Pass an id as `tab` to any browser_ tool to act on that tab."
+    }
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": {}, "required": [] })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("get_tabs", args).await {
+            Ok(raw) => {
+                let tabs: Vec<Value> = serde_json::from_str(&raw).unwrap_or_default();
+                if tabs.is_empty() {
+                    return Ok(ToolOutput::llm_only("No tabs open."));
+                }
+                let mut lines = vec![format!("{} tab(s):", tabs.len())];
+                for t in &tabs {
+                    let id = match &t["id"] {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    let mark = if t["active"].as_bool() == Some(true) { "*" } else { " " };
+                    let title = t["title"].as_str().unwrap_or("");
+                    let url = t["url"].as_str().unwrap_or("");
+                    lines.push(format!("{mark} [{id}] {title} — {url}"));
+                }
+                Ok(ToolOutput::llm_only(lines.join("\n")))
+            }
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserSwitchTabTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserSwitchTabTool {
+    fn name(&self) -> &str { "browser_switch_tab" }
+    fn description(&self) -> &str {
+        "Bring a tab to the front and make it the current tab for later browser_ calls."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "tab": { "type": "string", "description": "Tab id from browser_tabs" }
+            },
+            "required": ["tab"]
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("switch_tab", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserNewTabTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserNewTabTool {
+    fn name(&self) -> &str { "browser_new_tab" }
+    fn description(&self) -> &str {
+        "Open a new tab (optionally at a URL) and make it the current tab."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "URL to open (default: blank tab)" }
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("new_tab", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+pub struct BrowserCloseTabTool { pub state: Arc<BrowserState> }
+
+#[async_trait]
+impl Tool for BrowserCloseTabTool {
+    fn name(&self) -> &str { "browser_close_tab" }
+    fn description(&self) -> &str {
+        "Close a tab by id, or the current tab if no id is given."
+    }
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "tab": { "type": "string", "description": "Tab id from browser_tabs (default: current tab)" }
+            },
+            "required": []
+        })
+    }
+    fn category(&self) -> ToolCategory { ToolCategory::Shell }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        match self.state.run("close_tab", args).await {
+            Ok(r) => Ok(ToolOutput::llm_only(r)),
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
 // ===========================================================================
 // Factory
 // ===========================================================================
@@ -970,11 +3818,35 @@ pub fn all_browser_tools() -> (Arc<BrowserState>, Vec<Box<dyn Tool>>) {
         Box::new(BrowserConnectTool { state: state.clone() }),
         Box::new(BrowserClickTool { state: state.clone() }),
         Box::new(BrowserTypeTool { state: state.clone() }),
+        Box::new(BrowserFillFormTool { state: state.clone() }),
         Box::new(BrowserReadTool { state: state.clone() }),
+        Box::new(BrowserExtractTool { state: state.clone() }),
         Box::new(BrowserListElementsTool { state: state.clone() }),
+        Box::new(BrowserSnapshotTool { state: state.clone() }),
+        Box::new(BrowserScreenshotTool { state: state.clone() }),
         Box::new(BrowserNavigateTool { state: state.clone() }),
         Box::new(BrowserJsTool { state: state.clone() }),
         Box::new(BrowserWaitForTool { state: state.clone() }),
+        Box::new(BrowserWaitForRequestTool { state: state.clone() }),
+        Box::new(BrowserWaitForDownloadTool { state: state.clone() }),
+        Box::new(BrowserConsoleTool { state: state.clone() }),
+        Box::new(BrowserDialogTool { state: state.clone() }),
+        Box::new(BrowserCookiesTool { state: state.clone() }),
+        Box::new(BrowserStorageTool { state: state.clone() }),
+        Box::new(BrowserSessionTool { state: state.clone() }),
+        Box::new(BrowserHoverTool { state: state.clone() }),
+        Box::new(BrowserSelectOptionTool { state: state.clone() }),
+        Box::new(BrowserCheckTool { state: state.clone() }),
+        Box::new(BrowserUploadTool { state: state.clone() }),
+        Box::new(BrowserPressKeyTool { state: state.clone() }),
+        Box::new(BrowserScrollTool { state: state.clone() }),
+        Box::new(BrowserGoBackTool { state: state.clone() }),
+        Box::new(BrowserGoForwardTool { state: state.clone() }),
+        Box::new(BrowserReloadTool { state: state.clone() }),
+        Box::new(BrowserTabsTool { state: state.clone() }),
+        Box::new(BrowserSwitchTabTool { state: state.clone() }),
+        Box::new(BrowserNewTabTool { state: state.clone() }),
+        Box::new(BrowserCloseTabTool { state: state.clone() }),
     ];
     (state, tools)
 }
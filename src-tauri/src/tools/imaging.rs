@@ -0,0 +1,687 @@
+//! Pure image-processing routines used by the vision tools.
+//!
+//! Nothing in here touches the screen — everything works on in-memory
+//! buffers so it can be exercised on synthetic images.
+
+use image::{imageops, GrayImage, Rgba, RgbaImage};
+
+// ---------------------------------------------------------------------------
+// Grayscale float buffer
+// ---------------------------------------------------------------------------
+
+/// Row-major grayscale image with `f32` samples in `0.0..=255.0`.
+#[derive(Clone)]
+struct Plane {
+    width: usize,
+    height: usize,
+    data: Vec<f32>,
+}
+
+impl Plane {
+    fn from_gray(img: &GrayImage) -> Self {
+        Self {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            data: img.as_raw().iter().map(|&v| v as f32).collect(),
+        }
+    }
+
+    #[inline]
+    fn at(&self, x: usize, y: usize) -> f32 {
+        self.data[y * self.width + x]
+    }
+
+    /// Box-filter downsample by an integer factor.
+    fn downsample(&self, factor: usize) -> Plane {
+        if factor <= 1 {
+            return self.clone();
+        }
+        let width = (self.width / factor).max(1);
+        let height = (self.height / factor).max(1);
+        let mut data = vec![0.0; width * height];
+        let norm = (factor * factor) as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for dy in 0..factor {
+                    let sy = (y * factor + dy).min(self.height - 1);
+                    for dx in 0..factor {
+                        let sx = (x * factor + dx).min(self.width - 1);
+                        sum += self.at(sx, sy);
+                    }
+                }
+                data[y * width + x] = sum / norm;
+            }
+        }
+        Plane { width, height, data }
+    }
+}
+
+/// Summed-area tables of values and squared values, for O(1) window statistics.
+struct Integral {
+    width: usize,
+    sum: Vec<f64>,
+    sq: Vec<f64>,
+}
+
+impl Integral {
+    fn new(p: &Plane) -> Self {
+        let width = p.width + 1;
+        let mut sum = vec![0.0; width * (p.height + 1)];
+        let mut sq = vec![0.0; width * (p.height + 1)];
+        for y in 0..p.height {
+            let mut row_sum = 0.0;
+            let mut row_sq = 0.0;
+            for x in 0..p.width {
+                let v = p.at(x, y) as f64;
+                row_sum += v;
+                row_sq += v * v;
+                sum[(y + 1) * width + x + 1] = sum[y * width + x + 1] + row_sum;
+                sq[(y + 1) * width + x + 1] = sq[y * width + x + 1] + row_sq;
+            }
+        }
+        Self { width, sum, sq }
+    }
+
+    /// Sum and squared sum of the `w`×`h` window at (`x`, `y`).
+    #[inline]
+    fn window(&self, x: usize, y: usize, w: usize, h: usize) -> (f64, f64) {
+        let a = y * self.width + x;
+        let b = y * self.width + x + w;
+        let c = (y + h) * self.width + x;
+        let d = (y + h) * self.width + x + w;
+        (
+            self.sum[d] - self.sum[b] - self.sum[c] + self.sum[a],
+            self.sq[d] - self.sq[b] - self.sq[c] + self.sq[a],
+        )
+    }
+}
+
+/// Zero-mean template, ready for normalized cross-correlation.
+struct Template {
+    plane: Plane,
+    /// Template samples minus their mean.
+    centered: Vec<f32>,
+    /// `sqrt(Σ centered²)`; near zero for flat templates.
+    norm: f64,
+    mean: f64,
+}
+
+impl Template {
+    fn new(plane: Plane) -> Self {
+        let n = plane.data.len() as f64;
+        let mean = plane.data.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let centered: Vec<f32> = plane.data.iter().map(|&v| v - mean as f32).collect();
+        let norm = centered
+            .iter()
+            .map(|&v| (v as f64) * (v as f64))
+            .sum::<f64>()
+            .sqrt();
+        Self {
+            plane,
+            centered,
+            norm,
+            mean,
+        }
+    }
+
+    /// Normalized cross-correlation of this template against `hay` at (`x`, `y`), in `-1..=1`.
+    fn score_at(&self, hay: &Plane, integral: &Integral, x: usize, y: usize) -> f32 {
+        let (w, h) = (self.plane.width, self.plane.height);
+        let n = (w * h) as f64;
+        let (sum, sq) = integral.window(x, y, w, h);
+        let var = (sq - sum * sum / n).max(0.0);
+
+        // Flat template (e.g. a solid swatch): fall back to mean similarity.
+        if self.norm < 1e-3 {
+            if var / n > 4.0 {
+                return 0.0;
+            }
+            return (1.0 - ((sum / n - self.mean).abs() / 255.0)) as f32;
+        }
+        if var < 1e-3 {
+            return 0.0;
+        }
+
+        let mut dot = 0.0f64;
+        for ty in 0..h {
+            let row = (y + ty) * hay.width + x;
+            let trow = ty * w;
+            let mut acc = 0.0f32;
+            for tx in 0..w {
+                acc += hay.data[row + tx] * self.centered[trow + tx];
+            }
+            dot += acc as f64;
+        }
+        (dot / (var.sqrt() * self.norm)) as f32
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Template matching
+// ---------------------------------------------------------------------------
+
+/// A template match in haystack pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Match {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Normalized cross-correlation score (1.0 = identical).
+    pub confidence: f32,
+    /// Template scale that produced this match.
+    pub scale: f32,
+}
+
+impl Match {
+    fn iou(&self, other: &Match) -> f32 {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.width).min(other.x + other.width);
+        let y1 = (self.y + self.height).min(other.y + other.height);
+        if x1 <= x0 || y1 <= y0 {
+            return 0.0;
+        }
+        let inter = ((x1 - x0) * (y1 - y0)) as f32;
+        let union = (self.width * self.height + other.width * other.height) as f32 - inter;
+        inter / union
+    }
+}
+
+/// Tuning for [`find_template`].
+#[derive(Debug, Clone)]
+pub struct MatchOptions {
+    /// Minimum confidence to report (0.0–1.0).
+    pub threshold: f32,
+    /// Template scales to try (1.0 = as saved).
+    pub scales: Vec<f32>,
+    /// Maximum number of matches to return.
+    pub max_results: usize,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            scales: vec![1.0, 0.9, 1.1, 0.75, 1.25, 0.5, 2.0],
+            max_results: 5,
+        }
+    }
+}
+
+/// Smallest template side we still trust at the coarse search level.
+const MIN_COARSE_SIDE: usize = 6;
+/// How far below the threshold a coarse candidate may score and still be refined.
+const COARSE_SLACK: f32 = 0.2;
+/// Candidates refined per scale.
+const MAX_CANDIDATES: usize = 24;
+
+/// Find occurrences of `needle` in `haystack` using multi-scale, coarse-to-fine
+/// normalized cross-correlation.
+///
+/// Each scale is first searched on a downsampled copy of both images; the best
+/// coarse hits are then refined at full resolution. Overlapping hits are
+/// merged (non-maximum suppression), best first.
+pub fn find_template(haystack: &GrayImage, needle: &GrayImage, opts: &MatchOptions) -> Vec<Match> {
+    let hay_full = Plane::from_gray(haystack);
+    let hay_integral = Integral::new(&hay_full);
+    let mut coarse_cache: Vec<(usize, Plane, Integral)> = Vec::new();
+    let mut found: Vec<Match> = Vec::new();
+
+    for &scale in &opts.scales {
+        if scale <= 0.0 {
+            continue;
+        }
+        let tw = (needle.width() as f32 * scale).round() as u32;
+        let th = (needle.height() as f32 * scale).round() as u32;
+        if tw < 4 || th < 4 || tw > haystack.width() || th > haystack.height() {
+            continue;
+        }
+        let scaled = if tw == needle.width() && th == needle.height() {
+            needle.clone()
+        } else {
+            imageops::resize(needle, tw, th, imageops::FilterType::Triangle)
+        };
+        let tpl_full = Template::new(Plane::from_gray(&scaled));
+
+        // Coarse pass on downsampled images.
+        let factor = ((tw.min(th) as usize) / MIN_COARSE_SIDE).clamp(1, 4);
+        if !coarse_cache.iter().any(|(f, _, _)| *f == factor) {
+            let plane = hay_full.downsample(factor);
+            let integral = Integral::new(&plane);
+            coarse_cache.push((factor, plane, integral));
+        }
+        let (_, hay_coarse, coarse_integral) = coarse_cache
+            .iter()
+            .find(|(f, _, _)| *f == factor)
+            .expect("coarse level just inserted");
+        let tpl_coarse = Template::new(tpl_full.plane.downsample(factor));
+        let (cw, ch) = (tpl_coarse.plane.width, tpl_coarse.plane.height);
+        if cw > hay_coarse.width || ch > hay_coarse.height {
+            continue;
+        }
+
+        let floor = opts.threshold - COARSE_SLACK;
+        let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+        for y in 0..=(hay_coarse.height - ch) {
+            for x in 0..=(hay_coarse.width - cw) {
+                let s = tpl_coarse.score_at(hay_coarse, coarse_integral, x, y);
+                if s >= floor {
+                    candidates.push((s, x, y));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        // Refine the best distinct coarse hits at full resolution.
+        let mut refined = 0;
+        let mut seen: Vec<(usize, usize)> = Vec::new();
+        for (_, cx, cy) in candidates {
+            if refined >= MAX_CANDIDATES {
+                break;
+            }
+            if seen
+                .iter()
+                .any(|&(sx, sy)| sx.abs_diff(cx) <= cw / 2 && sy.abs_diff(cy) <= ch / 2)
+            {
+                continue;
+            }
+            seen.push((cx, cy));
+            refined += 1;
+
+            let (fw, fh) = (tw as usize, th as usize);
+            let x_lo = (cx * factor).saturating_sub(factor);
+            let y_lo = (cy * factor).saturating_sub(factor);
+            let x_hi = (cx * factor + factor).min(hay_full.width - fw);
+            let y_hi = (cy * factor + factor).min(hay_full.height - fh);
+            let mut best: Option<(f32, usize, usize)> = None;
+            for y in y_lo..=y_hi.max(y_lo) {
+                for x in x_lo..=x_hi.max(x_lo) {
+                    if x + fw > hay_full.width || y + fh > hay_full.height {
+                        continue;
+                    }
+                    let s = tpl_full.score_at(&hay_full, &hay_integral, x, y);
+                    match best {
+                        Some((b, _, _)) if b >= s => {}
+                        _ => best = Some((s, x, y)),
+                    }
+                }
+            }
+            if let Some((s, x, y)) = best {
+                if s >= opts.threshold {
+                    found.push(Match {
+                        x: x as u32,
+                        y: y as u32,
+                        width: tw,
+                        height: th,
+                        confidence: s.min(1.0),
+                        scale,
+                    });
+                }
+            }
+        }
+    }
+
+    // Non-maximum suppression across all scales.
+    found.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut kept: Vec<Match> = Vec::new();
+    for m in found {
+        if kept.iter().all(|k| k.iou(&m) < 0.3) {
+            kept.push(m);
+        }
+        if kept.len() >= opts.max_results {
+            break;
+        }
+    }
+    kept
+}
+
+// ---------------------------------------------------------------------------
+// Change detection
+// ---------------------------------------------------------------------------
+
+/// A rectangle in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Tuning for [`diff_frames`].
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Per-pixel gray-level difference treated as noise (0–255).
+    pub pixel_tolerance: u8,
+    /// Grid cell size used to group changed pixels into boxes.
+    pub cell: u32,
+    /// Maximum number of boxes to report (largest first).
+    pub max_boxes: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            pixel_tolerance: 24,
+            cell: 16,
+            max_boxes: 10,
+        }
+    }
+}
+
+/// Outcome of comparing two frames.
+#[derive(Debug, Clone, Default)]
+pub struct FrameDiff {
+    /// Fraction of pixels that changed beyond the tolerance (0.0–1.0).
+    pub changed_fraction: f32,
+    /// Bounding boxes of connected changed areas, largest first.
+    pub boxes: Vec<PixelRect>,
+}
+
+/// Pixel-diff two grayscale frames of the same size.
+///
+/// Changed pixels are bucketed into a grid; each 8-connected run of changed
+/// cells becomes one box, tight around the changed pixels it contains.
+/// Frames of different sizes count as a full change.
+pub fn diff_frames(a: &GrayImage, b: &GrayImage, opts: &DiffOptions) -> FrameDiff {
+    let (w, h) = a.dimensions();
+    if b.dimensions() != (w, h) || w == 0 || h == 0 {
+        return FrameDiff {
+            changed_fraction: 1.0,
+            boxes: vec![PixelRect {
+                x: 0,
+                y: 0,
+                width: b.width(),
+                height: b.height(),
+            }],
+        };
+    }
+
+    let cell = opts.cell.max(1) as usize;
+    let gw = (w as usize).div_ceil(cell);
+    let gh = (h as usize).div_ceil(cell);
+    // Tight bounds (x0, y0, x1, y1) of the changed pixels in each grid cell.
+    let mut grid: Vec<Option<(usize, usize, usize, usize)>> = vec![None; gw * gh];
+    let mut changed = 0u64;
+
+    let (ra, rb) = (a.as_raw(), b.as_raw());
+    for y in 0..h as usize {
+        let row = y * w as usize;
+        for x in 0..w as usize {
+            if ra[row + x].abs_diff(rb[row + x]) > opts.pixel_tolerance {
+                changed += 1;
+                let c = &mut grid[(y / cell) * gw + x / cell];
+                *c = Some(match *c {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    None => (x, y, x, y),
+                });
+            }
+        }
+    }
+
+    let mut boxes = Vec::new();
+    let mut visited = vec![false; gw * gh];
+    for start in 0..grid.len() {
+        if grid[start].is_none() || visited[start] {
+            continue;
+        }
+        let (mut x0, mut y0, mut x1, mut y1) = (usize::MAX, usize::MAX, 0, 0);
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(i) = stack.pop() {
+            if let Some((cx0, cy0, cx1, cy1)) = grid[i] {
+                x0 = x0.min(cx0);
+                y0 = y0.min(cy0);
+                x1 = x1.max(cx1);
+                y1 = y1.max(cy1);
+            }
+            let (cx, cy) = (i % gw, i / gw);
+            for ny in cy.saturating_sub(1)..=(cy + 1).min(gh - 1) {
+                for nx in cx.saturating_sub(1)..=(cx + 1).min(gw - 1) {
+                    let j = ny * gw + nx;
+                    if grid[j].is_some() && !visited[j] {
+                        visited[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+        boxes.push(PixelRect {
+            x: x0 as u32,
+            y: y0 as u32,
+            width: (x1 - x0 + 1) as u32,
+            height: (y1 - y0 + 1) as u32,
+        });
+    }
+    boxes.sort_by_key(|r| std::cmp::Reverse(r.width as u64 * r.height as u64));
+    boxes.truncate(opts.max_boxes);
+
+    FrameDiff {
+        changed_fraction: changed as f32 / (w as f32 * h as f32),
+        boxes,
+    }
+}
+
+/// 64-bit difference hash (dHash) — a cheap perceptual fingerprint.
+pub fn dhash(img: &GrayImage) -> u64 {
+    let small = imageops::resize(img, 9, 8, imageops::FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+/// Number of differing bits between two hashes.
+pub fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+// ---------------------------------------------------------------------------
+// Overlays
+// ---------------------------------------------------------------------------
+
+const GLYPH_W: u32 = 5;
+const GLYPH_H: u32 = 7;
+
+/// 5x7 bitmap glyph, one byte per row, bit 4 = leftmost column.
+/// Letters render upper-case; unknown characters render as `?`.
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ' ' => [0; 7],
+        '_' => [0, 0, 0, 0, 0, 0, 0x1F],
+        '-' => [0, 0, 0, 0x1F, 0, 0, 0],
+        '.' => [0, 0, 0, 0, 0, 0x0C, 0x0C],
+        ',' => [0, 0, 0, 0, 0x0C, 0x04, 0x08],
+        ':' => [0, 0x0C, 0x0C, 0, 0x0C, 0x0C, 0],
+        '/' => [0, 0x01, 0x02, 0x04, 0x08, 0x10, 0],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+fn put_clipped(img: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>) {
+    if x >= 0 && y >= 0 && (x as u64) < img.width() as u64 && (y as u64) < img.height() as u64 {
+        img.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Draw `text` as white-on-black at `(x, y)` with each font pixel `scale`
+/// pixels wide. Anything past the image edge is clipped.
+pub fn draw_label(img: &mut RgbaImage, x: u32, y: u32, text: &str, scale: u32) {
+    let scale = scale.max(1);
+    let pad = 2 * scale;
+    let advance = (GLYPH_W + 1) * scale;
+    let box_w = text.chars().count() as u32 * advance + 2 * pad - scale;
+    let box_h = GLYPH_H * scale + 2 * pad;
+
+    let black = Rgba([0, 0, 0, 255]);
+    let white = Rgba([255, 255, 255, 255]);
+    for by in 0..box_h {
+        for bx in 0..box_w {
+            put_clipped(img, (x + bx) as i64, (y + by) as i64, black);
+        }
+    }
+    for (i, c) in text.chars().enumerate() {
+        let gx = x + pad + i as u32 * advance;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_W {
+                if bits & (0x10 >> col) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let px = gx + col * scale + sx;
+                        let py = y + pad + row as u32 * scale + sy;
+                        put_clipped(img, px as i64, py as i64, white);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draw a red ring with a center dot marking the cursor at `(x, y)`.
+pub fn draw_cursor(img: &mut RgbaImage, x: i64, y: i64, radius: u32) {
+    let red = Rgba([255, 40, 40, 255]);
+    let r = radius.max(2) as i64;
+    let thickness = (r / 4).max(1);
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let d2 = dx * dx + dy * dy;
+            let on_ring = d2 <= r * r && d2 >= (r - thickness) * (r - thickness);
+            let in_dot = d2 <= thickness * thickness;
+            if on_ring || in_dot {
+                put_clipped(img, x + dx, y + dy, red);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    /// A 20×20 icon of concentric squares, with bands wide enough to survive
+    /// the coarse pass.
+    fn icon() -> GrayImage {
+        GrayImage::from_fn(20, 20, |x, y| {
+            let ring = x.min(y).min(19 - x).min(19 - y);
+            Luma([if (ring / 3) % 2 == 0 { 230 } else { 30 }])
+        })
+    }
+
+    /// A smooth gradient that no textured template correlates with.
+    fn backdrop(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| Luma([(60 + x / 4 + y / 8) as u8]))
+    }
+
+    #[test]
+    fn finds_a_scaled_template_at_its_offset() {
+        let needle = icon();
+        let pasted = imageops::resize(&needle, 40, 40, imageops::FilterType::Triangle);
+        let mut haystack = backdrop(160, 120);
+        imageops::replace(&mut haystack, &pasted, 53, 31);
+
+        let opts = MatchOptions { threshold: 0.9, scales: vec![1.0, 2.0], max_results: 3 };
+        let found = find_template(&haystack, &needle, &opts);
+        let best = found.first().expect("a match");
+        assert_eq!((best.x, best.y, best.width, best.height), (53, 31, 40, 40));
+        assert_eq!(best.scale, 2.0);
+        assert!(best.confidence > 0.99, "confidence {}", best.confidence);
+    }
+
+    #[test]
+    fn no_match_below_the_threshold() {
+        let found = find_template(&backdrop(160, 120), &icon(), &MatchOptions::default());
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn dhash_ignores_brightness_but_not_content() {
+        let a = backdrop(64, 48);
+        let brighter = GrayImage::from_fn(64, 48, |x, y| Luma([a.get_pixel(x, y)[0] + 20]));
+        let flipped = imageops::flip_horizontal(&a);
+        assert_eq!(hamming(dhash(&a), dhash(&brighter)), 0);
+        assert!(hamming(dhash(&a), dhash(&flipped)) > 32);
+    }
+
+    #[test]
+    fn diff_frames_boxes_each_changed_area() {
+        let a = GrayImage::new(64, 48);
+        let mut b = a.clone();
+        let mut fill = |x0: u32, y0: u32, w: u32, h: u32, v: u8| {
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    b.put_pixel(x, y, Luma([v]));
+                }
+            }
+        };
+        fill(5, 6, 10, 4, 200);
+        fill(40, 30, 12, 8, 200);
+        // Within the tolerance: noise, not change.
+        fill(30, 2, 3, 3, 10);
+
+        let diff = diff_frames(&a, &b, &DiffOptions::default());
+        assert_eq!(
+            diff.boxes,
+            vec![
+                PixelRect { x: 40, y: 30, width: 12, height: 8 },
+                PixelRect { x: 5, y: 6, width: 10, height: 4 },
+            ]
+        );
+        assert_eq!(diff.changed_fraction, (40 + 96) as f32 / (64 * 48) as f32);
+    }
+
+    #[test]
+    fn diff_frames_of_different_sizes_is_a_full_change() {
+        let diff = diff_frames(&GrayImage::new(64, 48), &GrayImage::new(32, 24), &DiffOptions::default());
+        assert_eq!(diff.changed_fraction, 1.0);
+        assert_eq!(diff.boxes, vec![PixelRect { x: 0, y: 0, width: 32, height: 24 }]);
+    }
+}
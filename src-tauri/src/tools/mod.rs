@@ -2,8 +2,22 @@ pub mod automation;
 pub mod ax;
 pub mod ax_tools;
 pub mod browser;
+pub mod imaging;
+pub mod redact;
+pub mod screen;
+pub mod screen_tools;
 pub mod screenshot;
+pub mod web_bidi;
+pub mod web_console;
+pub mod web_dialog;
+pub mod web_files;
+pub mod web_input;
+pub mod web_network;
+pub mod web_page;
+pub mod web_session;
+pub mod web_snapshot;
 pub use automation::*;
 pub use ax_tools::*;
 pub use browser::*;
+pub use screen_tools::*;
 pub use screenshot::*;
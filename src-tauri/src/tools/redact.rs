@@ -0,0 +1,314 @@
+//! Privacy redaction for screen captures that leave the machine.
+//!
+//! Before a screenshot is sent to the vision API we black out:
+//! - windows of configured apps (password managers, Notification Center, ...)
+//! - windows whose title matches a configured `{app, title}` rule
+//! - secure text fields (password inputs) in the frontmost app, via the AX tree
+//! - fixed screen regions from the config
+//!
+//! Config lives in `~/.zeptobot/redaction.json`; every field is optional:
+//!
+//! ```json
+//! {
+//!   "enabled": true,
+//!   "apps": ["1Password", "Messages"],
+//!   "windows": [{ "app": "Google Chrome", "title": "Bank" }],
+//!   "regions": [{ "x": 0, "y": 0, "width": 400, "height": 40 }],
+//!   "secure_fields": true
+//! }
+//! ```
+//!
+//! Planning (`plan`) talks to the Accessibility API; applying (`apply`) is
+//! pure pixel work on a [`Capture`].
+
+use std::path::PathBuf;
+
+use image::Rgba;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use super::ax;
+use super::screen::{self, Capture, Region};
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Redact every window of `app` whose title contains `title` (case-insensitive).
+#[derive(Debug, Clone, Deserialize)]
+pub struct WindowRule {
+    pub app: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Apps whose windows are always redacted (process names).
+    pub apps: Vec<String>,
+    pub windows: Vec<WindowRule>,
+    /// Fixed screen regions, in screen points.
+    pub regions: Vec<Region>,
+    /// Redact secure text fields in the frontmost app.
+    pub secure_fields: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            apps: [
+                "1Password",
+                "1Password 7",
+                "Bitwarden",
+                "Dashlane",
+                "Keychain Access",
+                "Passwords",
+                "NotificationCenter",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            windows: Vec::new(),
+            regions: Vec::new(),
+            secure_fields: true,
+        }
+    }
+}
+
+impl RedactionConfig {
+    pub fn path() -> PathBuf {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+        PathBuf::from(home).join(".zeptobot").join("redaction.json")
+    }
+
+    /// Load the config file, falling back to defaults when it's missing.
+    /// A malformed file is an error — we never silently drop redaction rules.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| format!("Invalid redaction config {}: {e}", path.display())),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Whether planning needs the Accessibility API.
+    fn needs_ax(&self) -> bool {
+        self.secure_fields || !self.apps.is_empty() || !self.windows.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+/// An area to black out and why.
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    pub region: Region,
+    pub reason: String,
+}
+
+/// Work out what to redact on the current screen.
+///
+/// Blocking (AppleScript + AX calls) — run inside `spawn_blocking`.
+pub fn plan(config: &RedactionConfig) -> Result<Vec<Redaction>, String> {
+    let mut out = Vec::new();
+    if !config.enabled {
+        return Ok(out);
+    }
+
+    for (i, region) in config.regions.iter().enumerate() {
+        out.push(Redaction {
+            region: *region,
+            reason: format!("configured region #{}", i + 1),
+        });
+    }
+
+    if !config.needs_ax() {
+        return Ok(out);
+    }
+    if !ax::is_trusted() {
+        // Without AX we can't locate sensitive windows — fail closed.
+        return Err(
+            "Screenshot blocked: privacy redaction needs Accessibility permission to locate \
+             sensitive windows. Grant it in System Settings > Privacy & Security > Accessibility, \
+             or set \"enabled\": false in ~/.zeptobot/redaction.json."
+                .into(),
+        );
+    }
+
+    for app in &config.apps {
+        let Some(pid) = ax::app_pid(app) else {
+            continue;
+        };
+        for w in ax::window_frames(pid) {
+            out.push(Redaction {
+                region: frame_region(w.position, w.size),
+                reason: match w.title.as_deref() {
+                    Some(t) if !t.is_empty() => format!("{app} window \"{t}\""),
+                    _ => format!("{app} window"),
+                },
+            });
+        }
+    }
+
+    for rule in &config.windows {
+        let Some(pid) = ax::app_pid(&rule.app) else {
+            continue;
+        };
+        let needle = rule.title.to_lowercase();
+        for w in ax::window_frames(pid) {
+            let title = w.title.clone().unwrap_or_default();
+            if title.to_lowercase().contains(&needle) {
+                out.push(Redaction {
+                    region: frame_region(w.position, w.size),
+                    reason: format!("{} window \"{title}\"", rule.app),
+                });
+            }
+        }
+    }
+
+    if config.secure_fields {
+        if let Some(pid) = ax::frontmost_app_pid() {
+            for (pos, size) in ax::secure_field_frames(pid, 12) {
+                out.push(Redaction {
+                    region: frame_region(pos, size),
+                    reason: "secure text field".into(),
+                });
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn frame_region(position: (f64, f64), size: (f64, f64)) -> Region {
+    Region {
+        x: position.0,
+        y: position.1,
+        width: size.0,
+        height: size.1,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Applying
+// ---------------------------------------------------------------------------
+
+/// Black out each redaction that intersects the capture.
+///
+/// Returns a description of every area actually redacted (redactions that
+/// fall entirely outside the capture are skipped).
+pub fn apply(capture: &mut Capture, redactions: &[Redaction]) -> Vec<String> {
+    let mut applied = Vec::new();
+    for r in redactions {
+        let Ok((x, y, w, h)) = capture.region_to_pixels(&r.region) else {
+            continue;
+        };
+        for py in y..y + h {
+            for px in x..x + w {
+                capture.image.put_pixel(px, py, Rgba([0, 0, 0, 255]));
+            }
+        }
+        applied.push(format!(
+            "{} at ({:.0},{:.0} {:.0}x{:.0})",
+            r.reason, r.region.x, r.region.y, r.region.width, r.region.height
+        ));
+    }
+    applied
+}
+
+/// Capture the screen (optionally a region) with redaction applied.
+///
+/// Returns the redacted capture and the list of redacted areas, which is
+/// also logged. Blocking — run inside `spawn_blocking`.
+pub fn capture_redacted(region: Option<&Region>) -> Result<(Capture, Vec<String>), String> {
+    let config = RedactionConfig::load()?;
+    let redactions = plan(&config)?;
+    let mut capture = screen::capture_region(region)?;
+    let applied = apply(&mut capture, &redactions);
+    for a in &applied {
+        info!("[Redact] {a}");
+    }
+    if !config.enabled {
+        warn!("[Redact] Redaction disabled in {}", RedactionConfig::path().display());
+    }
+    Ok((capture, applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    /// A white Retina capture of screen points (100,50)–(200,100): 200×100 pixels.
+    fn capture() -> Capture {
+        Capture {
+            image: RgbaImage::from_pixel(200, 100, WHITE),
+            scale: 2.0,
+            origin: (100.0, 50.0),
+        }
+    }
+
+    fn redaction(x: f64, y: f64, width: f64, height: f64) -> Redaction {
+        Redaction { region: Region { x, y, width, height }, reason: "test".into() }
+    }
+
+    /// Every pixel inside one of the `(x, y, w, h)` boxes is black; every other one is white.
+    fn assert_blacked_out(capture: &Capture, boxes: &[(u32, u32, u32, u32)]) {
+        for (px, py, pixel) in capture.image.enumerate_pixels() {
+            let inside = boxes
+                .iter()
+                .any(|&(x, y, w, h)| px >= x && px < x + w && py >= y && py < y + h);
+            let want = if inside { BLACK } else { WHITE };
+            assert_eq!(*pixel, want, "pixel ({px},{py})");
+        }
+    }
+
+    #[test]
+    fn region_to_pixels_maps_points_through_origin_and_scale() {
+        let c = capture();
+        let inside = Region { x: 110.0, y: 60.0, width: 10.0, height: 5.0 };
+        assert_eq!(c.region_to_pixels(&inside), Ok((20, 20, 20, 10)));
+        let straddling = Region { x: 190.0, y: 90.0, width: 30.0, height: 30.0 };
+        assert_eq!(c.region_to_pixels(&straddling), Ok((180, 80, 20, 20)));
+        let outside = Region { x: 0.0, y: 0.0, width: 50.0, height: 40.0 };
+        assert!(c.region_to_pixels(&outside).is_err());
+    }
+
+    #[test]
+    fn blacks_out_a_fully_covered_region_exactly() {
+        let mut c = capture();
+        let applied = apply(&mut c, &[redaction(110.0, 60.0, 10.0, 5.0)]);
+        assert_eq!(applied, vec!["test at (110,60 10x5)"]);
+        assert_blacked_out(&c, &[(20, 20, 20, 10)]);
+    }
+
+    #[test]
+    fn clamps_regions_that_overlap_the_edges() {
+        let mut c = capture();
+        let applied = apply(
+            &mut c,
+            &[redaction(190.0, 90.0, 30.0, 30.0), redaction(90.0, 40.0, 15.0, 15.0)],
+        );
+        assert_eq!(applied.len(), 2);
+        assert_blacked_out(&c, &[(180, 80, 20, 20), (0, 0, 10, 10)]);
+    }
+
+    #[test]
+    fn skips_regions_outside_the_capture() {
+        let mut c = capture();
+        let applied = apply(
+            &mut c,
+            &[redaction(0.0, 0.0, 50.0, 40.0), redaction(300.0, 60.0, 20.0, 20.0)],
+        );
+        assert!(applied.is_empty(), "{applied:?}");
+        assert_blacked_out(&c, &[]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Screen capture helpers shared by the vision tools.
+//!
+//! Captures go through autopilot, then get re-decoded with our own `image`
+//! version (autopilot pins an old one, so we round-trip through PNG like
+//! `ScreenshotTool` does). Coordinates exposed to the agent are always in
+//! screen points — the same space `move_mouse` and the AX tools use — while
+//! the pixel buffers may be larger on Retina displays.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use image::{imageops, RgbaImage};
+use serde_json::Value;
+
+static CAPTURE_ID: AtomicU64 = AtomicU64::new(1);
+
+// ---------------------------------------------------------------------------
+// Regions
+// ---------------------------------------------------------------------------
+
+/// A rectangle in screen points.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Region {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Region {
+    /// Parse an optional `region` object (`{x, y, width, height}`) from tool args.
+    pub fn from_args(args: &Value, key: &str) -> Result<Option<Region>, String> {
+        let Some(raw) = args.get(key) else {
+            return Ok(None);
+        };
+        if raw.is_null() {
+            return Ok(None);
+        }
+        let region: Region = serde_json::from_value(raw.clone())
+            .map_err(|e| format!("Invalid '{key}' (expected {{x, y, width, height}}): {e}"))?;
+        if region.width <= 0.0 || region.height <= 0.0 {
+            return Err(format!("'{key}' must have a positive width and height"));
+        }
+        Ok(Some(region))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Capture
+// ---------------------------------------------------------------------------
+
+/// A decoded screen capture plus the mapping back to screen points.
+pub struct Capture {
+    pub image: RgbaImage,
+    /// Pixels per screen point (2.0 on Retina displays).
+    pub scale: f64,
+    /// Top-left of this capture in screen points (non-zero after `crop`).
+    pub origin: (f64, f64),
+}
+
+impl Capture {
+    /// Crop to a region given in screen points. The region is clamped to the capture.
+    pub fn crop(&self, region: &Region) -> Result<Capture, String> {
+        let (px, py, pw, ph) = self.region_to_pixels(region)?;
+        Ok(Capture {
+            image: imageops::crop_imm(&self.image, px, py, pw, ph).to_image(),
+            scale: self.scale,
+            origin: (
+                self.origin.0 + px as f64 / self.scale,
+                self.origin.1 + py as f64 / self.scale,
+            ),
+        })
+    }
+
+    /// Convert a region in screen points to a clamped pixel rectangle.
+    pub fn region_to_pixels(&self, region: &Region) -> Result<(u32, u32, u32, u32), String> {
+        let (w, h) = self.image.dimensions();
+        let x0 = ((region.x - self.origin.0) * self.scale).max(0.0).round() as u32;
+        let y0 = ((region.y - self.origin.1) * self.scale).max(0.0).round() as u32;
+        let x1 = (((region.x + region.width - self.origin.0) * self.scale).round() as u32).min(w);
+        let y1 = (((region.y + region.height - self.origin.1) * self.scale).round() as u32).min(h);
+        if x0 >= x1 || y0 >= y1 {
+            return Err("Region is outside the captured screen area".into());
+        }
+        Ok((x0, y0, x1 - x0, y1 - y0))
+    }
+
+    /// Convert a pixel rectangle in this capture to a region in screen points.
+    pub fn pixels_to_region(&self, x: u32, y: u32, width: u32, height: u32) -> Region {
+        Region {
+            x: self.origin.0 + x as f64 / self.scale,
+            y: self.origin.1 + y as f64 / self.scale,
+            width: width as f64 / self.scale,
+            height: height as f64 / self.scale,
+        }
+    }
+}
+
+/// Capture the full screen.
+pub fn capture_screen() -> Result<Capture, String> {
+    let bitmap = autopilot::bitmap::capture_screen()
+        .map_err(|e| format!("Failed to capture screen: {e}"))?;
+
+    // Unique temp name — several tools may capture concurrently.
+    let tmp_path = std::env::temp_dir().join(format!(
+        "zeptobot_capture_{}_{}.png",
+        std::process::id(),
+        CAPTURE_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    bitmap
+        .image
+        .save(&tmp_path)
+        .map_err(|e| format!("Failed to save capture: {e}"))?;
+    let bytes = std::fs::read(&tmp_path).map_err(|e| format!("Failed to read capture: {e}"));
+    let _ = std::fs::remove_file(&tmp_path);
+
+    let image = image::load_from_memory(&bytes?)
+        .map_err(|e| format!("Failed to decode capture: {e}"))?
+        .to_rgba8();
+
+    Ok(Capture {
+        image,
+        scale: if bitmap.scale > 0.0 { bitmap.scale } else { 1.0 },
+        origin: (0.0, 0.0),
+    })
+}
+
+/// Capture the screen, optionally cropped to a region.
+pub fn capture_region(region: Option<&Region>) -> Result<Capture, String> {
+    let full = capture_screen()?;
+    match region {
+        Some(r) => full.crop(r),
+        None => Ok(full),
+    }
+}
+
+/// Encode an RGBA image as PNG bytes.
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut buf = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut buf, image::ImageFormat::Png)
+        .map_err(|e| format!("PNG encode failed: {e}"))?;
+    Ok(buf.into_inner())
+}
+
+// ---------------------------------------------------------------------------
+// Reference image library (~/.zeptobot/images)
+// ---------------------------------------------------------------------------
+
+/// Directory holding named reference images for `find_image`.
+pub fn images_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    PathBuf::from(home).join(".zeptobot").join("images")
+}
+
+/// Path of a named reference image. Names are restricted to `[A-Za-z0-9_-]`.
+pub fn reference_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!(
+            "Invalid image name '{name}'. Use letters, digits, '-' and '_' only."
+        ));
+    }
+    Ok(images_dir().join(format!("{name}.png")))
+}
+
+/// Names of all saved reference images, sorted.
+pub fn list_reference_images() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(images_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let path = e.path();
+                    if path.extension().and_then(|x| x.to_str()) != Some("png") {
+                        return None;
+                    }
+                    path.file_stem().and_then(|s| s.to_str()).map(String::from)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
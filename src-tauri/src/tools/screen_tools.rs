@@ -0,0 +1,608 @@
+//! Agent tools that work on raw screen pixels.
+//!
+//! These fill the gap between the Accessibility API (which can't see
+//! unlabeled icons) and the vision model (slow and costly): the agent can
+//! locate a known icon by template matching, or synchronise with animations
+//! and loading states by diffing successive captures, instead of describing
+//! the whole screen.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use image::GrayImage;
+use serde_json::{json, Value};
+use tracing::info;
+use zeptoclaw::tools::ToolOutput;
+use zeptoclaw::{Result as ZeptoResult, Tool, ToolCategory, ToolContext};
+
+use super::imaging::{self, DiffOptions, FrameDiff, MatchOptions, PixelRect};
+use super::screen::{self, Region};
+
+// ---------------------------------------------------------------------------
+// FindImageTool — locate a reference image on screen
+// ---------------------------------------------------------------------------
+
+pub struct FindImageTool;
+
+#[async_trait]
+impl Tool for FindImageTool {
+    fn name(&self) -> &str {
+        "find_image"
+    }
+
+    fn description(&self) -> &str {
+        "Find a reference image (e.g. an unlabeled toolbar icon) on screen using multi-scale \
+         template matching. Returns the center and bounding box of each match in screen \
+         coordinates plus a confidence score, ready for move_mouse + click. Reference images \
+         come from save_reference_image or a PNG path."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Name of a saved reference image"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Path to a PNG file to search for (alternative to 'name')"
+                },
+                "region": {
+                    "type": "object",
+                    "description": "Only search this screen area: {x, y, width, height} in screen coordinates",
+                    "properties": {
+                        "x": { "type": "number" },
+                        "y": { "type": "number" },
+                        "width": { "type": "number" },
+                        "height": { "type": "number" }
+                    }
+                },
+                "threshold": {
+                    "type": "number",
+                    "description": "Minimum confidence 0.0-1.0 (default: 0.8)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum matches to return (default: 5)"
+                },
+                "scales": {
+                    "type": "array",
+                    "items": { "type": "number" },
+                    "description": "Template scales to try (default: [1.0, 0.9, 1.1, 0.75, 1.25, 0.5, 2.0])"
+                }
+            },
+            "required": []
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Shell
+    }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let (label, path) = if let Some(name) = args.get("name").and_then(Value::as_str) {
+            match screen::reference_path(name) {
+                Ok(p) => (name.to_string(), p),
+                Err(e) => return Ok(ToolOutput::error(e)),
+            }
+        } else if let Some(p) = args.get("path").and_then(Value::as_str) {
+            (p.to_string(), std::path::PathBuf::from(p))
+        } else {
+            return Ok(ToolOutput::error("Provide 'name' or 'path'"));
+        };
+
+        if !path.exists() {
+            let saved = screen::list_reference_images();
+            return Ok(ToolOutput::error(if saved.is_empty() {
+                format!("Reference image '{label}' not found. No images saved yet — use save_reference_image.")
+            } else {
+                format!(
+                    "Reference image '{label}' not found. Saved images: {}",
+                    saved.join(", ")
+                )
+            }));
+        }
+
+        let region = match Region::from_args(&args, "region") {
+            Ok(r) => r,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+
+        let mut opts = MatchOptions::default();
+        if let Some(t) = args.get("threshold").and_then(Value::as_f64) {
+            opts.threshold = (t as f32).clamp(0.0, 1.0);
+        }
+        if let Some(n) = args.get("max_results").and_then(Value::as_u64) {
+            opts.max_results = (n as usize).clamp(1, 50);
+        }
+        if let Some(arr) = args.get("scales").and_then(Value::as_array) {
+            let scales: Vec<f32> = arr
+                .iter()
+                .filter_map(Value::as_f64)
+                .map(|s| s as f32)
+                .filter(|s| *s > 0.0 && *s <= 4.0)
+                .collect();
+            if !scales.is_empty() {
+                opts.scales = scales;
+            }
+        }
+
+        let threshold = opts.threshold;
+        info!("[FindImage] Searching for '{label}'...");
+
+        let result = tokio::task::spawn_blocking(move || -> Result<_, String> {
+            let needle = image::open(&path)
+                .map_err(|e| format!("Failed to load reference image: {e}"))?
+                .to_luma8();
+            let capture = screen::capture_region(region.as_ref())?;
+            let haystack = image::imageops::grayscale(&capture.image);
+            let matches = imaging::find_template(&haystack, &needle, &opts);
+            Ok((capture, matches))
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Task panicked: {e}")));
+
+        let (capture, matches) = match result {
+            Ok(r) => r,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+
+        if matches.is_empty() {
+            return Ok(ToolOutput::llm_only(format!(
+                "No match for '{label}' above confidence {:.2}. Try a lower threshold, \
+                 a wider region, or re-save the reference image.",
+                threshold
+            )));
+        }
+
+        let mut lines = vec![format!("Found {} match(es) for '{label}':", matches.len())];
+        for (i, m) in matches.iter().enumerate() {
+            let r = capture.pixels_to_region(m.x, m.y, m.width, m.height);
+            lines.push(format!(
+                "  #{} center=({:.0},{:.0}) box=({:.0},{:.0} {:.0}x{:.0}) confidence={:.2} scale={:.2}",
+                i + 1,
+                r.x + r.width / 2.0,
+                r.y + r.height / 2.0,
+                r.x,
+                r.y,
+                r.width,
+                r.height,
+                m.confidence,
+                m.scale
+            ));
+        }
+        Ok(ToolOutput::llm_only(lines.join("\n")))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SaveReferenceImageTool — store a screen region in the image library
+// ---------------------------------------------------------------------------
+
+pub struct SaveReferenceImageTool;
+
+#[async_trait]
+impl Tool for SaveReferenceImageTool {
+    fn name(&self) -> &str {
+        "save_reference_image"
+    }
+
+    fn description(&self) -> &str {
+        "Save a region of the current screen as a named reference image for find_image \
+         (stored in ~/.zeptobot/images). Crop tightly around the icon or control. \
+         Omit 'region' to list the saved images."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Name to save under (letters, digits, '-', '_'), e.g. 'slack-settings-gear'"
+                },
+                "region": {
+                    "type": "object",
+                    "description": "Screen area to capture: {x, y, width, height} in screen coordinates",
+                    "properties": {
+                        "x": { "type": "number" },
+                        "y": { "type": "number" },
+                        "width": { "type": "number" },
+                        "height": { "type": "number" }
+                    }
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "description": "Replace an existing image with the same name (default: false)"
+                }
+            },
+            "required": []
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Shell
+    }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let region = match Region::from_args(&args, "region") {
+            Ok(Some(r)) => r,
+            Ok(None) => {
+                let saved = screen::list_reference_images();
+                return Ok(ToolOutput::llm_only(if saved.is_empty() {
+                    "No reference images saved yet.".to_string()
+                } else {
+                    format!("Saved reference images: {}", saved.join(", "))
+                }));
+            }
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+
+        let name = match args.get("name").and_then(Value::as_str) {
+            Some(n) => n.to_string(),
+            None => return Ok(ToolOutput::error("Missing 'name' parameter")),
+        };
+        let path = match screen::reference_path(&name) {
+            Ok(p) => p,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let overwrite = args.get("overwrite").and_then(Value::as_bool).unwrap_or(false);
+        if path.exists() && !overwrite {
+            return Ok(ToolOutput::error(format!(
+                "Reference image '{name}' already exists. Pass overwrite=true to replace it."
+            )));
+        }
+
+        let result = tokio::task::spawn_blocking(move || -> Result<(u32, u32), String> {
+            let capture = screen::capture_region(Some(&region))?;
+            std::fs::create_dir_all(screen::images_dir())
+                .map_err(|e| format!("Failed to create image library: {e}"))?;
+            capture
+                .image
+                .save(&path)
+                .map_err(|e| format!("Failed to save image: {e}"))?;
+            Ok(capture.image.dimensions())
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Task panicked: {e}")));
+
+        match result {
+            Ok((w, h)) => {
+                info!("[FindImage] Saved reference image '{name}' ({w}x{h}px)");
+                Ok(ToolOutput::llm_only(format!(
+                    "Saved reference image '{name}' ({w}x{h}px). Use find_image name='{name}' to locate it."
+                )))
+            }
+            Err(e) => Ok(ToolOutput::error(e)),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Change detection shared by the wait tools
+// ---------------------------------------------------------------------------
+
+/// How successive captures are compared.
+#[derive(Clone, Copy, PartialEq)]
+enum ChangeMethod {
+    /// Per-pixel gray-level diff (precise, reports changed boxes).
+    Pixel,
+    /// Perceptual hash distance (cheap, ignores small changes).
+    Hash,
+}
+
+/// A grayscale capture plus what's needed to map it back to screen points.
+struct Frame {
+    gray: GrayImage,
+    hash: u64,
+    scale: f64,
+    origin: (f64, f64),
+}
+
+impl Frame {
+    fn to_region(&self, r: &PixelRect) -> Region {
+        Region {
+            x: self.origin.0 + r.x as f64 / self.scale,
+            y: self.origin.1 + r.y as f64 / self.scale,
+            width: r.width as f64 / self.scale,
+            height: r.height as f64 / self.scale,
+        }
+    }
+
+    fn describe(&self, diff: &FrameDiff) -> String {
+        if diff.boxes.is_empty() {
+            return "none".into();
+        }
+        diff.boxes
+            .iter()
+            .map(|b| {
+                let r = self.to_region(b);
+                format!("({:.0},{:.0} {:.0}x{:.0})", r.x, r.y, r.width, r.height)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Parameters common to `wait_for_screen_change` and `wait_until_stable`.
+struct ChangeWatch {
+    region: Option<Region>,
+    timeout: Duration,
+    interval: Duration,
+    /// Minimum changed fraction of the region (pixel method).
+    min_change: f32,
+    /// Minimum dHash distance in bits (hash method).
+    min_hash_bits: u32,
+    method: ChangeMethod,
+    diff: DiffOptions,
+}
+
+impl ChangeWatch {
+    fn from_args(args: &Value, default_timeout_ms: u64) -> Result<Self, String> {
+        let method = match args.get("method").and_then(Value::as_str).unwrap_or("pixel") {
+            "pixel" => ChangeMethod::Pixel,
+            "hash" => ChangeMethod::Hash,
+            other => return Err(format!("Unknown method '{other}'. Use 'pixel' or 'hash'.")),
+        };
+        let mut diff = DiffOptions::default();
+        if let Some(t) = args.get("tolerance").and_then(Value::as_u64) {
+            diff.pixel_tolerance = t.min(255) as u8;
+        }
+        Ok(Self {
+            region: Region::from_args(args, "region")?,
+            timeout: Duration::from_millis(
+                args.get("timeout_ms")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(default_timeout_ms)
+                    .min(30_000),
+            ),
+            interval: Duration::from_millis(
+                args.get("interval_ms")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(250)
+                    .clamp(50, 5_000),
+            ),
+            min_change: args
+                .get("min_change")
+                .and_then(Value::as_f64)
+                .map(|v| v.clamp(0.0, 1.0) as f32)
+                .unwrap_or(0.001),
+            min_hash_bits: args
+                .get("min_hash_bits")
+                .and_then(Value::as_u64)
+                .map(|v| v.clamp(1, 64) as u32)
+                .unwrap_or(4),
+            method,
+            diff,
+        })
+    }
+
+    async fn grab(&self) -> Result<Frame, String> {
+        let region = self.region;
+        tokio::task::spawn_blocking(move || {
+            let capture = screen::capture_region(region.as_ref())?;
+            let gray = image::imageops::grayscale(&capture.image);
+            Ok(Frame {
+                hash: imaging::dhash(&gray),
+                gray,
+                scale: capture.scale,
+                origin: capture.origin,
+            })
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Task panicked: {e}")))
+    }
+
+    /// Compare two frames; `Some` when the change is above the thresholds.
+    fn compare(&self, before: &Frame, after: &Frame) -> Option<FrameDiff> {
+        if self.method == ChangeMethod::Hash
+            && imaging::hamming(before.hash, after.hash) < self.min_hash_bits
+        {
+            return None;
+        }
+        let diff = imaging::diff_frames(&before.gray, &after.gray, &self.diff);
+        if self.method == ChangeMethod::Pixel && diff.changed_fraction < self.min_change {
+            return None;
+        }
+        Some(diff)
+    }
+}
+
+/// JSON schema properties shared by the wait tools.
+fn change_watch_properties(timeout_default: u64) -> Value {
+    json!({
+        "region": {
+            "type": "object",
+            "description": "Only watch this screen area: {x, y, width, height} in screen coordinates (default: full screen)",
+            "properties": {
+                "x": { "type": "number" },
+                "y": { "type": "number" },
+                "width": { "type": "number" },
+                "height": { "type": "number" }
+            }
+        },
+        "timeout_ms": {
+            "type": "integer",
+            "description": format!("Max wait ms (default: {timeout_default}, max: 30000)")
+        },
+        "interval_ms": {
+            "type": "integer",
+            "description": "Delay between captures in ms (default: 250)"
+        },
+        "method": {
+            "type": "string",
+            "enum": ["pixel", "hash"],
+            "description": "'pixel' diff (default, precise) or perceptual 'hash' (ignores small changes like a blinking caret)"
+        },
+        "min_change": {
+            "type": "number",
+            "description": "Pixel method: fraction of the region that must change (default: 0.001)"
+        },
+        "min_hash_bits": {
+            "type": "integer",
+            "description": "Hash method: perceptual hash distance that counts as a change (default: 4)"
+        },
+        "tolerance": {
+            "type": "integer",
+            "description": "Per-pixel gray-level difference ignored as noise, 0-255 (default: 24)"
+        }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// WaitForScreenChangeTool — block until something on screen changes
+// ---------------------------------------------------------------------------
+
+pub struct WaitForScreenChangeTool;
+
+#[async_trait]
+impl Tool for WaitForScreenChangeTool {
+    fn name(&self) -> &str {
+        "wait_for_screen_change"
+    }
+
+    fn description(&self) -> &str {
+        "Wait until the screen (or a region) changes, e.g. after clicking a button in a native \
+         app. Returns how long it took and the changed areas in screen coordinates. Much cheaper \
+         than wait + take_screenshot."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": change_watch_properties(5000),
+            "required": []
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Shell
+    }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let watch = match ChangeWatch::from_args(&args, 5000) {
+            Ok(w) => w,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+
+        let start = tokio::time::Instant::now();
+        let baseline = match watch.grab().await {
+            Ok(f) => f,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+
+        loop {
+            tokio::time::sleep(watch.interval).await;
+            let frame = match watch.grab().await {
+                Ok(f) => f,
+                Err(e) => return Ok(ToolOutput::error(e)),
+            };
+            if let Some(diff) = watch.compare(&baseline, &frame) {
+                let elapsed = start.elapsed().as_millis();
+                info!("[ScreenWait] Change detected after {elapsed}ms");
+                return Ok(ToolOutput::llm_only(format!(
+                    "Screen changed after {elapsed}ms ({:.1}% of area). Changed areas: {}",
+                    diff.changed_fraction * 100.0,
+                    frame.describe(&diff)
+                )));
+            }
+            if start.elapsed() >= watch.timeout {
+                return Ok(ToolOutput::error(format!(
+                    "No screen change within {}ms",
+                    watch.timeout.as_millis()
+                )));
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WaitUntilStableTool — block until the screen stops changing
+// ---------------------------------------------------------------------------
+
+pub struct WaitUntilStableTool;
+
+#[async_trait]
+impl Tool for WaitUntilStableTool {
+    fn name(&self) -> &str {
+        "wait_until_stable"
+    }
+
+    fn description(&self) -> &str {
+        "Wait until the screen (or a region) stops changing for 'stable_ms' — use after \
+         actions that trigger animations, spinners, or loading states before reading or \
+         clicking. Returns how long it took; on timeout reports the areas still changing."
+    }
+
+    fn parameters(&self) -> Value {
+        let mut props = change_watch_properties(10000);
+        props["stable_ms"] = json!({
+            "type": "integer",
+            "description": "How long the screen must stay unchanged (default: 500)"
+        });
+        json!({
+            "type": "object",
+            "properties": props,
+            "required": []
+        })
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Shell
+    }
+
+    async fn execute(&self, args: Value, _ctx: &ToolContext) -> ZeptoResult<ToolOutput> {
+        let watch = match ChangeWatch::from_args(&args, 10000) {
+            Ok(w) => w,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let stable_for = Duration::from_millis(
+            args.get("stable_ms")
+                .and_then(Value::as_u64)
+                .unwrap_or(500)
+                .clamp(100, 10_000),
+        );
+
+        let start = tokio::time::Instant::now();
+        let mut last = match watch.grab().await {
+            Ok(f) => f,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
+        let mut last_change = start;
+        let mut last_diff: Option<FrameDiff> = None;
+
+        loop {
+            tokio::time::sleep(watch.interval).await;
+            let frame = match watch.grab().await {
+                Ok(f) => f,
+                Err(e) => return Ok(ToolOutput::error(e)),
+            };
+            let now = tokio::time::Instant::now();
+            if let Some(diff) = watch.compare(&last, &frame) {
+                last_change = now;
+                last_diff = Some(diff);
+                last = frame;
+            } else if now.duration_since(last_change) >= stable_for {
+                let elapsed = start.elapsed().as_millis();
+                info!("[ScreenWait] Stable after {elapsed}ms");
+                return Ok(ToolOutput::llm_only(format!(
+                    "Screen stable after {elapsed}ms (unchanged for {}ms)",
+                    stable_for.as_millis()
+                )));
+            }
+
+            if start.elapsed() >= watch.timeout {
+                let areas = last_diff
+                    .as_ref()
+                    .map(|d| last.describe(d))
+                    .unwrap_or_else(|| "none".into());
+                return Ok(ToolOutput::error(format!(
+                    "Screen still changing after {}ms. Last changed areas: {areas}",
+                    watch.timeout.as_millis()
+                )));
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
 //! Screenshot tool with GPT-4o-mini vision analysis.
 //!
-//! Captures the screen using autopilot, sends to OpenAI vision API,
-//! and returns a text description of what's on screen.
+//! Captures the screen using autopilot, redacts private areas (see
+//! `redact`), sends to OpenAI vision API, and returns a text description
+//! of what's on screen. [`describe_image`] is shared with `browser_screenshot`.
 
 use async_trait::async_trait;
 use base64::Engine;
@@ -10,6 +11,8 @@ use tracing::info;
 use zeptoclaw::tools::ToolOutput;
 use zeptoclaw::{Result as ZeptoResult, Tool, ToolCategory, ToolContext};
 
+use super::{redact, screen};
+
 pub struct ScreenshotTool;
 
 #[async_trait]
@@ -49,51 +52,22 @@ impl Tool for ScreenshotTool {
 
         info!("[Screenshot] Capturing screen...");
 
-        // Capture screen using autopilot
-        let bitmap = match autopilot::bitmap::capture_screen() {
-            Ok(b) => b,
-            Err(e) => {
-                return Ok(ToolOutput::error(format!(
-                    "Failed to capture screen: {e}"
-                )));
-            }
+        // Capture with privacy redaction applied before anything leaves the machine
+        let captured = tokio::task::spawn_blocking(|| redact::capture_redacted(None))
+            .await
+            .unwrap_or_else(|e| Err(format!("Task panicked: {e}")));
+        let (capture, redacted) = match captured {
+            Ok(c) => c,
+            Err(e) => return Ok(ToolOutput::error(e)),
         };
 
-        // Save to temp file then read back (avoids image crate version conflicts)
-        let tmp_path = std::env::temp_dir().join("zeptobot_screenshot.png");
-        if let Err(e) = bitmap.image.save(&tmp_path) {
-            return Ok(ToolOutput::error(format!(
-                "Failed to save screenshot: {e}"
-            )));
-        }
-
-        let png_bytes = match std::fs::read(&tmp_path) {
+        let png_bytes = match screen::encode_png(&capture.image) {
             Ok(b) => b,
-            Err(e) => {
-                return Ok(ToolOutput::error(format!(
-                    "Failed to read screenshot: {e}"
-                )));
-            }
+            Err(e) => return Ok(ToolOutput::error(e)),
         };
-        let _ = std::fs::remove_file(&tmp_path);
-
-        let b64 = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
-        info!(
-            "[Screenshot] Captured, base64 size: {} bytes, sending to vision API...",
-            b64.len()
-        );
 
-        // Get API key
-        let api_key = match std::env::var("OPENAI_API_KEY") {
-            Ok(k) => k,
-            Err(_) => {
-                return Ok(ToolOutput::error(
-                    "OPENAI_API_KEY not set — needed for vision analysis",
-                ));
-            }
-        };
+        crate::services::session::record_image(&png_bytes, context);
 
-        // Call OpenAI vision API
         let prompt = format!(
             "You are a screen reader for a desktop automation assistant. \
              Describe what you see on this macOS screenshot in detail. Include:\n\
@@ -102,69 +76,85 @@ impl Tool for ScreenshotTool {
                (top-left, center, bottom-right, etc.)\n\
              - The state of the UI (e.g. search field is active, dialog is open)\n\
              - Any notifications or popups\n\n\
+             Solid black rectangles are privacy redactions — do not guess their contents.\n\n\
              User context: {context}\n\n\
              Be concise but thorough. Focus on actionable information."
         );
+        let description = match describe_image(&png_bytes, &prompt).await {
+            Ok(d) => d,
+            Err(e) => return Ok(ToolOutput::error(e)),
+        };
 
-        let body = json!({
-            "model": "gpt-4o-mini",
-            "max_tokens": 1000,
-            "messages": [{
-                "role": "user",
-                "content": [
-                    {
-                        "type": "text",
-                        "text": prompt
-                    },
-                    {
-                        "type": "image_url",
-                        "image_url": {
-                            "url": format!("data:image/png;base64,{b64}"),
-                            "detail": "high"
-                        }
-                    }
-                ]
-            }]
-        });
-
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
-            .json(&body)
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) => {
-                if !resp.status().is_success() {
-                    let status = resp.status();
-                    let text = resp.text().await.unwrap_or_default();
-                    return Ok(ToolOutput::error(format!(
-                        "Vision API error ({status}): {text}"
-                    )));
-                }
+        let note = if redacted.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\n[{} area(s) blacked out for privacy: {}]",
+                redacted.len(),
+                redacted.join("; ")
+            )
+        };
+        Ok(ToolOutput::llm_only(format!(
+            "SCREEN CONTENT:\n{description}{note}"
+        )))
+    }
+}
 
-                let json: Value = match resp.json().await {
-                    Ok(j) => j,
-                    Err(e) => {
-                        return Ok(ToolOutput::error(format!(
-                            "Failed to parse vision response: {e}"
-                        )));
+/// Send a PNG to the OpenAI vision API with `prompt` and return its description.
+pub async fn describe_image(png: &[u8], prompt: &str) -> Result<String, String> {
+    let b64 = base64::engine::general_purpose::STANDARD.encode(png);
+    info!(
+        "[Screenshot] Captured, base64 size: {} bytes, sending to vision API...",
+        b64.len()
+    );
+
+    let api_key = std::env::var("OPENAI_API_KEY")
+        .map_err(|_| "OPENAI_API_KEY not set — needed for vision analysis".to_string())?;
+
+    let body = json!({
+        "model": "gpt-4o-mini",
+        "max_tokens": 1000,
+        "messages": [{
+            "role": "user",
+            "content": [
+                {
+                    "type": "text",
+                    "text": prompt
+                },
+                {
+                    "type": "image_url",
+                    "image_url": {
+                        "url": format!("data:image/png;base64,{b64}"),
+                        "detail": "high"
                     }
-                };
-
-                let description = json["choices"][0]["message"]["content"]
-                    .as_str()
-                    .unwrap_or("No description returned")
-                    .to_string();
-
-                info!("[Screenshot] Vision analysis complete");
-                Ok(ToolOutput::llm_only(format!(
-                    "SCREEN CONTENT:\n{description}"
-                )))
-            }
-            Err(e) => Ok(ToolOutput::error(format!("Vision API request failed: {e}"))),
-        }
+                }
+            ]
+        }]
+    });
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post("https://api.openai.com/v1/chat/completions")
+        .header("Authorization", format!("Bearer {api_key}"))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Vision API request failed: {e}"))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("Vision API error ({status}): {text}"));
     }
+
+    let json: Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse vision response: {e}"))?;
+
+    info!("[Screenshot] Vision analysis complete");
+    Ok(json["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or("No description returned")
+        .to_string())
 }
@@ -0,0 +1,1392 @@
+//! WebDriver BiDi client and the Firefox backend built on it.
+//!
+//! Firefox started with `--remote-debugging-port` serves WebDriver BiDi at
+//! `ws://127.0.0.1:<port>/session`. A [`BidiConn`] opens one session there,
+//! matches command responses to their ids and fans protocol events out to
+//! subscribers:
+//!
+//! ```text
+//! → {"id":7,"method":"browsingContext.navigate","params":{"context":"a1b2…","url":"https://example.com","wait":"complete"}}
+//! ← {"type":"success","id":7,"result":{"navigation":"c3d4…","url":"https://example.com/"}}
+//! ← {"type":"event","method":"log.entryAdded","params":{"type":"console","level":"error",…}}
+//! ```
+//!
+//! Next comes protocol plumbing shared by the backend and the tests:
+//! finding and launching Firefox with a profile that saves downloads
+//! without asking, input action sequences, and turning BiDi `RemoteValue`s
+//! into plain JSON. Last is the backend, `Firefox`, which files the
+//! session's events under their tabs and runs the Firefox side of each
+//! action. Actions made of page functions and input go through
+//! `web_page::PageDriver`, the same code the CDP backend runs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+use super::web_dialog::{self, DialogPolicy};
+use super::web_files::{self, DownloadLog};
+use super::web_page::{
+    self, cookie_domain_arg, describe_files, files_arg, frame_arg, page_result, tab_arg, timeout_arg,
+    wait_until_arg, FrameInfo, PageDriver, PageLogs, DOM_JS, JS_BOX, JS_STORAGE, MAX_CAPTURE_HEIGHT,
+};
+use super::web_session;
+use super::web_snapshot;
+
+/// Environment variable with the Firefox binary to launch.
+pub const FIREFOX_ENV: &str = "ZEPTOBOT_FIREFOX";
+
+/// Remote agent port of the dedicated Firefox (Chrome's DevTools keeps 9222).
+pub const PORT: u16 = 9223;
+
+/// How long a launched Firefox gets to open its remote agent. A fresh
+/// profile makes the first start slower than Chrome's.
+const FIREFOX_STARTUP: Duration = Duration::from_secs(30);
+
+/// How long a command may take before the backend gives up on it.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Events buffered per subscriber before the oldest are dropped.
+const EVENT_BUFFER: usize = 1024;
+
+/// Largest response body kept for `browser_wait_for_request`.
+pub const MAX_BODY_BYTES: u64 = 10 * 1024 * 1024;
+
+/// The Firefox binary to launch: [`FIREFOX_ENV`], then the macOS app
+/// bundles, then Firefox on the `PATH` (Linux).
+pub fn find_firefox() -> Option<PathBuf> {
+    if let Some(bin) = std::env::var_os(FIREFOX_ENV).filter(|b| !b.is_empty()) {
+        return Some(bin.into());
+    }
+    let apps = [
+        "/Applications/Firefox.app/Contents/MacOS/firefox",
+        "/Applications/Firefox Developer Edition.app/Contents/MacOS/firefox",
+        "/Applications/Firefox Nightly.app/Contents/MacOS/firefox",
+    ];
+    if let Some(app) = apps.iter().map(PathBuf::from).find(|p| p.exists()) {
+        return Some(app);
+    }
+    let names = ["firefox", "firefox-esr", "firefox-developer-edition", "firefox-nightly"];
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs: Vec<PathBuf> = std::env::split_paths(&path)
+        .chain(["/usr/bin", "/snap/bin"].map(PathBuf::from))
+        .collect();
+    names
+        .iter()
+        .flat_map(|name| dirs.iter().map(move |dir| dir.join(name)))
+        .find(|p| p.is_file())
+}
+
+/// Write the profile's `user.js`: downloads go straight to `download_dir`
+/// without a dialog or the downloads panel.
+pub fn write_prefs(profile: &Path, download_dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(profile)?;
+    std::fs::create_dir_all(download_dir)?;
+    let prefs = [
+        ("browser.download.dir", json!(download_dir.to_string_lossy())),
+        ("browser.download.folderList", json!(2)),
+        ("browser.download.useDownloadDir", json!(true)),
+        ("browser.download.always_ask_before_handling_new_types", json!(false)),
+        ("browser.download.start_downloads_in_tmp_dir", json!(false)),
+        ("browser.download.alwaysOpenPanel", json!(false)),
+        ("browser.shell.checkDefaultBrowser", json!(false)),
+    ];
+    let text: String = prefs
+        .iter()
+        .map(|(name, value)| format!("user_pref(\"{name}\", {value});\n"))
+        .collect();
+    std::fs::write(profile.join("user.js"), text)
+}
+
+/// Start Firefox on `profile` with its remote agent on `port`.
+pub fn launch(bin: &Path, profile: &Path, port: u16, headless: bool) -> std::io::Result<std::process::Child> {
+    let mut cmd = std::process::Command::new(bin);
+    cmd.arg(format!("--remote-debugging-port={port}"))
+        .arg("--profile")
+        .arg(profile)
+        .arg("--no-remote");
+    if headless {
+        cmd.arg("--headless");
+    }
+    cmd.stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+}
+
+/// Poll until the remote agent of a just-launched Firefox accepts
+/// connections on `port`, failing early if Firefox exits.
+pub async fn wait_ready(child: &mut std::process::Child, port: u16, timeout: Duration) -> Result<(), String> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return Ok(());
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!(
+                "Firefox exited ({status}) before its remote agent came up — is another Firefox \
+                 already using the profile?"
+            ));
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!(
+                "Firefox's remote agent didn't come up within {}s",
+                timeout.as_secs()
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// A protocol event: its method (`log.entryAdded`, ...) and params.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub method: String,
+    pub params: Value,
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// One WebDriver BiDi session over a WebSocket.
+pub struct BidiConn {
+    tx: mpsc::UnboundedSender<String>,
+    pending: Pending,
+    next_id: AtomicU64,
+    events: broadcast::Sender<Event>,
+    reader: tokio::task::JoinHandle<()>,
+    writer: tokio::task::JoinHandle<()>,
+}
+
+impl BidiConn {
+    /// Connect to `url` and start a session that leaves JavaScript dialogs
+    /// open for the backend to handle.
+    pub async fn connect(url: &str) -> Result<Self, String> {
+        let (ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|e| format!("BiDi connect to {url} failed: {e}"))?;
+        let (mut write, mut read) = ws.split();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                if write.send(Message::Text(msg.into())).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        let reader = {
+            let pending = pending.clone();
+            let events = events.clone();
+            tokio::spawn(async move {
+                while let Some(Ok(msg)) = read.next().await {
+                    let Message::Text(text) = msg else { continue };
+                    let text: String = text.into();
+                    let Ok(val) = serde_json::from_str::<Value>(&text) else {
+                        continue;
+                    };
+                    match val.get("type").and_then(Value::as_str) {
+                        Some("event") => {
+                            let _ = events.send(Event {
+                                method: val["method"].as_str().unwrap_or_default().to_string(),
+                                params: val["params"].clone(),
+                            });
+                        }
+                        Some(kind) => {
+                            let Some(id) = val.get("id").and_then(Value::as_u64) else {
+                                continue;
+                            };
+                            let Some(sender) = pending.lock().await.remove(&id) else {
+                                continue;
+                            };
+                            let _ = sender.send(if kind == "success" {
+                                Ok(val["result"].clone())
+                            } else {
+                                let s = |k: &str| val.get(k).and_then(Value::as_str).unwrap_or("");
+                                Err(if s("message").is_empty() { s("error").to_string() } else { s("message").to_string() })
+                            });
+                        }
+                        None => {}
+                    }
+                }
+                // Fail whatever is still waiting instead of letting it time out.
+                for (_, sender) in pending.lock().await.drain() {
+                    let _ = sender.send(Err("Firefox closed the connection".into()));
+                }
+            })
+        };
+
+        let conn = Self {
+            tx,
+            pending,
+            next_id: AtomicU64::new(1),
+            events,
+            reader,
+            writer,
+        };
+        let capabilities = json!({
+            "capabilities": { "alwaysMatch": { "unhandledPromptBehavior": { "default": "ignore" } } }
+        });
+        conn.send("session.new", capabilities).await?;
+        Ok(conn)
+    }
+
+    /// Send a command and wait for its result.
+    pub async fn send(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (resp_tx, resp_rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, resp_tx);
+        let cmd = json!({ "id": id, "method": method, "params": params });
+        if self.tx.send(cmd.to_string()).is_err() {
+            self.pending.lock().await.remove(&id);
+            return Err("Firefox closed the connection".into());
+        }
+        match tokio::time::timeout(COMMAND_TIMEOUT, resp_rx).await {
+            Ok(Ok(result)) => result.map_err(|e| format!("{method}: {e}")),
+            Ok(Err(_)) => Err(format!("{method}: response channel closed")),
+            Err(_) => {
+                self.pending.lock().await.remove(&id);
+                Err(format!("{method}: no answer within {}s", COMMAND_TIMEOUT.as_secs()))
+            }
+        }
+    }
+
+    /// Events from now on. Subscribe with `session.subscribe` for them to flow.
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Whether the WebSocket is still up.
+    pub fn is_open(&self) -> bool {
+        !self.reader.is_finished()
+    }
+}
+
+impl Drop for BidiConn {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.writer.abort();
+    }
+}
+
+/// WebDriver key values for named keys (the same names as the CDP backend).
+const NAMED_KEYS: &[(&str, &str)] = &[
+    ("Enter", "\u{E007}"),
+    ("Tab", "\u{E004}"),
+    ("Escape", "\u{E00C}"),
+    ("Backspace", "\u{E003}"),
+    ("Delete", "\u{E017}"),
+    ("Space", " "),
+    ("ArrowUp", "\u{E013}"),
+    ("ArrowDown", "\u{E015}"),
+    ("ArrowLeft", "\u{E012}"),
+    ("ArrowRight", "\u{E014}"),
+    ("Home", "\u{E011}"),
+    ("End", "\u{E010}"),
+    ("PageUp", "\u{E00E}"),
+    ("PageDown", "\u{E00F}"),
+];
+
+fn modifier_key(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "alt" | "option" => Some("\u{E00A}"),
+        "control" | "ctrl" => Some("\u{E009}"),
+        "meta" | "cmd" | "command" => Some("\u{E03D}"),
+        "shift" => Some("\u{E008}"),
+        _ => None,
+    }
+}
+
+fn key_source(actions: Vec<Value>) -> Value {
+    json!([{ "type": "key", "id": "keyboard", "actions": actions }])
+}
+
+/// `input.performActions` actions for a key combo like `Enter`, `a`,
+/// `Shift+Tab` or `Control+A`.
+pub fn key_actions(combo: &str) -> Result<Value, String> {
+    let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let (key, mods) = match parts.split_last() {
+        Some((key, mods)) if !key.is_empty() => (*key, mods),
+        // "Control++" and "+" mean the plus key itself.
+        _ if combo.ends_with('+') => ("+", &parts[..parts.len().saturating_sub(2)]),
+        _ => return Err(format!("Invalid key '{combo}'")),
+    };
+    let mods: Vec<&str> = mods
+        .iter()
+        .map(|m| modifier_key(m).ok_or_else(|| format!("Unknown modifier '{m}' in '{combo}'")))
+        .collect::<Result<_, _>>()?;
+    let value = match NAMED_KEYS.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)) {
+        Some((_, v)) => v.to_string(),
+        None => {
+            let mut chars = key.chars();
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                return Err(format!(
+                    "Unknown key '{key}' — use a single character or one of: {}",
+                    NAMED_KEYS.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(", ")
+                ));
+            };
+            if mods.contains(&"\u{E008}") { ch.to_ascii_uppercase() } else { ch }.to_string()
+        }
+    };
+    let mut actions: Vec<Value> = mods.iter().map(|m| json!({ "type": "keyDown", "value": m })).collect();
+    actions.push(json!({ "type": "keyDown", "value": value }));
+    actions.push(json!({ "type": "keyUp", "value": value }));
+    actions.extend(mods.iter().rev().map(|m| json!({ "type": "keyUp", "value": m })));
+    Ok(key_source(actions))
+}
+
+/// Actions that type `text` one character at a time, newlines as Enter.
+pub fn text_actions(text: &str) -> Value {
+    let actions = text
+        .chars()
+        .filter(|ch| *ch != '\r')
+        .flat_map(|ch| {
+            let value = if ch == '\n' { "\u{E007}".to_string() } else { ch.to_string() };
+            [
+                json!({ "type": "keyDown", "value": value }),
+                json!({ "type": "keyUp", "value": value }),
+            ]
+        })
+        .collect();
+    key_source(actions)
+}
+
+fn pointer_source(actions: Vec<Value>) -> Value {
+    json!([{
+        "type": "pointer",
+        "id": "mouse",
+        "parameters": { "pointerType": "mouse" },
+        "actions": actions,
+    }])
+}
+
+/// Move, press and release the left button at viewport point `(x, y)`.
+pub fn click_actions(x: f64, y: f64) -> Value {
+    pointer_source(vec![
+        json!({ "type": "pointerMove", "x": x.round() as i64, "y": y.round() as i64 }),
+        json!({ "type": "pointerDown", "button": 0 }),
+        json!({ "type": "pointerUp", "button": 0 }),
+    ])
+}
+
+/// Move the mouse to viewport point `(x, y)` without pressing, for hover.
+pub fn hover_actions(x: f64, y: f64) -> Value {
+    pointer_source(vec![json!({ "type": "pointerMove", "x": x.round() as i64, "y": y.round() as i64 })])
+}
+
+/// The `RemoteValue` of a `script.evaluate` / `script.callFunction` result,
+/// or the exception it threw.
+pub fn script_result(res: &Value) -> Result<&Value, String> {
+    if res.get("type").and_then(Value::as_str) == Some("exception") {
+        let details = &res["exceptionDetails"];
+        let text = details["text"].as_str().unwrap_or("exception");
+        return Err(format!("js: {text}"));
+    }
+    Ok(&res["result"])
+}
+
+/// A BiDi `RemoteValue` as plain JSON: primitives as themselves, arrays and
+/// plain objects recursively, anything else (nodes, functions, ...) by type.
+pub fn remote_json(value: &Value) -> Value {
+    let inner = &value["value"];
+    match value.get("type").and_then(Value::as_str).unwrap_or("") {
+        "undefined" | "null" => Value::Null,
+        "string" | "boolean" => inner.clone(),
+        // Special numbers (NaN, -0, Infinity) arrive as strings.
+        "number" | "bigint" => match inner {
+            Value::String(s) => s.parse::<f64>().map(|n| json!(n)).unwrap_or_else(|_| json!(s)),
+            other => other.clone(),
+        },
+        "array" | "set" => Value::Array(inner.as_array().into_iter().flatten().map(remote_json).collect()),
+        "object" | "map" => Value::Object(
+            inner
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|pair| {
+                    let key = match &pair[0] {
+                        Value::String(k) => k.clone(),
+                        other => remote_json(other).to_string(),
+                    };
+                    Some((key, remote_json(pair.get(1)?)))
+                })
+                .collect(),
+        ),
+        "date" | "regexp" => inner.clone(),
+        other => json!(format!("[{other}]")),
+    }
+}
+
+/// `network.beforeRequestSent`'s request destination as a CDP-style
+/// resource type, so the shared network log treats both backends alike.
+pub fn resource_type(request: &Value) -> &'static str {
+    match request.get("destination").and_then(Value::as_str).unwrap_or("") {
+        "document" | "iframe" | "frame" => "Document",
+        "script" | "worker" | "sharedworker" | "serviceworker" => "Script",
+        "style" => "Stylesheet",
+        "image" => "Image",
+        "font" => "Font",
+        "audio" | "video" | "track" => "Media",
+        "" => match request.get("initiatorType").and_then(Value::as_str) {
+            Some("xmlhttprequest") => "XHR",
+            Some("other") if request["url"].as_str().is_some_and(|u| u.starts_with("ws")) => "WebSocket",
+            _ => "Fetch",
+        },
+        _ => "Other",
+    }
+}
+
+// ===========================================================================
+// The backend
+// ===========================================================================
+
+/// Where BiDi page functions run: a tab and one of its frames, both
+/// browsing contexts. Input actions go to the frame's own context, in its
+/// viewport's coordinates.
+pub(super) struct BidiTarget {
+    tab: String,
+    context: String,
+    /// The frame viewport's origin in the tab's viewport, for screenshot
+    /// clips; `None` when a cross-origin parent hides it.
+    offset: Option<(f64, f64)>,
+}
+
+struct FirefoxConn {
+    conn: Arc<BidiConn>,
+    /// `network.addDataCollector` id keeping response bodies, if supported.
+    collector: Option<String>,
+    _events: tokio::task::JoinHandle<()>,
+}
+
+/// Events every Firefox session subscribes to.
+const BIDI_EVENTS: [&str; 8] = [
+    "browsingContext.contextCreated",
+    "browsingContext.contextDestroyed",
+    "browsingContext.userPromptOpened",
+    "browsingContext.userPromptClosed",
+    "log.entryAdded",
+    "network.beforeRequestSent",
+    "network.responseCompleted",
+    "network.fetchError",
+];
+
+/// The logs of Firefox tab `tab`, created on first use.
+async fn page_logs(logs: &Mutex<HashMap<String, Arc<PageLogs>>>, tab: &str) -> Arc<PageLogs> {
+    logs.lock().await.entry(tab.to_string()).or_default().clone()
+}
+
+/// The Firefox backend: one BiDi session and what it has seen.
+pub(super) struct Firefox {
+    session: Mutex<Option<FirefoxConn>>,
+    /// Context id of the tab selected via `browser_switch_tab` / `browser_new_tab`.
+    selected: Mutex<Option<String>>,
+    /// Network activity, console output and dialogs per tab (top-level context id).
+    logs: Arc<Mutex<HashMap<String, Arc<PageLogs>>>>,
+    /// Downloads of the whole browser, fed by `browsingContext.download*` events.
+    downloads: Arc<Mutex<DownloadLog>>,
+    /// What to do with JavaScript dialogs, shared with the other backends.
+    policy: Arc<Mutex<DialogPolicy>>,
+}
+
+impl Firefox {
+    pub(super) fn new(policy: Arc<Mutex<DialogPolicy>>) -> Self {
+        Self {
+            session: Mutex::new(None),
+            selected: Mutex::new(None),
+            logs: Arc::new(Mutex::new(HashMap::new())),
+            downloads: Arc::new(Mutex::new(DownloadLog::default())),
+            policy,
+        }
+    }
+
+    /// Attach to the remote agent at `url`. Returns the tab count.
+    pub(super) async fn connect(&self, url: &str) -> Result<usize, String> {
+        let conn = self.connect_to(url).await?;
+        Ok(self.tabs(&conn).await?.len())
+    }
+
+    /// Launch the dedicated Firefox and attach to it. Returns the tab count.
+    pub(super) async fn launch_and_connect(&self, headless: bool) -> Result<usize, String> {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+        let profile = PathBuf::from(format!("{home}/.zeptobot/firefox-profile"));
+        let downloads = web_files::FilesConfig::load()?.download_dir();
+        write_prefs(&profile, &downloads)
+            .map_err(|e| format!("Firefox profile {}: {e}", profile.display()))?;
+
+        let bin = find_firefox().ok_or(
+            "Firefox not found — install Firefox, or set ZEPTOBOT_FIREFOX to its binary",
+        )?;
+        info!("[BiDi] Launching dedicated Firefox at {} (headless: {headless})", bin.display());
+        let mut child = launch(&bin, &profile, PORT, headless)
+            .map_err(|e| format!("Firefox launch failed: {e}"))?;
+        wait_ready(&mut child, PORT, FIREFOX_STARTUP).await?;
+        let conn = self.conn().await?;
+        Ok(self.tabs(&conn).await?.len())
+    }
+
+    /// The session, attaching to the dedicated Firefox's port if there is none.
+    async fn conn(&self) -> Result<Arc<BidiConn>, String> {
+        self.connect_to(&format!("ws://127.0.0.1:{PORT}/session")).await
+    }
+
+    async fn connect_to(&self, url: &str) -> Result<Arc<BidiConn>, String> {
+        let mut guard = self.session.lock().await;
+        if let Some(firefox) = guard.as_ref().filter(|f| f.conn.is_open()) {
+            return Ok(firefox.conn.clone());
+        }
+
+        info!("[BiDi] Connecting to Firefox at {url}...");
+        let conn = Arc::new(BidiConn::connect(url).await?);
+        let events = conn.subscribe();
+        conn.send("session.subscribe", json!({ "events": BIDI_EVENTS })).await?;
+        if let Err(e) = self.track_downloads(&conn).await {
+            warn!("[BiDi] Download tracking unavailable: {e}");
+        }
+        let keep_bodies = json!({
+            "dataTypes": ["response"],
+            "maxEncodedDataSize": MAX_BODY_BYTES,
+        });
+        let collector = match conn.send("network.addDataCollector", keep_bodies).await {
+            Ok(res) => res["collector"].as_str().map(String::from),
+            Err(e) => {
+                warn!("[BiDi] Response bodies unavailable: {e}");
+                None
+            }
+        };
+        // dom.js goes into each new document once, so page calls carry only
+        // their own function. Documents already open get it on first use.
+        let preload = json!({ "functionDeclaration": format!("() => {{\n{DOM_JS}\n}}") });
+        if let Err(e) = conn.send("script.addPreloadScript", preload).await {
+            warn!("[BiDi] dom.js preload unavailable: {e}");
+        }
+
+        let mut watcher = BidiWatcher {
+            conn: Arc::downgrade(&conn),
+            parents: HashMap::new(),
+            logs: self.logs.clone(),
+            downloads: self.downloads.clone(),
+            policy: self.policy.clone(),
+        };
+        let tree = conn.send("browsingContext.getTree", json!({})).await?;
+        watcher.add_contexts(&tree["contexts"], None);
+        let events = tokio::spawn(watcher.run(events));
+        *guard = Some(FirefoxConn { conn: conn.clone(), collector, _events: events });
+        info!("[BiDi] Connected");
+        Ok(conn)
+    }
+
+    /// Save downloads to the configured directory and log them as they finish.
+    async fn track_downloads(&self, conn: &BidiConn) -> Result<(), String> {
+        let dir = web_files::FilesConfig::load()?.download_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        let behavior = json!({
+            "downloadBehavior": { "type": "allowed", "destinationFolder": dir.to_string_lossy() }
+        });
+        // Older Firefox only has the profile's download prefs (write_prefs).
+        if let Err(e) = conn.send("browser.setDownloadBehavior", behavior).await {
+            info!("[BiDi] Downloads go to the profile's folder: {e}");
+        }
+        let events = ["browsingContext.downloadWillBegin", "browsingContext.downloadEnd"];
+        conn.send("session.subscribe", json!({ "events": events })).await?;
+        Ok(())
+    }
+
+    /// The open tabs (top-level browsing contexts), oldest first.
+    async fn tabs(&self, conn: &BidiConn) -> Result<Vec<Value>, String> {
+        let tree = conn.send("browsingContext.getTree", json!({ "maxDepth": 0 })).await?;
+        Ok(tree["contexts"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Resolve the tab to act on (see [`web_page::pick_tab`]), opening one
+    /// if there are no tabs.
+    pub(super) async fn page(&self, params: &Value) -> Result<String, String> {
+        let conn = self.conn().await?;
+        let tabs = self.tabs(&conn).await?;
+        let mut ids: Vec<String> =
+            tabs.iter().filter_map(|t| t["context"].as_str()).map(String::from).collect();
+        let selected = self.selected.lock().await.clone();
+        if let Some(i) = web_page::pick_tab(&ids, params, selected.as_deref())? {
+            return Ok(ids.swap_remove(i));
+        }
+        info!("[BiDi] No tabs, creating one...");
+        let created = conn.send("browsingContext.create", json!({ "type": "tab" })).await?;
+        created["context"].as_str().map(String::from).ok_or("browsingContext.create: no context".into())
+    }
+
+    /// The logs of tab `tab`.
+    pub(super) async fn tab_logs(&self, tab: &str) -> Arc<PageLogs> {
+        page_logs(&self.logs, tab).await
+    }
+
+    /// The tab's URL and title. A tab blocked by a dialog keeps its title to itself.
+    async fn tab_info(&self, tab: &str) -> (String, String) {
+        let url = match self.conn().await {
+            Ok(conn) => conn
+                .send("browsingContext.getTree", json!({ "root": tab, "maxDepth": 0 }))
+                .await
+                .ok()
+                .and_then(|tree| tree["contexts"][0]["url"].as_str().map(String::from))
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        let title = tokio::time::timeout(
+            Duration::from_secs(3),
+            self.eval(tab, "document.title"),
+        )
+        .await;
+        let title = match title {
+            Ok(Ok(Value::String(t))) => t,
+            _ => String::new(),
+        };
+        (url, title)
+    }
+
+    /// Wait until the tab's document has loaded, and with `left`, until the
+    /// tab is off that URL (for a moment only: history entries can share one).
+    async fn settle(&self, tab: &str, left: Option<&str>, deadline: tokio::time::Instant) {
+        let moved_by = tokio::time::Instant::now() + Duration::from_secs(2);
+        loop {
+            let ready = self.eval(tab, "document.readyState").await.ok();
+            let moved = match left {
+                Some(old) => tokio::time::Instant::now() >= moved_by || self.tab_info(tab).await.0 != old,
+                None => true,
+            };
+            if (moved && ready.as_ref().and_then(Value::as_str) == Some("complete"))
+                || tokio::time::Instant::now() >= deadline
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Frames of a tab, depth-first in document order (the tab itself first).
+    async fn tab_frames(&self, tab: &str) -> Result<Vec<FrameInfo>, String> {
+        fn flatten(contexts: &Value, out: &mut Vec<FrameInfo>) {
+            for ctx in contexts.as_array().into_iter().flatten() {
+                out.push(FrameInfo {
+                    id: ctx["context"].as_str().unwrap_or_default().to_string(),
+                    name: String::new(),
+                    url: ctx["url"].as_str().unwrap_or_default().to_string(),
+                });
+                flatten(&ctx["children"], out);
+            }
+        }
+        let conn = self.conn().await?;
+        let tree = conn.send("browsingContext.getTree", json!({ "root": tab })).await?;
+        let mut frames = Vec::new();
+        flatten(&tree["contexts"], &mut frames);
+        // BiDi doesn't report frame names; each frame knows its own.
+        for frame in frames.iter_mut().skip(1) {
+            if let Ok(Value::String(name)) = self.eval(&frame.id, "window.name").await {
+                frame.name = name;
+            }
+        }
+        Ok(frames)
+    }
+
+    /// Evaluate `expression` in a browsing context, returning its value as JSON.
+    async fn eval(&self, context: &str, expression: &str) -> Result<Value, String> {
+        let conn = self.conn().await?;
+        let eval = json!({
+            "expression": expression,
+            "target": { "context": context },
+            "awaitPromise": true,
+            "resultOwnership": "none",
+        });
+        let res = conn.send("script.evaluate", eval).await.map_err(|e| format!("js: {e}"))?;
+        script_result(&res).map(remote_json)
+    }
+
+    /// [`PageDriver::call`] for a function returning an element: its shared
+    /// id, for commands that take one (`input.setFiles`).
+    async fn call_node(&self, target: &BidiTarget, function: &str, args: &[Value]) -> Result<String, String> {
+        let call = json!({
+            "functionDeclaration": format!(
+                "function(args) {{\nif (!window.__zb) return false;\n\
+                 return ({function}).apply(this, JSON.parse(args));\n}}"
+            ),
+            "arguments": [{ "type": "string", "value": Value::from(args.to_vec()).to_string() }],
+            "target": { "context": target.context },
+            "awaitPromise": true,
+            "resultOwnership": "none",
+            "serializationOptions": { "maxDomDepth": 0 },
+        });
+        self.call_page(&target.context, call)
+            .await?
+            .get("sharedId")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .ok_or("No element found".into())
+    }
+
+    /// `script.callFunction` with `call`, whose function returns `false`
+    /// while the document lacks `window.__zb`. That only happens in
+    /// documents open before the preload script was added: dom.js is
+    /// evaluated there once and the call retried.
+    async fn call_page(&self, context: &str, call: Value) -> Result<Value, String> {
+        let conn = self.conn().await?;
+        let res = conn.send("script.callFunction", call.clone()).await.map_err(|e| format!("js: {e}"))?;
+        let result = script_result(&res)?;
+        if !(result["type"] == "boolean" && result["value"] == false) {
+            return Ok(result.clone());
+        }
+        let install = json!({ "expression": DOM_JS, "target": { "context": context }, "awaitPromise": false });
+        conn.send("script.evaluate", install).await.map_err(|e| format!("js: {e}"))?;
+        let res = conn.send("script.callFunction", call).await.map_err(|e| format!("js: {e}"))?;
+        script_result(&res).cloned()
+    }
+
+    /// Dispatch `input.performActions` sources in a browsing context.
+    async fn perform(&self, context: &str, actions: Value) -> Result<(), String> {
+        let conn = self.conn().await?;
+        conn.send("input.performActions", json!({ "context": context, "actions": actions })).await?;
+        Ok(())
+    }
+
+    /// Every cookie in the browser, in [`web_session`]'s shape.
+    async fn cookies(&self) -> Result<Vec<Value>, String> {
+        let conn = self.conn().await?;
+        let res = conn.send("storage.getCookies", json!({})).await?;
+        Ok(res["cookies"].as_array().into_iter().flatten().map(web_session::from_bidi).collect())
+    }
+
+    /// The body of a finished request as text, if Firefox kept it.
+    pub(super) async fn response_body(&self, request_id: &str) -> Result<String, String> {
+        let conn = self.conn().await?;
+        let collector = self.session.lock().await.as_ref().and_then(|f| f.collector.clone())
+            .ok_or("Response body unavailable: this Firefox doesn't keep response bodies")?;
+        let data = conn
+            .send(
+                "network.getData",
+                json!({ "dataType": "response", "request": request_id, "collector": collector }),
+            )
+            .await
+            .map_err(|e| format!("Response body unavailable: {e}"))?;
+        let bytes = &data["bytes"];
+        let value = bytes["value"].as_str().unwrap_or_default();
+        if bytes["type"] != "base64" {
+            return Ok(value.to_string());
+        }
+        let raw = base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|e| format!("Response body: {e}"))?;
+        String::from_utf8(raw).map_err(|e| format!("Binary response body ({} bytes)", e.as_bytes().len()))
+    }
+
+    /// Downloads of the whole browser.
+    pub(super) fn downloads(&self) -> &Mutex<DownloadLog> {
+        &self.downloads
+    }
+
+    /// `browser_console` for the tab `params` picks, from `since` on.
+    pub(super) async fn console(&self, params: &Value, since: u64) -> Result<String, String> {
+        let tab = self.page(params).await?;
+        let logs = self.tab_logs(&tab).await;
+        // Entries arrive as events: a round trip through the page lets
+        // the ones its last action logged come in first.
+        if since > 0 && logs.dialog.lock().await.is_none() {
+            let _ = tokio::time::timeout(Duration::from_secs(1), self.eval(&tab, "0")).await;
+        }
+        Ok(web_page::console(&logs, params, since).await)
+    }
+
+    /// Run an action with the same results as the CDP backend; what isn't
+    /// protocol-specific goes to [`web_page::run`].
+    pub(super) async fn run(&self, action: &str, mut params: Value) -> Result<String, String> {
+        web_page::resolve_args(self, action, &mut params).await?;
+        let conn = self.conn().await?;
+        match action {
+            "get_tabs" => {
+                let current = self.page(&json!({})).await.ok();
+                let mut tabs = Vec::new();
+                for tab in self.tabs(&conn).await? {
+                    let id = tab["context"].as_str().unwrap_or_default();
+                    let (url, title) = self.tab_info(id).await;
+                    tabs.push(json!({
+                        "title": title,
+                        "url": url,
+                        "active": current.as_deref() == Some(id),
+                        "id": id,
+                    }));
+                }
+                Ok(serde_json::to_string(&tabs).unwrap_or_default())
+            }
+            "switch_tab" => {
+                if tab_arg(&params).is_none() {
+                    return Err("Missing 'tab'".into());
+                }
+                let tab = self.page(&params).await?;
+                conn.send("browsingContext.activate", json!({ "context": tab })).await?;
+                *self.selected.lock().await = Some(tab.clone());
+                let (_, title) = self.tab_info(&tab).await;
+                Ok(format!("Switched to tab {tab} — title: \"{title}\""))
+            }
+            "new_tab" => {
+                let url = params.get("url").and_then(Value::as_str).unwrap_or("about:blank");
+                let created = conn.send("browsingContext.create", json!({ "type": "tab" })).await?;
+                let tab = created["context"].as_str().ok_or("browsingContext.create: no context")?.to_string();
+                if url != "about:blank" {
+                    conn.send(
+                        "browsingContext.navigate",
+                        json!({ "context": tab, "url": url, "wait": "complete" }),
+                    )
+                    .await?;
+                }
+                *self.selected.lock().await = Some(tab.clone());
+                Ok(format!("Opened new tab {tab} at {url}"))
+            }
+            "close_tab" => {
+                let tab = self.page(&params).await?;
+                conn.send("browsingContext.close", json!({ "context": tab })).await?;
+                self.logs.lock().await.remove(&tab);
+                let mut current = self.selected.lock().await;
+                if current.as_deref() == Some(tab.as_str()) {
+                    *current = None;
+                }
+                Ok(format!("Closed tab {tab}"))
+            }
+            "navigate" => {
+                let url = params.get("url").and_then(Value::as_str)
+                    .ok_or("Missing 'url'")?;
+                let wait_until = wait_until_arg(&params)?;
+                let timeout = timeout_arg(&params, 15_000);
+                let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout);
+                let tab = self.page(&params).await?;
+                let logs = self.tab_logs(&tab).await;
+
+                let wait = if wait_until == "domcontentloaded" { "interactive" } else { "complete" };
+                let nav = conn.send(
+                    "browsingContext.navigate",
+                    json!({ "context": tab, "url": url, "wait": wait }),
+                );
+                let mut note = String::new();
+                match tokio::time::timeout_at(deadline, nav).await {
+                    Ok(Err(e)) => return Err(format!("navigate: {e}")),
+                    Err(_) => note = format!(" (still loading after {timeout}ms)"),
+                    Ok(Ok(_)) => {}
+                }
+                if wait_until == "networkidle" && note.is_empty() {
+                    if let Err(busy) = web_page::network_idle(&logs.network, deadline).await {
+                        note = format!(" ({busy} after {timeout}ms)");
+                    }
+                }
+                let (_, title) = self.tab_info(&tab).await;
+                Ok(format!("Navigated to {url} — title: \"{title}\"{note}"))
+            }
+            "screenshot" => {
+                let target = web_page::target(self, &params).await?;
+                let mut capture = json!({
+                    "context": target.tab,
+                    "origin": "viewport",
+                    "format": { "type": "image/png" },
+                });
+                if let Some(sel) = params.get("selector").and_then(Value::as_str) {
+                    let b = self.call(&target, JS_BOX, &[json!(sel)]).await?;
+                    if let Some(e) = b.get("error").and_then(Value::as_str) {
+                        return Err(e.to_string());
+                    }
+                    let (dx, dy) = target.offset.ok_or(
+                        "Can't place the element: its frame sits in a cross-origin frame — \
+                         screenshot the whole tab instead",
+                    )?;
+                    let n = |k: &str| b.get(k).and_then(Value::as_f64).unwrap_or(0.0);
+                    capture["clip"] = json!({
+                        "type": "box",
+                        "x": dx + n("x"),
+                        "y": dy + n("y"),
+                        "width": n("width"),
+                        "height": n("height"),
+                    });
+                } else if params.get("full_page").and_then(Value::as_bool) == Some(true) {
+                    let size = self
+                        .eval(
+                            &target.tab,
+                            "[document.documentElement.scrollWidth, document.documentElement.scrollHeight]",
+                        )
+                        .await?;
+                    capture["origin"] = json!("document");
+                    capture["clip"] = json!({
+                        "type": "box",
+                        "x": 0,
+                        "y": 0,
+                        "width": size[0].as_f64().unwrap_or(0.0),
+                        "height": size[1].as_f64().unwrap_or(0.0).min(MAX_CAPTURE_HEIGHT),
+                    });
+                }
+                let shot = conn.send("browsingContext.captureScreenshot", capture).await?;
+                let data = shot["data"].as_str().unwrap_or_default();
+                Ok(json!({ "data": data }).to_string())
+            }
+            "upload" => {
+                let target = web_page::target(self, &params).await?;
+                let sel = params.get("selector").and_then(Value::as_str)
+                    .ok_or("Provide 'ref', 'selector', 'label' or 'text'")?;
+                let files = files_arg(&params)?;
+                let input = self
+                    .call_node(&target, "(sel) => window.__zb.fileInput(sel)", &[json!(sel)])
+                    .await?;
+                conn.send(
+                    "input.setFiles",
+                    json!({ "context": target.context, "element": { "sharedId": input }, "files": files }),
+                )
+                .await?;
+                Ok(format!("Attached {}", describe_files(&files)))
+            }
+            "go_back" | "go_forward" => {
+                let tab = self.page(&params).await?;
+                let (from, _) = self.tab_info(&tab).await;
+                let step = if action == "go_back" { -1 } else { 1 };
+                conn.send("browsingContext.traverseHistory", json!({ "context": tab, "delta": step }))
+                    .await
+                    .map_err(|e| match e.contains("no such history entry") {
+                        true if step < 0 => "No previous page in this tab's history".to_string(),
+                        true => "No next page in this tab's history".to_string(),
+                        false => e,
+                    })?;
+                // Back/forward cache restores don't fire a fresh load; don't wait forever.
+                let deadline = tokio::time::Instant::now() + Duration::from_secs(15);
+                self.settle(&tab, Some(&from), deadline).await;
+                let (url, title) = self.tab_info(&tab).await;
+                Ok(format!("Went {} to {url} — title: \"{title}\"",
+                    if step < 0 { "back" } else { "forward" }))
+            }
+            "reload" => {
+                let tab = self.page(&params).await?;
+                let reload = conn.send("browsingContext.reload", json!({ "context": tab, "wait": "complete" }));
+                tokio::time::timeout(Duration::from_secs(15), reload)
+                    .await
+                    .unwrap_or(Ok(Value::Null))
+                    .map_err(|e| format!("reload: {e}"))?;
+                let (url, title) = self.tab_info(&tab).await;
+                Ok(format!("Reloaded {url} — title: \"{title}\""))
+            }
+            "snapshot" => {
+                // No accessibility domain in BiDi: dom.js builds the same node shape.
+                let target = web_page::target(self, &params).await?;
+                let tree = self
+                    .call(
+                        &target,
+                        "(roles, max) => window.__zb.axTree(roles, max)",
+                        &[json!(web_snapshot::INTERACTIVE_ROLES), json!(web_snapshot::MAX_REFS)],
+                    )
+                    .await?;
+                Ok(tree.to_string())
+            }
+            "list_elements" => {
+                let target = web_page::target(self, &params).await?;
+                let filter = params.get("filter").and_then(Value::as_str).unwrap_or("");
+                let list = "(filter) => window.__zb.listElements(filter)";
+                if frame_arg(&params).is_some() {
+                    let elements = self.call(&target, list, &[json!(filter)]).await?;
+                    return Ok(elements.to_string());
+                }
+
+                // Whole tab: every frame, cross-origin ones included, tagged with its index.
+                let frames = self.tab_frames(&target.tab).await?;
+                let mut all = Vec::new();
+                for (i, frame) in frames.iter().enumerate() {
+                    let in_frame = BidiTarget {
+                        tab: target.tab.clone(),
+                        context: frame.id.clone(),
+                        offset: None,
+                    };
+                    let Ok(Value::Array(elements)) = self.call(&in_frame, list, &[json!(filter)]).await else {
+                        continue;
+                    };
+                    for mut el in elements {
+                        if i > 0 {
+                            el["frame"] = json!(i);
+                            el["frame_url"] = json!(frame.url);
+                        }
+                        all.push(el);
+                    }
+                }
+                Ok(Value::Array(all).to_string())
+            }
+            "execute_js" => {
+                let target = web_page::target(self, &params).await?;
+                let code = params.get("code").and_then(Value::as_str)
+                    .ok_or("Missing 'code'")?;
+                let eval = json!({
+                    "expression": code,
+                    "target": { "context": target.context },
+                    "awaitPromise": true,
+                    "resultOwnership": "none",
+                });
+                let res = conn.send("script.evaluate", eval).await.map_err(|e| format!("js: {e}"))?;
+                let result = script_result(&res)?;
+                if result["type"] == "undefined" {
+                    return Ok("undefined".into());
+                }
+                Ok(match remote_json(result) {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                })
+            }
+            "dialog" => {
+                let tab = self.page(&params).await?;
+                let logs = self.tab_logs(&tab).await;
+                let dialog = logs.dialog.lock().await.clone().ok_or("No dialog is open")?;
+                let accept = params.get("action").and_then(Value::as_str) != Some("dismiss");
+                let mut answer = json!({ "context": tab, "accept": accept });
+                if let Some(text) = params.get("prompt_text").and_then(Value::as_str) {
+                    answer["userText"] = json!(text);
+                }
+                conn.send("browsingContext.handleUserPrompt", answer).await?;
+                *logs.dialog.lock().await = None;
+                Ok(format!(
+                    "{} the {}",
+                    if accept { "Accepted" } else { "Dismissed" },
+                    web_dialog::describe(&dialog)
+                ))
+            }
+            "cookies" => {
+                let tab = self.page(&params).await?;
+                let (url, _) = self.tab_info(&tab).await;
+                let domain = cookie_domain_arg(&params, &url)?;
+                let name = params.get("name").and_then(Value::as_str);
+                let cookies: Vec<Value> = self.cookies().await?
+                    .into_iter()
+                    .filter(|c| web_session::cookie_matches(c, &domain, name))
+                    .collect();
+                if params.get("clear").and_then(Value::as_bool) == Some(true) {
+                    for cookie in &cookies {
+                        let filter = json!({
+                            "name": cookie["name"],
+                            "domain": cookie["domain"],
+                            "path": cookie["path"],
+                        });
+                        conn.send("storage.deleteCookies", json!({ "filter": filter })).await?;
+                    }
+                }
+                Ok(json!({ "domain": domain, "cookies": cookies }).to_string())
+            }
+            "export_session" => {
+                let cookies = self.cookies().await?;
+                // localStorage can only be read from a page: take it from every open site.
+                let mut local_storage = serde_json::Map::new();
+                for tab in self.tabs(&conn).await? {
+                    if web_session::host_of(tab["url"].as_str().unwrap_or_default()).is_none() {
+                        continue;
+                    }
+                    let target = BidiTarget {
+                        tab: tab["context"].as_str().unwrap_or_default().to_string(),
+                        context: tab["context"].as_str().unwrap_or_default().to_string(),
+                        offset: None,
+                    };
+                    let read = async {
+                        page_result(self.call(&target, JS_STORAGE, &[json!("local"), json!({})]).await?)
+                    };
+                    // A tab blocked by a dialog runs no script; leave it out.
+                    let Ok(Ok(raw)) = tokio::time::timeout(Duration::from_secs(3), read).await else {
+                        continue;
+                    };
+                    let Ok(found) = serde_json::from_str::<Value>(&raw) else { continue };
+                    if let (Some(origin), Some(items)) = (found["origin"].as_str(), found["items"].as_object()) {
+                        if !items.is_empty() {
+                            local_storage.insert(origin.to_string(), json!(items));
+                        }
+                    }
+                }
+                Ok(json!({ "cookies": cookies, "local_storage": local_storage }).to_string())
+            }
+            "import_session" => {
+                let bundle = params.get("bundle").ok_or("Missing 'bundle'")?;
+                let cookies: Vec<&Value> = bundle.get("cookies").and_then(Value::as_array).into_iter().flatten().collect();
+                for cookie in &cookies {
+                    conn.send("storage.setCookie", json!({ "cookie": web_session::to_bidi(cookie) }))
+                        .await
+                        .map_err(|e| format!("Bad cookie in the session bundle: {e}"))?;
+                }
+                // localStorage goes into an open tab of each origin.
+                let tabs = self.tabs(&conn).await?;
+                let mut not_open = Vec::new();
+                for (origin, items) in bundle.get("local_storage").and_then(Value::as_object).into_iter().flatten() {
+                    let Some(tab) = tabs.iter()
+                        .filter_map(|t| Some((t["context"].as_str()?, t["url"].as_str()?)))
+                        .find(|(_, url)| *url == origin || url.starts_with(&format!("{origin}/")))
+                        .map(|(id, _)| id.to_string())
+                    else {
+                        not_open.push(origin.clone());
+                        continue;
+                    };
+                    let target = BidiTarget { tab: tab.clone(), context: tab, offset: None };
+                    let op = json!({ "set": items });
+                    page_result(self.call(&target, JS_STORAGE, &[json!("local"), op]).await?)?;
+                }
+                Ok(json!({ "cookies": cookies.len(), "not_open": not_open }).to_string())
+            }
+            _ => web_page::run(self, action, &params).await,
+        }
+    }
+}
+
+#[async_trait]
+impl PageDriver for Firefox {
+    type Target = BidiTarget;
+
+    const NAME: &'static str = "BiDi";
+
+    async fn main_target(&self, params: &Value) -> Result<BidiTarget, String> {
+        let tab = self.page(params).await?;
+        Ok(BidiTarget { context: tab.clone(), tab, offset: Some((0.0, 0.0)) })
+    }
+
+    async fn frames(&self, main: &BidiTarget) -> Result<Vec<FrameInfo>, String> {
+        self.tab_frames(&main.tab).await
+    }
+
+    async fn frame_target(
+        &self,
+        main: BidiTarget,
+        frames: &[FrameInfo],
+        index: usize,
+    ) -> Result<BidiTarget, String> {
+        let mut target = BidiTarget { tab: main.tab, context: frames[index].id.clone(), offset: None };
+        let origin = self.call(&target, "() => window.__zb.frameOffset()", &[]).await?;
+        target.offset = origin.get("x").and_then(Value::as_f64).zip(origin.get("y").and_then(Value::as_f64));
+        Ok(target)
+    }
+
+    /// Arguments and the result cross as JSON text, so results keep the
+    /// shape the CDP backend sees.
+    async fn call(&self, target: &BidiTarget, function: &str, args: &[Value]) -> Result<Value, String> {
+        let call = json!({
+            "functionDeclaration": format!(
+                "async function(args) {{\nif (!window.__zb) return false;\n\
+                 const r = await ({function}).apply(this, JSON.parse(args));\n\
+                 return r === undefined ? null : JSON.stringify(r);\n}}"
+            ),
+            "arguments": [{ "type": "string", "value": Value::from(args.to_vec()).to_string() }],
+            "target": { "context": target.context },
+            "awaitPromise": true,
+            "resultOwnership": "none",
+        });
+        match self.call_page(&target.context, call).await?.get("value").and_then(Value::as_str) {
+            Some(text) => serde_json::from_str(text).map_err(|e| format!("js: {e}")),
+            None => Ok(Value::Null),
+        }
+    }
+
+    async fn logs(&self, target: &BidiTarget) -> Result<Arc<PageLogs>, String> {
+        Ok(self.tab_logs(&target.tab).await)
+    }
+
+    /// Input actions go to the frame's own context, in its viewport's coordinates.
+    fn input_origin(&self, _target: &BidiTarget) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+
+    async fn click(&self, target: &BidiTarget, x: f64, y: f64) -> Result<(), String> {
+        self.perform(&target.context, click_actions(x, y)).await
+    }
+
+    async fn hover(&self, target: &BidiTarget, x: f64, y: f64) -> Result<(), String> {
+        self.perform(&target.context, hover_actions(x, y)).await
+    }
+
+    async fn press(&self, target: &BidiTarget, combo: &str) -> Result<(), String> {
+        self.perform(&target.context, key_actions(combo)?).await
+    }
+
+    /// Key by key: BiDi has no way to insert text in one go.
+    async fn insert_text(&self, target: &BidiTarget, text: &str) -> Result<(), String> {
+        self.perform(&target.context, text_actions(text)).await
+    }
+}
+
+/// Files a Firefox session's events under the tab they belong to and
+/// answers dialogs by policy — what `BrowserState::cdp_track` does per CDP tab.
+struct BidiWatcher {
+    conn: Weak<BidiConn>,
+    /// Parent of every known browsing context (`None` for tabs).
+    parents: HashMap<String, Option<String>>,
+    logs: Arc<Mutex<HashMap<String, Arc<PageLogs>>>>,
+    downloads: Arc<Mutex<DownloadLog>>,
+    policy: Arc<Mutex<DialogPolicy>>,
+}
+
+impl BidiWatcher {
+    /// Record a `browsingContext.getTree` result.
+    fn add_contexts(&mut self, contexts: &Value, parent: Option<&str>) {
+        for ctx in contexts.as_array().into_iter().flatten() {
+            let Some(id) = ctx["context"].as_str() else { continue };
+            self.parents.insert(id.to_string(), parent.map(String::from));
+            self.add_contexts(&ctx["children"], Some(id));
+        }
+    }
+
+    /// The tab a browsing context is part of.
+    fn tab_of<'a>(&'a self, mut context: &'a str) -> &'a str {
+        while let Some(Some(parent)) = self.parents.get(context) {
+            context = parent;
+        }
+        context
+    }
+
+    /// Handle events until the session ends.
+    async fn run(mut self, mut events: broadcast::Receiver<Event>) {
+        loop {
+            match events.recv().await {
+                Ok(ev) => self.handle(&ev.method, &ev.params).await,
+                Err(broadcast::error::RecvError::Lagged(n)) => warn!("[BiDi] Missed {n} events"),
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn handle(&mut self, method: &str, p: &Value) {
+        let Some(context) = p["context"].as_str().or_else(|| p["source"]["context"].as_str()) else {
+            return;
+        };
+        match method {
+            "browsingContext.contextCreated" => {
+                let parent = p["parent"].as_str().map(String::from);
+                self.parents.insert(context.to_string(), parent);
+                return;
+            }
+            "browsingContext.contextDestroyed" => {
+                if self.parents.remove(context).flatten().is_none() {
+                    self.logs.lock().await.remove(context);
+                }
+                return;
+            }
+            // The navigation id doubles as the download id.
+            "browsingContext.downloadWillBegin" => {
+                self.downloads.lock().await.started(
+                    p["navigation"].as_str().unwrap_or_default(),
+                    p["url"].as_str().unwrap_or_default(),
+                    p["suggestedFilename"].as_str().unwrap_or_default(),
+                );
+                return;
+            }
+            "browsingContext.downloadEnd" => {
+                let path = p["filepath"].as_str().map(String::from);
+                let bytes = path.as_ref().and_then(|f| std::fs::metadata(f).ok()).map_or(0, |m| m.len());
+                let state = if p["status"] == "complete" { "completed" } else { "canceled" };
+                self.downloads.lock().await.progress(
+                    p["navigation"].as_str().unwrap_or_default(),
+                    state,
+                    bytes,
+                    path,
+                );
+                return;
+            }
+            _ => {}
+        }
+
+        let logs = page_logs(&self.logs, self.tab_of(context)).await;
+        match method {
+            "log.entryAdded" => {
+                let frame = p["stackTrace"]["callFrames"].get(0);
+                let url = frame.and_then(|f| f["url"].as_str()).map(String::from);
+                let line = frame.and_then(|f| f["lineNumber"].as_i64()).map(|n| n + 1);
+                let text = p["text"].as_str().unwrap_or_default();
+                let mut console = logs.console.lock().await;
+                if p["type"] == "console" {
+                    let level = p["method"].as_str().or(p["level"].as_str()).unwrap_or("log");
+                    console.push(level, "console", text, url, line);
+                } else {
+                    console.push("error", "exception", text, url, line);
+                }
+            }
+            "network.beforeRequestSent" => {
+                let request = &p["request"];
+                logs.network.lock().await.request_started(
+                    request["request"].as_str().unwrap_or_default(),
+                    request["url"].as_str().unwrap_or_default(),
+                    request["method"].as_str().unwrap_or("GET"),
+                    resource_type(request),
+                );
+            }
+            "network.responseCompleted" => {
+                let id = p["request"]["request"].as_str().unwrap_or_default();
+                let response = &p["response"];
+                let status = response["status"].as_i64().unwrap_or(0);
+                {
+                    let mut network = logs.network.lock().await;
+                    network.response_received(id, status, response["mimeType"].as_str().unwrap_or_default());
+                    // A redirect carries on as the same request.
+                    if !matches!(status, 301 | 302 | 303 | 307 | 308) {
+                        network.request_finished(id, None);
+                    }
+                }
+                // Chrome logs these itself; Firefox only shows them in devtools.
+                if status >= 400 {
+                    logs.console.lock().await.push(
+                        "error",
+                        "network",
+                        &format!(
+                            "Failed to load resource: the server responded with a status of {status} ({})",
+                            response["statusText"].as_str().unwrap_or_default()
+                        ),
+                        response["url"].as_str().map(String::from),
+                        None,
+                    );
+                }
+            }
+            "network.fetchError" => {
+                let request = &p["request"];
+                let error = p["errorText"].as_str().unwrap_or("failed");
+                logs.network.lock().await.request_finished(
+                    request["request"].as_str().unwrap_or_default(),
+                    Some(error.to_string()),
+                );
+                // Requests cut short by navigation aren't page errors.
+                if !error.contains("ABORTED") {
+                    logs.console.lock().await.push(
+                        "error",
+                        "network",
+                        &format!("Failed to load resource: {error}"),
+                        request["url"].as_str().map(String::from),
+                        None,
+                    );
+                }
+            }
+            "browsingContext.userPromptOpened" => {
+                let Some(conn) = self.conn.upgrade() else { return };
+                let url = conn
+                    .send("browsingContext.getTree", json!({ "root": context, "maxDepth": 0 }))
+                    .await
+                    .ok()
+                    .and_then(|tree| tree["contexts"][0]["url"].as_str().map(String::from))
+                    .unwrap_or_default();
+                let default_value = p["defaultValue"].as_str();
+                let dialog = web_dialog::to_json(
+                    p["type"].as_str().unwrap_or("alert"),
+                    p["message"].as_str().unwrap_or_default(),
+                    default_value,
+                    &url,
+                );
+                let policy = *self.policy.lock().await;
+                if policy == DialogPolicy::Report {
+                    *logs.dialog.lock().await = Some(dialog);
+                    logs.dialog_opened.notify_waiters();
+                    return;
+                }
+                let mut answer = json!({ "context": context, "accept": policy == DialogPolicy::Accept });
+                if let (DialogPolicy::Accept, Some(text)) = (policy, default_value) {
+                    answer["userText"] = json!(text);
+                }
+                // Noted first: the action may return as soon as the page unblocks.
+                logs.dialog_notes.lock().await.push(web_dialog::auto_note(policy, &dialog));
+                if let Err(e) = conn.send("browsingContext.handleUserPrompt", answer).await {
+                    logs.dialog_notes.lock().await
+                        .push(format!("Could not answer {}: {e}", web_dialog::describe(&dialog)));
+                }
+            }
+            "browsingContext.userPromptClosed" => *logs.dialog.lock().await = None,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Console messages, uncaught exceptions and failed requests of a browser tab.
+//!
+//! The CDP backend fills a [`ConsoleLog`] from `Runtime.consoleAPICalled`,
+//! `Runtime.exceptionThrown` and `Log.entryAdded`; the extension keeps the
+//! same ring buffer from its content-script hook and `chrome.webRequest`.
+//! Both report entries in one JSON shape, rendered by [`render`]:
+//!
+//! ```text
+//! [error] exception: TypeError: Cannot read properties of null (reading 'value') (https://app.example.com/app.js:88)
+//! [warning] Deprecated option 'legacy' (https://app.example.com/app.js:12)
+//! [error] network: POST https://app.example.com/api/save → 500
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{json, Value};
+
+/// Entries kept per tab.
+const MAX_ENTRIES: usize = 500;
+
+/// Longest message kept, in characters.
+const MAX_TEXT_CHARS: usize = 500;
+
+static SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// The newest entry number handed out. Later entries compare greater.
+pub fn mark() -> u64 {
+    SEQ.load(Ordering::Relaxed)
+}
+
+/// One of `debug`, `log`, `info`, `warning`, `error` for a console method,
+/// CDP log level or exception.
+pub fn normalize_level(level: &str) -> &'static str {
+    match level {
+        "error" | "assert" => "error",
+        "warning" | "warn" => "warning",
+        "info" => "info",
+        "debug" | "verbose" | "trace" => "debug",
+        _ => "log",
+    }
+}
+
+fn rank(level: &str) -> u8 {
+    match normalize_level(level) {
+        "debug" => 0,
+        "log" | "info" => 1,
+        "warning" => 2,
+        _ => 3,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsoleEntry {
+    pub seq: u64,
+    pub level: &'static str,
+    /// `console`, `exception`, `network`, or another CDP log source.
+    pub source: String,
+    pub text: String,
+    pub url: Option<String>,
+    pub line: Option<i64>,
+}
+
+impl ConsoleEntry {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "level": self.level,
+            "source": self.source,
+            "text": self.text,
+            "url": self.url,
+            "line": self.line,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConsoleLog {
+    entries: VecDeque<ConsoleEntry>,
+}
+
+impl ConsoleLog {
+    pub fn push(&mut self, level: &str, source: &str, text: &str, url: Option<String>, line: Option<i64>) {
+        let mut text = text.trim().to_string();
+        if text.chars().count() > MAX_TEXT_CHARS {
+            text = format!("{}…", text.chars().take(MAX_TEXT_CHARS).collect::<String>());
+        }
+        self.entries.push_back(ConsoleEntry {
+            seq: SEQ.fetch_add(1, Ordering::Relaxed) + 1,
+            level: normalize_level(level),
+            source: source.to_string(),
+            text,
+            url: url.filter(|u| !u.is_empty()),
+            line,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// The newest `limit` entries after `since` at `min_level` or above, oldest first.
+    pub fn query(&self, min_level: &str, since: u64, limit: usize) -> Vec<Value> {
+        let matching: Vec<&ConsoleEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.seq > since && rank(e.level) >= rank(min_level))
+            .collect();
+        let skip = matching.len().saturating_sub(limit);
+        matching.into_iter().skip(skip).map(ConsoleEntry::to_json).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// One line per entry as reported by a backend.
+pub fn render(entries: &[Value]) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            let s = |k: &str| e.get(k).and_then(Value::as_str).unwrap_or("");
+            let mut line = format!("[{}] ", s("level"));
+            if !s("source").is_empty() && s("source") != "console" {
+                line.push_str(&format!("{}: ", s("source")));
+            }
+            line.push_str(s("text"));
+            if !s("url").is_empty() {
+                match e.get("line").and_then(Value::as_i64) {
+                    Some(n) => line.push_str(&format!(" ({}:{n})", s("url"))),
+                    None => line.push_str(&format!(" ({})", s("url"))),
+                }
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
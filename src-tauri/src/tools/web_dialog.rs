@@ -0,0 +1,81 @@
+//! JavaScript dialogs: `alert`, `confirm`, `prompt` and `beforeunload`.
+//!
+//! While a dialog is open the page runs no script, so every command sent to
+//! it hangs. The CDP backend and the extension watch `Page.javascriptDialogOpening`
+//! while an action runs and, following the [`DialogPolicy`], either report
+//! the dialog in that action's result or answer it straight away. Both
+//! describe a dialog in one JSON shape (`type`, `message`, `default_prompt`,
+//! `url`), rendered by [`describe`]:
+//!
+//! ```text
+//! confirm dialog "Delete 3 items?"
+//! prompt dialog "Your name?" (default answer "guest")
+//! ```
+
+use serde_json::{json, Value};
+
+/// Environment variable with the default policy: `report`, `accept` or `dismiss`.
+pub const POLICY_ENV: &str = "ZEPTOBOT_DIALOG_POLICY";
+
+/// What to do with a dialog nobody has answered yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogPolicy {
+    /// Leave it open and report it; `browser_dialog` answers it.
+    Report,
+    Accept,
+    Dismiss,
+}
+
+impl DialogPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "report" => Some(Self::Report),
+            "accept" => Some(Self::Accept),
+            "dismiss" => Some(Self::Dismiss),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Report => "report",
+            Self::Accept => "accept",
+            Self::Dismiss => "dismiss",
+        }
+    }
+
+    /// The policy from [`POLICY_ENV`], or `Report`.
+    pub fn from_env() -> Self {
+        std::env::var(POLICY_ENV)
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(Self::Report)
+    }
+}
+
+/// A dialog in the shape both backends report it in.
+pub fn to_json(kind: &str, message: &str, default_prompt: Option<&str>, url: &str) -> Value {
+    json!({
+        "type": kind,
+        "message": message,
+        "default_prompt": default_prompt,
+        "url": url,
+    })
+}
+
+/// e.g. `confirm dialog "Delete 3 items?"`.
+pub fn describe(dialog: &Value) -> String {
+    let s = |k: &str| dialog.get(k).and_then(Value::as_str).unwrap_or("");
+    let kind = if s("type").is_empty() { "JavaScript" } else { s("type") };
+    let mut out = format!("{kind} dialog \"{}\"", s("message"));
+    if s("type") == "prompt" && !s("default_prompt").is_empty() {
+        out.push_str(&format!(" (default answer \"{}\")", s("default_prompt")));
+    }
+    out
+}
+
+/// The note added to a result when a dialog was answered by the policy.
+pub fn auto_note(policy: DialogPolicy, dialog: &Value) -> String {
+    let done = if policy == DialogPolicy::Dismiss { "dismissed" } else { "accepted" };
+    format!("Dialog {done} automatically: {}", describe(dialog))
+}
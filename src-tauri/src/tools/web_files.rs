@@ -0,0 +1,230 @@
+//! Files crossing the browser boundary: uploads read from, and downloads
+//! saved to, directories the user allowed.
+//!
+//! Config lives in `~/.zeptobot/files.json`; every field is optional:
+//!
+//! ```json
+//! {
+//!   "upload_dirs": ["~/Documents", "~/Desktop", "~/Downloads"],
+//!   "download_dir": "~/Downloads"
+//! }
+//! ```
+//!
+//! The CDP backend saves downloads to `download_dir` itself; the extension
+//! can't pick a folder, so Chrome's own download location must lie inside
+//! it. Both backends report downloads in one JSON shape, rendered by
+//! [`summary`]:
+//!
+//! ```text
+//! invoice-0042.pdf (18.2 KB) saved to /Users/me/Downloads/invoice-0042.pdf
+//! ```
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::web_network::url_matches;
+
+/// Downloads kept in the log.
+const MAX_ENTRIES: usize = 100;
+
+fn home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".into()))
+}
+
+/// `~/x` → `$HOME/x`.
+fn expand(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home().join(rest),
+        None if path == "~" => home(),
+        None => PathBuf::from(path),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FilesConfig {
+    /// Directories `browser_upload` may read files from.
+    pub upload_dirs: Vec<String>,
+    /// Where downloads are saved (CDP) or must end up (extension).
+    pub download_dir: String,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            upload_dirs: ["~/Documents", "~/Desktop", "~/Downloads"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            download_dir: "~/Downloads".into(),
+        }
+    }
+}
+
+impl FilesConfig {
+    pub fn path() -> PathBuf {
+        home().join(".zeptobot").join("files.json")
+    }
+
+    /// Load the config file, falling back to defaults when it's missing.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| format!("Invalid files config {}: {e}", path.display())),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    fn upload_roots(&self) -> Vec<PathBuf> {
+        self.upload_dirs
+            .iter()
+            .filter_map(|d| expand(d).canonicalize().ok())
+            .collect()
+    }
+
+    pub fn download_dir(&self) -> PathBuf {
+        expand(&self.download_dir)
+    }
+
+    /// The absolute path of a file to upload. Relative paths are looked up
+    /// in each upload directory in turn; symlinks are followed before the
+    /// directory check.
+    pub fn upload_path(&self, file: &str) -> Result<PathBuf, String> {
+        let roots = self.upload_roots();
+        let wanted = expand(file);
+        let found = if wanted.is_absolute() {
+            wanted.canonicalize().ok()
+        } else {
+            roots.iter().find_map(|r| r.join(&wanted).canonicalize().ok())
+        };
+        let Some(path) = found.filter(|p| p.is_file()) else {
+            return Err(format!("No file '{file}'"));
+        };
+        if !roots.iter().any(|r| path.starts_with(r)) {
+            return Err(format!(
+                "{} is outside the upload directories ({}) — add its folder to \"upload_dirs\" in {}",
+                path.display(),
+                self.upload_dirs.join(", "),
+                Self::path().display()
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Fail unless a finished download was saved inside `download_dir`.
+    pub fn check_download(&self, saved: &str) -> Result<(), String> {
+        let dir = self.download_dir();
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let saved = Path::new(saved);
+        let saved = saved.canonicalize().unwrap_or_else(|_| saved.to_path_buf());
+        if saved.starts_with(&dir) {
+            return Ok(());
+        }
+        Err(format!(
+            "The download was saved to {}, outside the download directory {} — change Chrome's \
+             download location or \"download_dir\" in {}",
+            saved.display(),
+            dir.display(),
+            Self::path().display()
+        ))
+    }
+}
+
+static SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// The newest download number handed out. Later downloads compare greater.
+pub fn mark() -> u64 {
+    SEQ.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone)]
+pub struct Download {
+    pub seq: u64,
+    pub id: String,
+    pub url: String,
+    pub filename: String,
+    /// `in_progress`, `completed` or `canceled`.
+    pub state: &'static str,
+    pub path: Option<String>,
+    pub bytes: u64,
+}
+
+impl Download {
+    pub fn finished(&self) -> bool {
+        self.state != "in_progress"
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url,
+            "filename": self.filename,
+            "state": self.state,
+            "path": self.path,
+            "bytes": self.bytes,
+        })
+    }
+}
+
+/// One line for a download as reported by a backend.
+pub fn summary(download: &Value) -> String {
+    let s = |k: &str| download.get(k).and_then(Value::as_str).unwrap_or("");
+    let name = if s("filename").is_empty() { s("url") } else { s("filename") };
+    let kb = download.get("bytes").and_then(Value::as_u64).unwrap_or(0) as f64 / 1024.0;
+    match s("state") {
+        "completed" => format!("{name} ({kb:.1} KB) saved to {}", s("path")),
+        "canceled" if !s("error").is_empty() => format!("{name} failed: {}", s("error")),
+        "canceled" => format!("{name} was canceled"),
+        _ => format!("{name} still downloading ({kb:.1} KB so far)"),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DownloadLog {
+    entries: VecDeque<Download>,
+}
+
+impl DownloadLog {
+    pub fn started(&mut self, id: &str, url: &str, filename: &str) {
+        self.entries.push_back(Download {
+            seq: SEQ.fetch_add(1, Ordering::Relaxed) + 1,
+            id: id.to_string(),
+            url: url.to_string(),
+            filename: filename.to_string(),
+            state: "in_progress",
+            path: None,
+            bytes: 0,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Record progress; `state` is CDP's `inProgress`, `completed` or `canceled`.
+    pub fn progress(&mut self, id: &str, state: &str, bytes: u64, path: Option<String>) {
+        let Some(entry) = self.entries.iter_mut().rev().find(|e| e.id == id) else {
+            return;
+        };
+        entry.bytes = bytes;
+        entry.state = match state {
+            "completed" => "completed",
+            "canceled" => "canceled",
+            _ => "in_progress",
+        };
+        if path.is_some() {
+            entry.path = path;
+        }
+    }
+
+    /// The first download after `since` whose file name or URL matches `pattern`.
+    pub fn find(&self, pattern: Option<&str>, since: u64) -> Option<&Download> {
+        self.entries.iter().find(|e| {
+            e.seq > since
+                && pattern.is_none_or(|p| url_matches(p, &e.filename) || url_matches(p, &e.url))
+        })
+    }
+}
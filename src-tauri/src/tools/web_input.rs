@@ -0,0 +1,133 @@
+//! Trusted browser input through the CDP `Input` domain.
+//!
+//! Events dispatched this way arrive with `isTrusted === true` and go through
+//! the browser's real input pipeline, so React-controlled fields, canvas
+//! editors and sites that ignore synthetic DOM events react to them. The CDP
+//! backend sends these params through chromiumoxide; the extension builds the
+//! same sequences for `chrome.debugger`.
+
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams,
+    DispatchMouseEventType, MouseButton,
+};
+
+/// Named keys: (key, code, Windows virtual key code, text it types).
+const NAMED_KEYS: &[(&str, &str, i64, &str)] = &[
+    ("Enter", "Enter", 13, "\r"),
+    ("Tab", "Tab", 9, ""),
+    ("Escape", "Escape", 27, ""),
+    ("Backspace", "Backspace", 8, ""),
+    ("Delete", "Delete", 46, ""),
+    ("Space", "Space", 32, " "),
+    ("ArrowUp", "ArrowUp", 38, ""),
+    ("ArrowDown", "ArrowDown", 40, ""),
+    ("ArrowLeft", "ArrowLeft", 37, ""),
+    ("ArrowRight", "ArrowRight", 39, ""),
+    ("Home", "Home", 36, ""),
+    ("End", "End", 35, ""),
+    ("PageUp", "PageUp", 33, ""),
+    ("PageDown", "PageDown", 34, ""),
+];
+
+/// CDP modifier bits.
+fn modifier_bit(name: &str) -> Option<i64> {
+    match name.to_ascii_lowercase().as_str() {
+        "alt" | "option" => Some(1),
+        "control" | "ctrl" => Some(2),
+        "meta" | "cmd" | "command" => Some(4),
+        "shift" => Some(8),
+        _ => None,
+    }
+}
+
+/// keyDown/keyUp params for a key combo like `Enter`, `a`, `Shift+Tab` or `Control+A`.
+pub fn key_events(combo: &str) -> Result<Vec<DispatchKeyEventParams>, String> {
+    let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let (key, mods) = match parts.split_last() {
+        Some((key, mods)) if !key.is_empty() => (*key, mods),
+        // "Control++" and "+" mean the plus key itself.
+        _ if combo.ends_with('+') => ("+", &parts[..parts.len().saturating_sub(2)]),
+        _ => return Err(format!("Invalid key '{combo}'")),
+    };
+    let mut modifiers = 0;
+    for m in mods {
+        modifiers |= modifier_bit(m).ok_or_else(|| format!("Unknown modifier '{m}' in '{combo}'"))?;
+    }
+
+    let (key, code, key_code, mut text) =
+        if let Some(&(k, c, vk, t)) = NAMED_KEYS.iter().find(|(k, ..)| k.eq_ignore_ascii_case(key)) {
+            (k.to_string(), c.to_string(), vk, t.to_string())
+        } else {
+            let mut chars = key.chars();
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                return Err(format!(
+                    "Unknown key '{key}' — use a single character or one of: {}",
+                    NAMED_KEYS.iter().map(|(k, ..)| *k).collect::<Vec<_>>().join(", ")
+                ));
+            };
+            let upper = ch.to_ascii_uppercase();
+            let code = if ch.is_ascii_alphabetic() {
+                format!("Key{upper}")
+            } else if ch.is_ascii_digit() {
+                format!("Digit{ch}")
+            } else {
+                String::new()
+            };
+            let key_code = if ch.is_ascii_alphanumeric() { upper as i64 } else { 0 };
+            let shown = if modifiers & 8 != 0 { upper } else { ch };
+            (shown.to_string(), code, key_code, shown.to_string())
+        };
+    // Shortcuts with Control/Alt/Meta don't type their character.
+    if modifiers & (1 | 2 | 4) != 0 {
+        text.clear();
+    }
+
+    let build = |kind: DispatchKeyEventType, with_text: bool| {
+        let mut b = DispatchKeyEventParams::builder()
+            .r#type(kind)
+            .key(key.clone())
+            .windows_virtual_key_code(key_code)
+            .modifiers(modifiers);
+        if !code.is_empty() {
+            b = b.code(code.clone());
+        }
+        if with_text {
+            b = b.text(text.clone()).unmodified_text(text.clone());
+        }
+        b.build()
+    };
+    let down = if text.is_empty() {
+        build(DispatchKeyEventType::RawKeyDown, false)?
+    } else {
+        build(DispatchKeyEventType::KeyDown, true)?
+    };
+    Ok(vec![down, build(DispatchKeyEventType::KeyUp, false)?])
+}
+
+/// Move, press and release the left button at viewport point `(x, y)`.
+pub fn click_events(x: f64, y: f64) -> Result<Vec<DispatchMouseEventParams>, String> {
+    [
+        DispatchMouseEventType::MouseMoved,
+        DispatchMouseEventType::MousePressed,
+        DispatchMouseEventType::MouseReleased,
+    ]
+    .into_iter()
+    .map(|kind| {
+        let moved = kind == DispatchMouseEventType::MouseMoved;
+        let mut b = DispatchMouseEventParams::builder().r#type(kind).x(x).y(y);
+        if !moved {
+            b = b.button(MouseButton::Left).click_count(1);
+        }
+        b.build()
+    })
+    .collect()
+}
+
+/// Move the mouse to viewport point `(x, y)` without pressing, for hover.
+pub fn hover_event(x: f64, y: f64) -> Result<DispatchMouseEventParams, String> {
+    DispatchMouseEventParams::builder()
+        .r#type(DispatchMouseEventType::MouseMoved)
+        .x(x)
+        .y(y)
+        .build()
+}
@@ -0,0 +1,200 @@
+//! Network activity of a browser tab: requests in flight, recently finished
+//! ones, and URL pattern matching.
+//!
+//! The CDP backend feeds a [`NetworkLog`] from `Network.*` events; the
+//! extension keeps the same bookkeeping over `chrome.webRequest`. Entries
+//! are numbered from one process-wide counter, so "requests made since the
+//! last page interaction" is a comparison against a [`mark`]:
+//!
+//! ```text
+//! GET https://app.example.com/api/items?page=2 → 200 application/json (143 ms)
+//! POST https://app.example.com/api/save → failed: net::ERR_CONNECTION_RESET
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+/// Finished requests kept per tab.
+const MAX_ENTRIES: usize = 300;
+
+/// How long the network must stay quiet to count as idle.
+pub const IDLE_QUIET: Duration = Duration::from_millis(500);
+
+/// Resource types that stay open by design and never make a page busy.
+const LONG_LIVED: &[&str] = &["EventSource", "WebSocket"];
+
+static SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// The newest request number handed out. Requests that start later compare
+/// greater.
+pub fn mark() -> u64 {
+    SEQ.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestEntry {
+    pub seq: u64,
+    pub id: String,
+    pub url: String,
+    pub method: String,
+    pub resource_type: String,
+    pub status: Option<i64>,
+    pub mime_type: Option<String>,
+    pub error: Option<String>,
+    started: Instant,
+    pub duration: Option<Duration>,
+}
+
+impl RequestEntry {
+    pub fn finished(&self) -> bool {
+        self.duration.is_some()
+    }
+
+    /// The shape both backends report a request in (see [`summary`]).
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url,
+            "method": self.method,
+            "type": self.resource_type,
+            "status": self.status,
+            "mime_type": self.mime_type,
+            "error": self.error,
+            "duration_ms": self.duration.map(|d| d.as_millis() as u64),
+        })
+    }
+}
+
+/// One line for a request as reported by a backend.
+pub fn summary(request: &Value) -> String {
+    let s = |k: &str| request.get(k).and_then(Value::as_str).unwrap_or("");
+    let mut line = format!("{} {}", s("method"), s("url"));
+    if !s("error").is_empty() {
+        line.push_str(&format!(" → failed: {}", s("error")));
+    } else if let Some(status) = request.get("status").and_then(Value::as_i64) {
+        line.push_str(&format!(" → {status}"));
+        if !s("mime_type").is_empty() {
+            line.push_str(&format!(" {}", s("mime_type")));
+        }
+    } else {
+        line.push_str(" → pending");
+    }
+    if let Some(ms) = request.get("duration_ms").and_then(Value::as_u64) {
+        line.push_str(&format!(" ({ms} ms)"));
+    }
+    line
+}
+
+/// Whether `url` matches `pattern`: a glob over the whole URL when the
+/// pattern has `*` (e.g. `*/api/items?*`), otherwise a substring.
+pub fn url_matches(pattern: &str, url: &str) -> bool {
+    if !pattern.contains('*') {
+        return url.contains(pattern);
+    }
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !url.starts_with(first) || !url.ends_with(last) || url.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &url[first.len()..url.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[derive(Debug)]
+pub struct NetworkLog {
+    entries: VecDeque<RequestEntry>,
+    last_activity: Instant,
+}
+
+impl Default for NetworkLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            last_activity: Instant::now(),
+        }
+    }
+}
+
+impl NetworkLog {
+    fn entry_mut(&mut self, id: &str) -> Option<&mut RequestEntry> {
+        self.entries.iter_mut().rev().find(|e| e.id == id)
+    }
+
+    pub fn request_started(&mut self, id: &str, url: &str, method: &str, resource_type: &str) {
+        self.last_activity = Instant::now();
+        // Redirects reuse the request id: follow the request to its new URL.
+        if let Some(entry) = self.entry_mut(id).filter(|e| !e.finished()) {
+            entry.url = url.to_string();
+            return;
+        }
+        self.entries.push_back(RequestEntry {
+            seq: SEQ.fetch_add(1, Ordering::Relaxed) + 1,
+            id: id.to_string(),
+            url: url.to_string(),
+            method: method.to_string(),
+            resource_type: resource_type.to_string(),
+            status: None,
+            mime_type: None,
+            error: None,
+            started: Instant::now(),
+            duration: None,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            if let Some(i) = self.entries.iter().position(RequestEntry::finished) {
+                self.entries.remove(i);
+            }
+        }
+    }
+
+    pub fn response_received(&mut self, id: &str, status: i64, mime_type: &str) {
+        self.last_activity = Instant::now();
+        if let Some(entry) = self.entry_mut(id) {
+            entry.status = Some(status);
+            entry.mime_type = Some(mime_type.to_string()).filter(|m| !m.is_empty());
+        }
+    }
+
+    pub fn request_finished(&mut self, id: &str, error: Option<String>) {
+        self.last_activity = Instant::now();
+        if let Some(entry) = self.entry_mut(id).filter(|e| !e.finished()) {
+            entry.duration = Some(entry.started.elapsed());
+            entry.error = error;
+        }
+    }
+
+    /// Requests still loading, long-lived streams excluded.
+    pub fn in_flight(&self) -> impl Iterator<Item = &RequestEntry> {
+        self.entries
+            .iter()
+            .filter(|e| !e.finished() && !LONG_LIVED.contains(&e.resource_type.as_str()))
+    }
+
+    /// No request in flight for at least [`IDLE_QUIET`].
+    pub fn is_idle(&self) -> bool {
+        self.in_flight().next().is_none() && self.last_activity.elapsed() >= IDLE_QUIET
+    }
+
+    /// The first finished request after `since` matching `pattern` (and `method`).
+    pub fn find(&self, pattern: &str, method: Option<&str>, since: u64) -> Option<&RequestEntry> {
+        self.entries.iter().find(|e| {
+            e.seq > since
+                && e.finished()
+                && method.is_none_or(|m| e.method.eq_ignore_ascii_case(m))
+                && url_matches(pattern, &e.url)
+        })
+    }
+
+    /// The newest `n` requests, oldest first.
+    pub fn recent(&self, n: usize) -> Vec<&RequestEntry> {
+        let skip = self.entries.len().saturating_sub(n);
+        self.entries.iter().skip(skip).collect()
+    }
+}
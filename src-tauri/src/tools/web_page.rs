@@ -0,0 +1,735 @@
+//! Page actions shared by the CDP and Firefox (WebDriver BiDi) backends.
+//!
+//! Both backends run the same `window.__zb` page functions and trusted
+//! input, so everything above the protocol lives here once: picking the
+//! tab and frame an action targets, turning `ref` and locator arguments
+//! into selectors, waiting for elements to become actionable, typing, and
+//! the actions that are nothing but page functions plus input. A backend
+//! implements `PageDriver` — tab and frame lookup, one way to call a page
+//! function, four kinds of input — and hands every action it has no
+//! protocol-specific code for to `run`. The argument parsers and page
+//! constants every backend reads live here too.
+//!
+//! A typical action:
+//!
+//! ```text
+//! click {ref: "e7"} → target (tab, frame) → resolve ref → actionable? → click at (x, y)
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use super::web_console::ConsoleLog;
+use super::web_dialog;
+use super::web_network::NetworkLog;
+use super::web_session;
+
+/// Page-side helpers (element refs, ...) shared with the extension.
+pub(super) const DOM_JS: &str = include_str!("../../../extension/dom.js");
+
+/// Actions that accept a `ref` from `browser_list_elements` in place of a selector.
+pub(super) const REF_ACTIONS: [&str; 12] = [
+    "click", "type", "read", "wait_for", "fill", "hover", "select_option", "check", "press_key", "scroll",
+    "upload", "screenshot",
+];
+
+/// Tallest full-page capture, in CSS pixels (Chrome's texture limit).
+pub(super) const MAX_CAPTURE_HEIGHT: f64 = 16_384.0;
+
+/// The optional `tab` argument as a string (ids may arrive as numbers or strings).
+pub(super) fn tab_arg(params: &Value) -> Option<String> {
+    match params.get("tab")? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// `navigate`'s `wait_until`: `load` (default), `domcontentloaded` or `networkidle`.
+pub(super) fn wait_until_arg(params: &Value) -> Result<&str, String> {
+    match params.get("wait_until").and_then(Value::as_str).unwrap_or("load") {
+        w @ ("load" | "domcontentloaded" | "networkidle") => Ok(w),
+        other => Err(format!(
+            "Unknown wait_until '{other}' — use load, domcontentloaded or networkidle"
+        )),
+    }
+}
+
+/// The `timeout_ms` argument, or `default`, capped at 30s.
+pub(super) fn timeout_arg(params: &Value, default: u64) -> u64 {
+    params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(default).min(30_000)
+}
+
+/// Unwrap a `{ ok, result }` / `{ error }` object from a `window.__zb` action.
+pub(super) fn page_result(res: Value) -> Result<String, String> {
+    match res.get("result").and_then(Value::as_str) {
+        Some(done) => Ok(done.to_string()),
+        None => Err(res
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or("The page returned no result")
+            .to_string()),
+    }
+}
+
+/// The `files` argument: absolute paths, already checked by `browser_upload`.
+pub(super) fn files_arg(params: &Value) -> Result<Vec<String>, String> {
+    let files: Vec<String> = params
+        .get("files")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().filter_map(Value::as_str).map(String::from).collect())
+        .unwrap_or_default();
+    if files.is_empty() {
+        return Err("Missing 'files'".into());
+    }
+    Ok(files)
+}
+
+/// e.g. `2 files: invoice.pdf, receipt.png`.
+pub(super) fn describe_files(files: &[String]) -> String {
+    let names: Vec<&str> = files
+        .iter()
+        .map(|f| std::path::Path::new(f).file_name().and_then(|n| n.to_str()).unwrap_or(f))
+        .collect();
+    match names.len() {
+        1 => format!("1 file: {}", names[0]),
+        n => format!("{n} files: {}", names.join(", ")),
+    }
+}
+
+/// The site a `cookies` action is about: `domain`, or the host of the tab's `url`.
+pub(super) fn cookie_domain_arg(params: &Value, url: &str) -> Result<String, String> {
+    if let Some(domain) = params.get("domain").and_then(Value::as_str).filter(|d| !d.trim().is_empty()) {
+        return Ok(domain.trim().to_string());
+    }
+    web_session::host_of(url).ok_or_else(|| format!("The tab ({url}) isn't a website — pass 'domain'"))
+}
+
+/// The `(area, op)` arguments of `window.__zb.storage` for a `storage` action.
+pub(super) fn storage_args(params: &Value) -> Result<[Value; 2], String> {
+    let area = match params.get("area").and_then(Value::as_str).unwrap_or("local") {
+        a @ ("local" | "session") => a,
+        other => return Err(format!("Unknown area '{other}' — use local or session")),
+    };
+    let op = json!({
+        "key": params.get("key").and_then(Value::as_str),
+        "clear": params.get("clear").and_then(Value::as_bool).unwrap_or(false),
+    });
+    Ok([json!(area), op])
+}
+
+/// Whether `browser_read` asked for Markdown, and the options for `window.__zb.markdown`.
+pub(super) fn markdown_opts(params: &Value) -> Option<Value> {
+    if params.get("format").and_then(Value::as_str) != Some("markdown") {
+        return None;
+    }
+    Some(json!({ "main": params.get("main_content").and_then(Value::as_bool).unwrap_or(true) }))
+}
+
+/// `browser_scroll` options in the shape `window.__zb.scroll` takes.
+pub(super) fn scroll_opts(params: &Value) -> Value {
+    json!({
+        "direction": params.get("direction").and_then(Value::as_str).unwrap_or("down"),
+        "amount": params.get("amount").and_then(Value::as_f64),
+        "to": params.get("to").and_then(Value::as_str),
+        "intoView": params.get("into_view").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+/// The optional `frame` argument (index, name, URL part or iframe selector).
+pub(super) fn frame_arg(params: &Value) -> Option<String> {
+    match params.get("frame")? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether the call asked for trusted (CDP Input domain) events.
+pub(super) fn trusted_arg(params: &Value) -> bool {
+    params.get("trusted").and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Whether `browser_type` should press Enter afterwards.
+pub(super) fn submit_arg(params: &Value) -> bool {
+    params.get("submit").and_then(Value::as_bool).unwrap_or(false)
+}
+
+// Page functions, called through `PageDriver::call` with their arguments
+// passed as JSON values.
+
+/// `(value)`: set the focused field's value through the native setter so
+/// React/Vue/Angular state updates.
+const JS_SET_FOCUSED_VALUE: &str = r#"(val) => {
+    const el = document.activeElement;
+    if (!el || !(el.tagName === 'INPUT' || el.tagName === 'TEXTAREA' || el.isContentEditable)) {
+        return 'no focused input';
+    }
+    if (el.isContentEditable) {
+        el.textContent = val;
+        el.dispatchEvent(new Event('input', { bubbles: true }));
+    } else {
+        const proto = el.tagName === 'TEXTAREA'
+            ? HTMLTextAreaElement.prototype
+            : HTMLInputElement.prototype;
+        Object.getOwnPropertyDescriptor(proto, 'value').set.call(el, val);
+        el.dispatchEvent(new Event('input', { bubbles: true }));
+        el.dispatchEvent(new Event('change', { bubbles: true }));
+    }
+    return 'typed';
+}"#;
+
+/// `(selector, pageText)`: element text, full page text, or title + URL.
+/// Untruncated; `BrowserReadTool` pages through it.
+const JS_READ: &str = r#"(sel, full) => {
+    if (sel) {
+        const el = window.__zb.query(sel);
+        return el ? (el.innerText || el.textContent || el.value || '') : 'not found';
+    }
+    if (full) return document.body.innerText || '';
+    return JSON.stringify({ title: document.title, url: location.href });
+}"#;
+
+/// `(selector, text)`: text of the matching element, or null if not there yet.
+const JS_WAIT_FOR: &str = r#"(sel, text) => {
+    if (sel) {
+        const el = window.__zb.query(sel);
+        return el ? (el.innerText || el.textContent || 'found') : null;
+    }
+    const needle = text.toLowerCase();
+    const w = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT);
+    while (w.nextNode()) {
+        if (w.currentNode.textContent.toLowerCase().includes(needle)) {
+            return w.currentNode.parentElement.innerText.substring(0, 200);
+        }
+    }
+    return null;
+}"#;
+
+/// `(selector, value, submit)`: fill one form field of any kind.
+const JS_FILL: &str = "(sel, value, submit) => window.__zb.fill(sel, value, submit)";
+
+/// `(selector, options)`: Markdown of an element or the page's main content.
+const JS_MARKDOWN: &str = "(sel, opts) => window.__zb.markdown(sel, opts)";
+
+/// `(spec)`: schema- or table-driven extraction, as a JSON string.
+const JS_EXTRACT: &str = "(spec) => window.__zb.extract(spec)";
+
+/// `(selector, values)`: pick `<select>` options by value or label.
+const JS_SELECT_OPTION: &str = "(sel, values) => window.__zb.selectOption(sel, values)";
+
+/// `(selector, checked)`: set a checkbox, switch or radio button.
+const JS_CHECK: &str = "(sel, checked) => window.__zb.check(sel, checked)";
+
+/// `(selector, options)`: scroll the page or an element's scroll container.
+const JS_SCROLL: &str = "(sel, opts) => window.__zb.scroll(sel, opts)";
+
+/// `(selector)`: scroll an element into view and return its viewport box.
+pub(super) const JS_BOX: &str = "(sel) => window.__zb.box(sel)";
+
+/// `(area, op)`: read, clear or fill `localStorage` / `sessionStorage`.
+pub(super) const JS_STORAGE: &str = "(area, op) => window.__zb.storage(area, op)";
+
+/// `(selector)`: name and src of an `<iframe>`, to pick its frame.
+const JS_FRAME_OWNER: &str = "(sel) => { try { return window.__zb.frameOwner(sel); } \
+    catch (e) { return { error: e.message }; } }";
+
+/// What a tab has been doing, fed by its protocol events.
+#[derive(Default)]
+pub(super) struct PageLogs {
+    pub(super) network: Mutex<NetworkLog>,
+    pub(super) console: Mutex<ConsoleLog>,
+    /// The dialog waiting for `browser_dialog`, in [`web_dialog`]'s shape.
+    pub(super) dialog: Mutex<Option<Value>>,
+    /// Signalled when a dialog opens and is left for `browser_dialog`.
+    pub(super) dialog_opened: tokio::sync::Notify,
+    /// Dialogs the policy answered since the last action reported them.
+    pub(super) dialog_notes: Mutex<Vec<String>>,
+}
+
+/// A frame of a tab. Frames are listed depth-first in document order, so
+/// index 0 is the main frame.
+pub(super) struct FrameInfo {
+    /// The CDP frame id or the BiDi browsing context id.
+    pub(super) id: String,
+    pub(super) name: String,
+    pub(super) url: String,
+}
+
+/// Index of the frame a `frame` argument names: its index in
+/// browser_list_elements output, its exact name, or part of its URL.
+fn pick_frame(frames: &[FrameInfo], spec: &str) -> Option<usize> {
+    if let Ok(i) = spec.trim().parse::<usize>() {
+        return (i < frames.len()).then_some(i);
+    }
+    frames
+        .iter()
+        .position(|f| f.name == spec)
+        .or_else(|| frames.iter().position(|f| f.url.contains(spec)))
+}
+
+fn describe_frames(frames: &[FrameInfo]) -> String {
+    frames
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, f)| {
+            if f.name.is_empty() {
+                format!("{i}: {}", f.url)
+            } else {
+                format!("{i}: \"{}\" {}", f.name, f.url)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Index in `tabs` (ids, oldest first) of the tab to act on: the `tab`
+/// argument if given, else `selected` (from switch/new tab) while it is
+/// open, else the most recent one. `None` when there are no tabs.
+pub(super) fn pick_tab(tabs: &[String], params: &Value, selected: Option<&str>) -> Result<Option<usize>, String> {
+    if let Some(tab) = tab_arg(params) {
+        return match tabs.iter().position(|t| *t == tab) {
+            Some(i) => Ok(Some(i)),
+            None => Err(format!("No tab with id '{tab}' — use an id from browser_tabs")),
+        };
+    }
+    Ok(selected
+        .and_then(|current| tabs.iter().position(|t| t == current))
+        .or(tabs.len().checked_sub(1)))
+}
+
+/// A browser protocol the shared page actions can drive.
+#[async_trait]
+pub(super) trait PageDriver: Sync {
+    /// Where page functions run: a tab and one of its frames.
+    type Target: Send + Sync;
+
+    /// The backend's name in "Unknown … action" errors.
+    const NAME: &'static str;
+
+    /// The main frame of the tab the `tab` argument picks (see [`pick_tab`]).
+    async fn main_target(&self, params: &Value) -> Result<Self::Target, String>;
+
+    /// Frames of `main`'s tab, depth-first in document order.
+    async fn frames(&self, main: &Self::Target) -> Result<Vec<FrameInfo>, String>;
+
+    /// Child frame `frames[index]` of `main`'s tab.
+    async fn frame_target(
+        &self,
+        main: Self::Target,
+        frames: &[FrameInfo],
+        index: usize,
+    ) -> Result<Self::Target, String>;
+
+    /// Call `function` (a fixed JS function expression) in the target frame
+    /// with the shared DOM helpers (`window.__zb`) loaded. Arguments travel
+    /// apart from the source and are never spliced into it, so quotes,
+    /// backslashes or `</script>` in selectors and text stay data.
+    async fn call(&self, target: &Self::Target, function: &str, args: &[Value]) -> Result<Value, String>;
+
+    /// The network, console and dialog logs of the target's tab.
+    async fn logs(&self, target: &Self::Target) -> Result<Arc<PageLogs>, String>;
+
+    /// Where the target frame's viewport sits in the coordinates its input
+    /// events use.
+    fn input_origin(&self, target: &Self::Target) -> (f64, f64);
+
+    /// Move the mouse to `(x, y)` and click.
+    async fn click(&self, target: &Self::Target, x: f64, y: f64) -> Result<(), String>;
+
+    /// Move the mouse to `(x, y)` without pressing.
+    async fn hover(&self, target: &Self::Target, x: f64, y: f64) -> Result<(), String>;
+
+    /// Press a key combo (`Enter`, `Shift+Tab`, ...).
+    async fn press(&self, target: &Self::Target, combo: &str) -> Result<(), String>;
+
+    /// Put `text` into the focused field as if typed by the user.
+    async fn insert_text(&self, target: &Self::Target, text: &str) -> Result<(), String>;
+}
+
+/// Resolve the tab and frame an action targets from its `tab` / `frame` arguments.
+pub(super) async fn target<D: PageDriver>(d: &D, params: &Value) -> Result<D::Target, String> {
+    let main = d.main_target(params).await?;
+    let Some(spec) = frame_arg(params) else {
+        return Ok(main);
+    };
+
+    let frames = d.frames(&main).await?;
+    let index = match pick_frame(&frames, &spec) {
+        Some(i) => i,
+        None => {
+            // Not an index, name or URL: try it as a selector for the <iframe>.
+            let owner = d.call(&main, JS_FRAME_OWNER, &[json!(spec)]).await?;
+            let name = owner.get("name").and_then(Value::as_str).unwrap_or("");
+            let src = owner.get("src").and_then(Value::as_str).unwrap_or("");
+            frames
+                .iter()
+                .position(|f| (!name.is_empty() && f.name == name) || (!src.is_empty() && f.url == src))
+                .ok_or_else(|| format!("No frame matches '{spec}'. Frames: {}", describe_frames(&frames)))?
+        }
+    };
+    if index == 0 {
+        return Ok(main);
+    }
+    d.frame_target(main, &frames, index).await
+}
+
+/// Replace the `ref` or `locator` argument of an action that takes one with
+/// a selector, so every action after this sees a plain `selector`.
+pub(super) async fn resolve_args<D: PageDriver>(d: &D, action: &str, params: &mut Value) -> Result<(), String> {
+    if !REF_ACTIONS.contains(&action) {
+        return Ok(());
+    }
+    let target = target(d, params).await?;
+    resolve_ref(d, &target, params).await?;
+    resolve_locator(d, &target, action, params).await
+}
+
+/// Replace a `ref` argument with the selector it stands for, failing on stale refs.
+async fn resolve_ref<D: PageDriver>(d: &D, target: &D::Target, params: &mut Value) -> Result<(), String> {
+    let Some(r) = params.get("ref").and_then(Value::as_str) else {
+        return Ok(());
+    };
+    let res = d
+        .call(
+            target,
+            "(r) => { try { return { selector: window.__zb.refSelector(r) }; } \
+             catch (e) { return { error: e.message }; } }",
+            &[json!(r)],
+        )
+        .await?;
+    match res.get("selector").and_then(Value::as_str) {
+        Some(sel) => {
+            params["selector"] = json!(sel);
+            Ok(())
+        }
+        None => Err(res
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or("Could not resolve ref")
+            .to_string()),
+    }
+}
+
+/// Replace a `locator` argument with a selector for the one element it
+/// matches. `wait_for` keeps retrying until the element shows up.
+async fn resolve_locator<D: PageDriver>(
+    d: &D,
+    target: &D::Target,
+    action: &str,
+    params: &mut Value,
+) -> Result<(), String> {
+    let Some(loc) = params.get("locator").cloned() else {
+        return Ok(());
+    };
+    let wait_ms = if action == "wait_for" {
+        params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(5000).min(15000)
+    } else {
+        0
+    };
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(wait_ms);
+    loop {
+        let res = d.call(target, "(loc) => window.__zb.locate(loc)", std::slice::from_ref(&loc)).await?;
+        if let Some(sel) = res.get("selector").and_then(Value::as_str) {
+            params["selector"] = json!(sel);
+            return Ok(());
+        }
+        let kind = res.get("kind").and_then(Value::as_str).unwrap_or("");
+        if kind != "none" || tokio::time::Instant::now() >= deadline {
+            return Err(res
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("Locator did not match")
+                .to_string());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+}
+
+/// Poll the page until the element at `sel` passes the actionability
+/// checks for `action` (see `actionable` in dom.js), or report the check
+/// that was still failing when `timeout_ms` ran out. Returns the
+/// element's centre in input coordinates.
+async fn wait_actionable<D: PageDriver>(
+    d: &D,
+    target: &D::Target,
+    action: &str,
+    sel: &str,
+    params: &Value,
+) -> Result<(f64, f64), String> {
+    let timeout = params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(5000).min(15000);
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout);
+    loop {
+        let res = d
+            .call(
+                target,
+                "(sel, action) => window.__zb.actionable(sel, action)",
+                &[json!(sel), json!(action)],
+            )
+            .await?;
+        if res.get("ok").and_then(Value::as_bool) == Some(true) {
+            let coord = |k: &str| res.get(k).and_then(Value::as_f64).unwrap_or(0.0);
+            let (dx, dy) = d.input_origin(target);
+            return Ok((coord("x") + dx, coord("y") + dy));
+        }
+        if tokio::time::Instant::now() >= deadline {
+            let check = res.get("check").and_then(Value::as_str).unwrap_or("unknown");
+            let error = res.get("error").and_then(Value::as_str).unwrap_or("not actionable");
+            return Err(format!("Not actionable after {timeout}ms ({check} check failed): {error}"));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+/// Focus the field at `sel` (or keep the focused one) without clearing it.
+async fn focus_field<D: PageDriver>(d: &D, target: &D::Target, sel: Option<&str>, clear: bool) -> Result<(), String> {
+    let res = d
+        .call(target, "(sel, clear) => window.__zb.focusField(sel, clear)", &[json!(sel), json!(clear)])
+        .await?;
+    match res.get("error").and_then(Value::as_str) {
+        Some(err) => Err(err.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Type with trusted input: focus the field (or keep the focused one),
+/// select and delete its contents if `clear`, then insert `value`.
+async fn trusted_type<D: PageDriver>(
+    d: &D,
+    target: &D::Target,
+    sel: Option<&str>,
+    value: &str,
+    clear: bool,
+) -> Result<(), String> {
+    focus_field(d, target, sel, clear).await?;
+    if clear {
+        d.press(target, "Backspace").await?;
+    }
+    d.insert_text(target, value).await
+}
+
+/// Wait until `log` shows no requests in flight for a moment. On timeout,
+/// says what is still loading.
+pub(super) async fn network_idle(log: &Mutex<NetworkLog>, deadline: tokio::time::Instant) -> Result<(), String> {
+    loop {
+        {
+            let log = log.lock().await;
+            if log.is_idle() {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let busy: Vec<String> = log.in_flight().map(|e| e.url.clone()).collect();
+                return Err(format!(
+                    "network still busy — {} request(s) in flight{}",
+                    busy.len(),
+                    busy.first().map(|u| format!(", e.g. {u}")).unwrap_or_default()
+                ));
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+/// Run a page action, returning as soon as it opens a dialog: a page with
+/// a dialog open runs no script, so the action would only hang.
+pub(super) async fn watched(
+    action: &str,
+    logs: &PageLogs,
+    run: impl std::future::Future<Output = Result<String, String>>,
+) -> Result<String, String> {
+    if let Some(dialog) = logs.dialog.lock().await.as_ref() {
+        return Err(format!(
+            "A {} is open — answer it with browser_dialog first",
+            web_dialog::describe(dialog)
+        ));
+    }
+    let opened = logs.dialog_opened.notified();
+    tokio::pin!(opened);
+    opened.as_mut().enable();
+    let mut result = tokio::select! {
+        result = run => result,
+        _ = opened => {
+            let dialog = logs.dialog.lock().await.clone().unwrap_or_default();
+            return Ok(format!(
+                "{action} opened a {}. The page is blocked until you answer it with \
+                 browser_dialog (accept or dismiss).",
+                web_dialog::describe(&dialog)
+            ));
+        }
+    };
+    // A dialog from a timer may open just after the action returns.
+    let mut notes = std::mem::take(&mut *logs.dialog_notes.lock().await);
+    if let Some(dialog) = logs.dialog.lock().await.as_ref() {
+        notes.push(format!(
+            "A {} opened — answer it with browser_dialog.",
+            web_dialog::describe(dialog)
+        ));
+    }
+    if !notes.is_empty() {
+        let notes = format!("\n\n{}", notes.join("\n"));
+        match &mut result {
+            Ok(r) => r.push_str(&notes),
+            Err(e) => e.push_str(&notes),
+        }
+    }
+    result
+}
+
+/// `browser_console` over a tab's logs: entries at `level` or above logged
+/// after `since`, cleared afterwards if asked.
+pub(super) async fn console(logs: &PageLogs, params: &Value, since: u64) -> String {
+    let level = params.get("level").and_then(Value::as_str).unwrap_or("log");
+    let limit = params.get("limit").and_then(Value::as_u64).unwrap_or(50) as usize;
+    let mut console = logs.console.lock().await;
+    let entries = console.query(level, since, limit);
+    if params.get("clear").and_then(Value::as_bool) == Some(true) {
+        console.clear();
+    }
+    Value::Array(entries).to_string()
+}
+
+/// Run an action that is the same on every backend: page functions plus
+/// trusted input. `params` has its `ref` / locator already resolved (see
+/// [`resolve_args`]).
+pub(super) async fn run<D: PageDriver>(d: &D, action: &str, params: &Value) -> Result<String, String> {
+    match action {
+        "click" => {
+            let target = target(d, params).await?;
+            let sel = params.get("selector").and_then(Value::as_str).ok_or(
+                "Provide 'ref', 'selector', 'text', 'role', 'label' or 'placeholder'",
+            )?;
+            // Always real mouse events here: protocol input is trusted by nature.
+            let (x, y) = wait_actionable(d, &target, "click", sel, params).await?;
+            d.click(&target, x, y).await?;
+            Ok(format!("clicked: {sel}"))
+        }
+        "type" => {
+            let target = target(d, params).await?;
+            let value = params.get("value").and_then(Value::as_str)
+                .ok_or("Missing 'value'")?;
+            let selector = params.get("selector").and_then(Value::as_str);
+            if let Some(sel) = selector {
+                wait_actionable(d, &target, "type", sel, params).await?;
+            }
+            let typed = if selector.is_some() || trusted_arg(params) {
+                let clear = params.get("clear_first").and_then(Value::as_bool).unwrap_or(true);
+                trusted_type(d, &target, selector, value, clear).await?;
+                format!("typed into {}", selector.unwrap_or("focused element"))
+            } else {
+                let typed = d.call(&target, JS_SET_FOCUSED_VALUE, &[json!(value)]).await?;
+                typed.as_str().unwrap_or_default().to_string()
+            };
+            if submit_arg(params) {
+                d.press(&target, "Enter").await?;
+                return Ok(format!("{typed}, pressed Enter"));
+            }
+            Ok(typed)
+        }
+        "read" => {
+            let target = target(d, params).await?;
+            let selector = params.get("selector").and_then(Value::as_str);
+            if let Some(opts) = markdown_opts(params) {
+                return page_result(d.call(&target, JS_MARKDOWN, &[json!(selector), opts]).await?);
+            }
+            let full = params.get("page_text").and_then(Value::as_bool).unwrap_or(false);
+            let val = d.call(&target, JS_READ, &[json!(selector), json!(full)]).await?;
+            Ok(val.as_str().unwrap_or_default().to_string())
+        }
+        "fill" => {
+            let target = target(d, params).await?;
+            let sel = params.get("selector").and_then(Value::as_str)
+                .ok_or("Provide 'ref', 'selector', 'role', 'label' or 'placeholder'")?;
+            let value = params.get("value").cloned().ok_or("Missing 'value'")?;
+            page_result(d.call(&target, JS_FILL, &[json!(sel), value, json!(submit_arg(params))]).await?)
+        }
+        "hover" => {
+            let target = target(d, params).await?;
+            let sel = params.get("selector").and_then(Value::as_str).ok_or(
+                "Provide 'ref', 'selector', 'text', 'role', 'label' or 'placeholder'",
+            )?;
+            let (x, y) = wait_actionable(d, &target, "hover", sel, params).await?;
+            d.hover(&target, x, y).await?;
+            Ok(format!("hovered: {sel}"))
+        }
+        "select_option" => {
+            let target = target(d, params).await?;
+            let sel = params.get("selector").and_then(Value::as_str)
+                .ok_or("Provide 'ref', 'selector', 'label' or 'role'")?;
+            let values = params.get("values").cloned().ok_or("Missing 'values'")?;
+            page_result(d.call(&target, JS_SELECT_OPTION, &[json!(sel), values]).await?)
+        }
+        "check" => {
+            let target = target(d, params).await?;
+            let sel = params.get("selector").and_then(Value::as_str)
+                .ok_or("Provide 'ref', 'selector', 'label' or 'role'")?;
+            let checked = params.get("checked").and_then(Value::as_bool).unwrap_or(true);
+            page_result(d.call(&target, JS_CHECK, &[json!(sel), json!(checked)]).await?)
+        }
+        "press_key" => {
+            let target = target(d, params).await?;
+            let key = params.get("key").and_then(Value::as_str).ok_or("Missing 'key'")?;
+            let selector = params.get("selector").and_then(Value::as_str);
+            if let Some(sel) = selector {
+                focus_field(d, &target, Some(sel), false).await?;
+            }
+            d.press(&target, key).await?;
+            Ok(format!("pressed {key}{}", selector.map(|s| format!(" in {s}")).unwrap_or_default()))
+        }
+        "extract" => {
+            let target = target(d, params).await?;
+            let spec = params.get("spec").cloned().ok_or("Missing 'spec'")?;
+            page_result(d.call(&target, JS_EXTRACT, &[spec]).await?)
+        }
+        "scroll" => {
+            let target = target(d, params).await?;
+            let selector = params.get("selector").and_then(Value::as_str);
+            page_result(d.call(&target, JS_SCROLL, &[json!(selector), scroll_opts(params)]).await?)
+        }
+        "storage" => {
+            let target = target(d, params).await?;
+            page_result(d.call(&target, JS_STORAGE, &storage_args(params)?).await?)
+        }
+        "wait_for" => {
+            let selector = params.get("selector").and_then(Value::as_str);
+            let text = params.get("text").and_then(Value::as_str);
+            let timeout = params.get("timeout_ms").and_then(Value::as_u64).unwrap_or(5000).min(15000);
+            let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout);
+            if params.get("network_idle").and_then(Value::as_bool) == Some(true) {
+                let logs = d.logs(&d.main_target(params).await?).await?;
+                network_idle(&logs.network, deadline)
+                    .await
+                    .map_err(|busy| format!("Timeout after {timeout}ms: {busy}"))?;
+                if selector.is_none() && text.is_none() {
+                    return Ok("Network idle".into());
+                }
+            }
+            if selector.is_none() && text.is_none() {
+                return Err("Provide 'selector' or 'text'".into());
+            }
+            let args = [json!(selector), json!(text)];
+
+            loop {
+                // Re-resolve each round: navigation replaces the frame's script context.
+                let found = match target(d, params).await {
+                    Ok(target) => d.call(&target, JS_WAIT_FOR, &args).await,
+                    Err(e) => Err(e),
+                };
+                if let Ok(Value::String(result)) = &found {
+                    return Ok(result.clone());
+                }
+                if tokio::time::Instant::now() >= deadline {
+                    return match found {
+                        Err(e) => Err(format!("Timeout after {timeout}ms: {e}")),
+                        Ok(_) => Err(format!("Timeout after {timeout}ms")),
+                    };
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            }
+        }
+        _ => Err(format!("Unknown {} action: {action}", D::NAME)),
+    }
+}
@@ -0,0 +1,301 @@
+//! Cookies, web storage and saved sessions.
+//!
+//! Whether a cookie or storage key exists is reported for any site — that
+//! alone tells the agent if the user is logged in. Values (cookie values,
+//! storage items, exported session bundles) are only handed out for domains
+//! listed in `~/.zeptobot/session.json`; an entry covers its subdomains:
+//!
+//! ```json
+//! { "allowed_domains": ["github.com", "intranet.example.com"] }
+//! ```
+//!
+//! Every backend reports cookies in one JSON shape (`name`, `value`,
+//! `domain`, `path`, `expires`, `http_only`, `secure`, `same_site`),
+//! rendered by [`describe_cookie`]:
+//!
+//! ```text
+//! user_session (.github.com/) HttpOnly Secure, expires in 13 days
+//! logged_in (.github.com/) Secure, session
+//! ```
+//!
+//! Session bundles are what `browser_session` exports from the dedicated
+//! Chrome or Firefox profile into `~/.zeptobot/sessions/<name>.json`:
+//!
+//! ```json
+//! {
+//!   "saved_at": 1760000000,
+//!   "cookies": [{ "name": "user_session", "domain": ".github.com", ... }],
+//!   "local_storage": { "https://github.com": { "theme": "dark" } }
+//! }
+//! ```
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+fn home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".into()))
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Domains whose cookie and storage values may be read or exported.
+    pub allowed_domains: Vec<String>,
+}
+
+impl SessionConfig {
+    pub fn path() -> PathBuf {
+        home().join(".zeptobot").join("session.json")
+    }
+
+    /// Load the config file; without one no values are handed out.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| format!("Invalid session config {}: {e}", path.display())),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Whether values for `domain` (a host or a cookie domain like
+    /// `.github.com`) may be handed out.
+    pub fn allows(&self, domain: &str) -> bool {
+        let domain = bare(domain);
+        self.allowed_domains.iter().any(|entry| {
+            let entry = bare(entry.trim_start_matches("*."));
+            !entry.is_empty() && is_within(&domain, &entry)
+        })
+    }
+
+    /// The note shown in place of values for a domain that isn't allowed.
+    pub fn hidden_note(domain: &str) -> String {
+        format!(
+            "Values are hidden — add \"{}\" to \"allowed_domains\" in {} to read them",
+            bare(domain),
+            Self::path().display()
+        )
+    }
+}
+
+/// `.GitHub.com` → `github.com`.
+fn bare(domain: &str) -> String {
+    domain.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+/// `host` is `domain` or one of its subdomains.
+fn is_within(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+/// The host of an http(s) URL.
+pub fn host_of(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Whether a cookie belongs to the site `domain`: set for it, a parent
+/// domain it receives cookies from, or one of its subdomains.
+pub fn cookie_matches(cookie: &Value, domain: &str, name: Option<&str>) -> bool {
+    let cookie_domain = bare(cookie.get("domain").and_then(Value::as_str).unwrap_or(""));
+    let domain = bare(domain);
+    (is_within(&domain, &cookie_domain) || is_within(&cookie_domain, &domain))
+        && name.is_none_or(|n| cookie.get("name").and_then(Value::as_str) == Some(n))
+}
+
+/// A cookie from CDP (`Network.Cookie`) or agent-browser (Playwright's
+/// cookie, which marks session cookies with `expires: -1`) in the shared shape.
+pub fn from_cdp(cookie: &Value) -> Value {
+    let session = cookie.get("session").and_then(Value::as_bool).unwrap_or(false)
+        || cookie.get("expires").and_then(Value::as_f64).is_none_or(|e| e < 0.0);
+    json!({
+        "name": cookie["name"],
+        "value": cookie["value"],
+        "domain": cookie["domain"],
+        "path": cookie["path"],
+        "expires": if session { Value::Null } else { cookie["expires"].clone() },
+        "http_only": cookie.get("httpOnly").and_then(Value::as_bool).unwrap_or(false),
+        "secure": cookie.get("secure").and_then(Value::as_bool).unwrap_or(false),
+        "same_site": cookie.get("sameSite").and_then(Value::as_str).map(str::to_ascii_lowercase),
+    })
+}
+
+/// A cookie in the shared shape as a CDP `Network.CookieParam`.
+pub fn to_cdp(cookie: &Value) -> Value {
+    let mut out = json!({
+        "name": cookie["name"],
+        "value": cookie["value"],
+        "domain": cookie["domain"],
+        "path": cookie["path"],
+        "httpOnly": cookie.get("http_only").and_then(Value::as_bool).unwrap_or(false),
+        "secure": cookie.get("secure").and_then(Value::as_bool).unwrap_or(false),
+    });
+    if let Some(expires) = cookie.get("expires").and_then(Value::as_f64) {
+        out["expires"] = json!(expires);
+    }
+    let same_site = match cookie.get("same_site").and_then(Value::as_str) {
+        Some("strict") => Some("Strict"),
+        Some("lax") => Some("Lax"),
+        Some("none") => Some("None"),
+        _ => None,
+    };
+    if let Some(same_site) = same_site {
+        out["sameSite"] = json!(same_site);
+    }
+    out
+}
+
+/// A WebDriver BiDi `network.Cookie` in the shared shape.
+pub fn from_bidi(cookie: &Value) -> Value {
+    let value = match &cookie["value"] {
+        v if v["type"] == "base64" => Value::Null,
+        v => v["value"].clone(),
+    };
+    json!({
+        "name": cookie["name"],
+        "value": value,
+        "domain": cookie["domain"],
+        "path": cookie["path"],
+        "expires": cookie.get("expiry").filter(|e| e.is_number()).cloned().unwrap_or(Value::Null),
+        "http_only": cookie.get("httpOnly").and_then(Value::as_bool).unwrap_or(false),
+        "secure": cookie.get("secure").and_then(Value::as_bool).unwrap_or(false),
+        "same_site": cookie.get("sameSite").and_then(Value::as_str).filter(|s| *s != "default"),
+    })
+}
+
+/// A cookie in the shared shape as a BiDi `storage.PartialCookie`.
+pub fn to_bidi(cookie: &Value) -> Value {
+    let mut out = json!({
+        "name": cookie["name"],
+        "value": { "type": "string", "value": cookie["value"].as_str().unwrap_or_default() },
+        "domain": cookie["domain"],
+        "path": cookie.get("path").and_then(Value::as_str).unwrap_or("/"),
+        "httpOnly": cookie.get("http_only").and_then(Value::as_bool).unwrap_or(false),
+        "secure": cookie.get("secure").and_then(Value::as_bool).unwrap_or(false),
+    });
+    if let Some(expires) = cookie.get("expires").and_then(Value::as_f64) {
+        out["expiry"] = json!(expires as u64);
+    }
+    if let Some(same_site @ ("strict" | "lax" | "none")) = cookie.get("same_site").and_then(Value::as_str) {
+        out["sameSite"] = json!(same_site);
+    }
+    out
+}
+
+/// One line for a cookie, without its value.
+pub fn describe_cookie(cookie: &Value) -> String {
+    let s = |k: &str| cookie.get(k).and_then(Value::as_str).unwrap_or("");
+    let flag = |k: &str| cookie.get(k).and_then(Value::as_bool) == Some(true);
+    let mut out = format!("{} ({}{})", s("name"), s("domain"), s("path"));
+    if flag("http_only") {
+        out.push_str(" HttpOnly");
+    }
+    if flag("secure") {
+        out.push_str(" Secure");
+    }
+    match cookie.get("expires").and_then(Value::as_f64) {
+        None => out.push_str(", session"),
+        Some(at) => {
+            let left = at - now_secs();
+            if left <= 0.0 {
+                out.push_str(", expired");
+            } else if left < 86_400.0 {
+                out.push_str(&format!(", expires in {} h", (left / 3600.0).ceil()));
+            } else {
+                out.push_str(&format!(", expires in {} days", (left / 86_400.0).floor()));
+            }
+        }
+    }
+    out
+}
+
+/// `~/.zeptobot/sessions/<name>.json`, for names of letters, digits, `-` and `_`.
+pub fn bundle_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid session name '{name}' — use letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(home().join(".zeptobot").join("sessions").join(format!("{name}.json")))
+}
+
+/// A bundle holding only the cookies and origins `config` allows, narrowed
+/// to `domains` when given.
+pub fn filter_bundle(bundle: &Value, config: &SessionConfig, domains: &[String]) -> Value {
+    let wanted = |domain: &str| {
+        config.allows(domain)
+            && (domains.is_empty() || domains.iter().any(|d| is_within(&bare(domain), &bare(d))))
+    };
+    let cookies: Vec<Value> = bundle
+        .get("cookies")
+        .and_then(Value::as_array)
+        .map(|all| {
+            all.iter()
+                .filter(|c| wanted(c.get("domain").and_then(Value::as_str).unwrap_or("")))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let local_storage: Map<String, Value> = bundle
+        .get("local_storage")
+        .and_then(Value::as_object)
+        .map(|all| {
+            all.iter()
+                .filter(|(origin, _)| host_of(origin).is_some_and(|h| wanted(&h)))
+                .map(|(origin, items)| (origin.clone(), items.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    json!({
+        "saved_at": bundle.get("saved_at").cloned().unwrap_or_else(|| json!(now_secs() as u64)),
+        "cookies": cookies,
+        "local_storage": local_storage,
+    })
+}
+
+/// e.g. `12 cookies for github.com, google.com; localStorage of 1 origin`.
+pub fn describe_bundle(bundle: &Value) -> String {
+    let cookies = bundle.get("cookies").and_then(Value::as_array).cloned().unwrap_or_default();
+    let mut domains: Vec<String> = cookies
+        .iter()
+        .map(|c| bare(c.get("domain").and_then(Value::as_str).unwrap_or("")))
+        .collect();
+    domains.sort();
+    domains.dedup();
+    let origins = bundle.get("local_storage").and_then(Value::as_object).map_or(0, Map::len);
+    let mut out = format!(
+        "{} cookie{}",
+        cookies.len(),
+        if cookies.len() == 1 { "" } else { "s" }
+    );
+    if !domains.is_empty() {
+        out.push_str(&format!(" for {}", domains.join(", ")));
+    }
+    if origins > 0 {
+        out.push_str(&format!(
+            "; localStorage of {origins} origin{}",
+            if origins == 1 { "" } else { "s" }
+        ));
+    }
+    out
+}
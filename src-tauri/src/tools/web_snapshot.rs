@@ -0,0 +1,271 @@
+//! Compact role/name outline of a web page's accessibility tree.
+//!
+//! Backends fetch the raw tree with CDP `Accessibility.getFullAXTree` (the
+//! CDP backend directly, the extension through `chrome.debugger`) and tag
+//! interactive nodes with element refs. Everything here is pure JSON work on
+//! the protocol's `AXNode` objects:
+//!
+//! ```text
+//! document "Inbox"
+//!   navigation "Mail"
+//!     link "Compose" [ref=e1:k3f9]
+//!   main
+//!     heading "Inbox" (level=1)
+//!     checkbox "Select all" [ref=e2:k3f9] (checked=false)
+//!     textbox "Search mail" [ref=e3:k3f9] value="invoices"
+//! ```
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// Roles that get a clickable ref.
+pub const INTERACTIVE_ROLES: &[&str] = &[
+    "button",
+    "link",
+    "textbox",
+    "searchbox",
+    "combobox",
+    "listbox",
+    "option",
+    "checkbox",
+    "radio",
+    "switch",
+    "slider",
+    "spinbutton",
+    "tab",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "treeitem",
+];
+
+/// Roles kept for context when only interactive nodes are requested.
+const STRUCTURE_ROLES: &[&str] = &[
+    "banner",
+    "navigation",
+    "main",
+    "complementary",
+    "contentinfo",
+    "region",
+    "form",
+    "search",
+    "dialog",
+    "alertdialog",
+    "heading",
+];
+
+/// Roles that never print; their children move up a level.
+const TRANSPARENT_ROLES: &[&str] = &["generic", "none", "presentation", "LineBreak"];
+
+/// Upper bound on refs tagged per snapshot (each costs a protocol round trip).
+pub const MAX_REFS: usize = 300;
+
+const MAX_NAME_CHARS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct SnapshotOptions {
+    /// Only interactive nodes plus landmarks/headings/dialogs.
+    pub interactive_only: bool,
+    /// Stop after this many lines.
+    pub max_lines: usize,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            interactive_only: false,
+            max_lines: 500,
+        }
+    }
+}
+
+fn ax_str<'a>(node: &'a Value, key: &str) -> &'a str {
+    node.get(key)
+        .and_then(|v| v.get("value"))
+        .and_then(Value::as_str)
+        .unwrap_or("")
+}
+
+fn role(node: &Value) -> &str {
+    ax_str(node, "role")
+}
+
+fn backend_id(node: &Value) -> Option<i64> {
+    node.get("backendDOMNodeId").and_then(Value::as_i64)
+}
+
+fn ignored(node: &Value) -> bool {
+    node.get("ignored").and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Backend DOM node ids of interactive nodes that should get a ref.
+pub fn ref_candidates(nodes: &[Value]) -> Vec<i64> {
+    nodes
+        .iter()
+        .filter(|n| !ignored(n) && INTERACTIVE_ROLES.contains(&role(n)))
+        .filter_map(backend_id)
+        .take(MAX_REFS)
+        .collect()
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    if s.chars().count() <= max {
+        return s;
+    }
+    let cut: String = s.chars().take(max).collect();
+    format!("{cut}…")
+}
+
+/// States worth showing, e.g. `checked=false`, `disabled`, `level=2`.
+fn states(node: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    let Some(props) = node.get("properties").and_then(Value::as_array) else {
+        return out;
+    };
+    for prop in props {
+        let name = prop.get("name").and_then(Value::as_str).unwrap_or("");
+        let value = prop.get("value").and_then(|v| v.get("value"));
+        match (name, value) {
+            ("checked" | "pressed" | "expanded" | "selected", Some(v)) => match v {
+                Value::Bool(true) => out.push(name.to_string()),
+                Value::String(s) if s == "true" => out.push(name.to_string()),
+                Value::String(s) if s == "mixed" => out.push(format!("{name}=mixed")),
+                // Explicit false matters for toggles, not for selection.
+                _ if name != "selected" => out.push(format!("{name}=false")),
+                _ => {}
+            },
+            ("disabled" | "required" | "readonly" | "modal" | "focused", Some(Value::Bool(true))) => {
+                out.push(name.to_string())
+            }
+            ("invalid", Some(Value::String(s))) if s != "false" => out.push("invalid".into()),
+            ("level", Some(v)) => out.push(format!("level={v}")),
+            _ => {}
+        }
+    }
+    out
+}
+
+struct Renderer<'a> {
+    by_id: HashMap<&'a str, &'a Value>,
+    refs: &'a HashMap<i64, String>,
+    opts: &'a SnapshotOptions,
+    lines: Vec<String>,
+    omitted: usize,
+}
+
+impl<'a> Renderer<'a> {
+    fn visit(&mut self, node: &'a Value, depth: usize, parent_name: &str) {
+        let role = role(node);
+        if role == "InlineTextBox" {
+            return;
+        }
+        let name = ax_str(node, "name");
+
+        let printable = !ignored(node)
+            && !TRANSPARENT_ROLES.contains(&role)
+            && match role {
+                "StaticText" => {
+                    !self.opts.interactive_only && !name.trim().is_empty() && name != parent_name
+                }
+                "RootWebArea" => true,
+                _ if self.opts.interactive_only => {
+                    INTERACTIVE_ROLES.contains(&role) || STRUCTURE_ROLES.contains(&role)
+                }
+                _ => true,
+            };
+
+        let child_depth = if printable {
+            if self.lines.len() >= self.opts.max_lines {
+                self.omitted += 1;
+            } else {
+                self.lines.push(self.line(node, role, name, depth));
+            }
+            depth + 1
+        } else {
+            depth
+        };
+
+        // Children repeat their parent's name as StaticText; skip those.
+        let name_for_children = if printable { name } else { parent_name };
+        let children = node
+            .get("childIds")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for child in children {
+            if let Some(c) = child.as_str().and_then(|id| self.by_id.get(id)).copied() {
+                self.visit(c, child_depth, name_for_children);
+            }
+        }
+    }
+
+    fn line(&self, node: &Value, role: &str, name: &str, depth: usize) -> String {
+        let role = match role {
+            "RootWebArea" => "document",
+            "StaticText" => "text",
+            r => r,
+        };
+        let mut line = format!("{}{role}", "  ".repeat(depth));
+        if !name.trim().is_empty() {
+            line.push_str(&format!(" \"{}\"", truncate(name, MAX_NAME_CHARS)));
+        }
+        if let Some(r) = backend_id(node).and_then(|id| self.refs.get(&id)) {
+            line.push_str(&format!(" [ref={r}]"));
+        }
+        let value = ax_str(node, "value");
+        if !value.is_empty() && value != name {
+            line.push_str(&format!(" value=\"{}\"", truncate(value, MAX_NAME_CHARS)));
+        }
+        let states = states(node);
+        if !states.is_empty() {
+            line.push_str(&format!(" ({})", states.join(", ")));
+        }
+        line
+    }
+}
+
+/// Render raw `AXNode`s as an indented outline, annotating nodes whose
+/// backend DOM id appears in `refs`.
+pub fn render(nodes: &[Value], refs: &HashMap<i64, String>, opts: &SnapshotOptions) -> String {
+    let mut renderer = Renderer {
+        by_id: nodes
+            .iter()
+            .filter_map(|n| Some((n.get("nodeId")?.as_str()?, n)))
+            .collect(),
+        refs,
+        opts,
+        lines: Vec::new(),
+        omitted: 0,
+    };
+    // The root is the node without a parent (the RootWebArea).
+    if let Some(root) = nodes.iter().find(|n| n.get("parentId").is_none()) {
+        renderer.visit(root, 0, "");
+    }
+
+    let mut out = renderer.lines.join("\n");
+    if renderer.omitted > 0 {
+        let hint = if opts.interactive_only {
+            "raise max_lines"
+        } else {
+            "use interactive_only=true or raise max_lines"
+        };
+        out.push_str(&format!(
+            "\n… {} more node(s) not shown — {hint}",
+            renderer.omitted
+        ));
+    }
+    out
+}
+
+/// Parse the `refs` object returned by a backend (`{"<backendNodeId>": "e3:k3f9"}`).
+pub fn parse_refs(raw: Option<&Value>) -> HashMap<i64, String> {
+    raw.and_then(Value::as_object)
+        .map(|m| {
+            m.iter()
+                .filter_map(|(k, v)| Some((k.parse().ok()?, v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,104 @@
+//! Actionability checks (`window.__zb.actionable`) against a fixture page.
+
+mod common;
+
+use chromiumoxide::Page;
+use serde_json::Value;
+
+async fn actionable(page: &Page, selector: &str, action: &str) -> Value {
+    common::eval_dom(
+        page,
+        &format!("window.__zb.actionable({selector:?}, {action:?})"),
+    )
+    .await
+}
+
+async fn failed_check(page: &Page, selector: &str, action: &str) -> String {
+    let r = actionable(page, selector, action).await;
+    assert_eq!(r["ok"], false, "{selector} should not be actionable: {r}");
+    r["check"].as_str().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+async fn plain_button_is_clickable() {
+    let Some(browser) = common::launch().await else { return };
+    let page = browser.open("actionability.html").await;
+
+    let r = actionable(&page, "#ok", "click").await;
+    assert_eq!(r["ok"], true, "{r}");
+    assert!(r["x"].as_i64().unwrap() > 0 && r["y"].as_i64().unwrap() > 0);
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn reports_the_failing_check() {
+    let Some(browser) = common::launch().await else { return };
+    let page = browser.open("actionability.html").await;
+
+    assert_eq!(failed_check(&page, "#missing", "click").await, "attached");
+    assert_eq!(failed_check(&page, "#hidden", "click").await, "visible");
+    assert_eq!(failed_check(&page, "#invisible", "click").await, "visible");
+    assert_eq!(failed_check(&page, "#disabled", "click").await, "enabled");
+    assert_eq!(failed_check(&page, "#aria-disabled", "click").await, "enabled");
+    assert_eq!(failed_check(&page, "#moving", "click").await, "stable");
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn covered_element_names_what_covers_it() {
+    let Some(browser) = common::launch().await else { return };
+    let page = browser.open("actionability.html").await;
+
+    let r = actionable(&page, "#covered", "click").await;
+    assert_eq!(r["check"], "hit_target", "{r}");
+    let error = r["error"].as_str().unwrap();
+    assert!(error.contains("#modal-backdrop"), "{error}");
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn offscreen_element_is_scrolled_into_view() {
+    let Some(browser) = common::launch().await else { return };
+    let page = browser.open("actionability.html").await;
+
+    let r = actionable(&page, "#far", "click").await;
+    assert_eq!(r["ok"], true, "{r}");
+    let scrolled: f64 = page
+        .evaluate("window.scrollY")
+        .await
+        .unwrap()
+        .into_value()
+        .unwrap();
+    assert!(scrolled > 0.0);
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn type_requires_an_editable_field() {
+    let Some(browser) = common::launch().await else { return };
+    let page = browser.open("actionability.html").await;
+
+    assert_eq!(actionable(&page, "#name", "type").await["ok"], true);
+    assert_eq!(failed_check(&page, "#readonly", "type").await, "editable");
+    assert_eq!(failed_check(&page, "#checkbox", "type").await, "editable");
+    assert_eq!(failed_check(&page, "#ok", "type").await, "editable");
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn element_becomes_actionable_after_a_delay() {
+    let Some(browser) = common::launch().await else { return };
+    let page = browser.open("actionability.html").await;
+
+    assert_eq!(failed_check(&page, "#late", "click").await, "enabled");
+    let mut ready = false;
+    for _ in 0..40 {
+        if actionable(&page, "#late", "click").await["ok"] == true {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert!(ready, "#late never became clickable");
+    browser.close().await;
+}
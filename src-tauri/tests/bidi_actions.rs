@@ -0,0 +1,92 @@
+//! Every Firefox (WebDriver BiDi) backend action end to end: the shared
+//! suite in `common::actions` against headless Firefox. The last test
+//! launches the dedicated Firefox itself, headless.
+
+mod common;
+
+use common::{actions, sandbox_home};
+use serde_json::json;
+use zeptobot_lib::tools::browser::BrowserState;
+use zeptobot_lib::tools::web_bidi::{self, BidiConn};
+
+/// A state on `shop.html` served over HTTP, and the server's base URL.
+async fn shop(firefox: &common::TestFirefox) -> (BrowserState, String) {
+    let base = common::serve_fixtures(&[]).await;
+    let state = firefox.bidi_state_at(&format!("{base}/shop.html")).await;
+    (state, base)
+}
+
+#[tokio::test]
+async fn tabs_and_history() {
+    let home = sandbox_home();
+    let Some(firefox) = common::launch_firefox(&home.join("downloads")).await else { return };
+    let (state, base) = shop(&firefox).await;
+    actions::tabs_and_history(&state, &base).await;
+    firefox.close().await;
+}
+
+#[tokio::test]
+async fn reading_the_page() {
+    let home = sandbox_home();
+    let Some(firefox) = common::launch_firefox(&home.join("downloads")).await else { return };
+    let (state, _) = shop(&firefox).await;
+    actions::reading_the_page(&state).await;
+    firefox.close().await;
+}
+
+#[tokio::test]
+async fn interacting_with_the_form() {
+    let home = sandbox_home();
+    let Some(firefox) = common::launch_firefox(&home.join("downloads")).await else { return };
+    let (state, _) = shop(&firefox).await;
+    actions::interacting_with_the_form(&state, &home).await;
+    firefox.close().await;
+}
+
+#[tokio::test]
+async fn requests_errors_dialogs_and_downloads() {
+    let home = sandbox_home();
+    let Some(firefox) = common::launch_firefox(&home.join("downloads")).await else { return };
+    let (state, _) = shop(&firefox).await;
+    actions::requests_errors_dialogs_and_downloads(&state, &home).await;
+    firefox.close().await;
+}
+
+#[tokio::test]
+async fn cookies_storage_and_session_bundles() {
+    let home = sandbox_home();
+    let Some(firefox) = common::launch_firefox(&home.join("downloads")).await else { return };
+    let (state, base) = shop(&firefox).await;
+    actions::cookies_storage_and_session_bundles(&state, &base).await;
+    firefox.close().await;
+}
+
+#[tokio::test]
+async fn dedicated_firefox_launches_headless() {
+    let home = sandbox_home();
+    if web_bidi::find_firefox().is_none() {
+        eprintln!("skipping: no Firefox for the launch test");
+        return;
+    }
+    let addr = format!("127.0.0.1:{}", web_bidi::PORT);
+    if tokio::net::TcpStream::connect(&addr).await.is_ok() {
+        eprintln!("skipping: something already listens on the Firefox remote agent port");
+        return;
+    }
+
+    let state = BrowserState::new();
+    let tabs = state.launch_bidi(true).await.expect("launch headless Firefox");
+    assert!(tabs >= 1, "{tabs} tabs");
+    assert!(home.join(".zeptobot/firefox-profile/user.js").is_file());
+
+    let base = common::serve_fixtures(&[]).await;
+    let went = state.run("navigate", json!({ "url": format!("{base}/shop.html") })).await.expect("navigate");
+    assert!(went.contains("Trail Shop"), "{went}");
+
+    // Firefox allows one BiDi session: end ours, then shut Firefox down from a new one.
+    drop(state);
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    if let Ok(conn) = BidiConn::connect(&format!("ws://{addr}/session")).await {
+        let _ = conn.send("browser.close", json!({})).await;
+    }
+}
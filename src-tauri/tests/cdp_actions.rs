@@ -0,0 +1,85 @@
+//! Every CDP backend action end to end: the shared suite in
+//! `common::actions` against headless Chrome. The last test launches the
+//! dedicated Chrome itself, headless.
+
+mod common;
+
+use common::{actions, sandbox_home};
+use serde_json::json;
+use zeptobot_lib::tools::browser::{find_chrome, BrowserState};
+
+/// A state on `shop.html` served over HTTP, and the server's base URL.
+async fn shop(browser: &common::TestBrowser) -> (BrowserState, String) {
+    let base = common::serve_fixtures(&[]).await;
+    let state = browser.cdp_state_at(&format!("{base}/shop.html")).await;
+    (state, base)
+}
+
+#[tokio::test]
+async fn tabs_and_history() {
+    let Some(browser) = common::launch().await else { return };
+    let (state, base) = shop(&browser).await;
+    actions::tabs_and_history(&state, &base).await;
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn reading_the_page() {
+    let Some(browser) = common::launch().await else { return };
+    let (state, _) = shop(&browser).await;
+    actions::reading_the_page(&state).await;
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn interacting_with_the_form() {
+    let home = sandbox_home();
+    let Some(browser) = common::launch().await else { return };
+    let (state, _) = shop(&browser).await;
+    actions::interacting_with_the_form(&state, &home).await;
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn requests_errors_dialogs_and_downloads() {
+    let home = sandbox_home();
+    let Some(browser) = common::launch().await else { return };
+    let (state, _) = shop(&browser).await;
+    actions::requests_errors_dialogs_and_downloads(&state, &home).await;
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn cookies_storage_and_session_bundles() {
+    let Some(browser) = common::launch().await else { return };
+    let (state, base) = shop(&browser).await;
+    actions::cookies_storage_and_session_bundles(&state, &base).await;
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn dedicated_chrome_launches_headless() {
+    let home = sandbox_home();
+    if find_chrome().is_none() {
+        eprintln!("skipping: no Chrome for the launch test");
+        return;
+    }
+    if tokio::net::TcpStream::connect("127.0.0.1:9222").await.is_ok() {
+        eprintln!("skipping: something already listens on the DevTools port");
+        return;
+    }
+    let state = BrowserState::new();
+    let tabs = state.launch_cdp(true).await.expect("launch headless Chrome");
+    assert!(tabs >= 1, "{tabs} tabs");
+    assert!(home.join(".zeptobot/chrome-profile").is_dir());
+
+    let base = common::serve_fixtures(&[]).await;
+    let went = state.run("navigate", json!({ "url": format!("{base}/shop.html") })).await.expect("navigate");
+    assert!(went.contains("Trail Shop"), "{went}");
+
+    // Shut the launched Chrome down over the same endpoint.
+    if let Ok((mut chrome, mut handler)) = chromiumoxide::Browser::connect("http://127.0.0.1:9222").await {
+        tokio::spawn(async move { while futures_util::StreamExt::next(&mut handler).await.is_some() {} });
+        let _ = chrome.close().await;
+    }
+}
@@ -0,0 +1,178 @@
+//! The backend action suite: every `BrowserState` action end to end on
+//! `shop.html` served over HTTP (`common::serve_fixtures`), so fetches,
+//! cookies and storage behave as on a real site. `cdp_actions.rs` and
+//! `bidi_actions.rs` run it against Chrome and Firefox; both must pass the
+//! same assertions.
+
+use std::path::PathBuf;
+
+use base64::Engine;
+use serde_json::{json, Value};
+use zeptobot_lib::tools::browser::{extract_spec, BrowserState};
+use zeptobot_lib::tools::web_session::{self, SessionConfig};
+
+async fn js(state: &BrowserState, code: &str) -> String {
+    state.run("execute_js", json!({ "code": code })).await.expect("execute_js")
+}
+
+async fn out(state: &BrowserState) -> String {
+    state.run("read", json!({ "selector": "#out" })).await.expect("read #out")
+}
+
+async fn tabs(state: &BrowserState) -> Vec<Value> {
+    let raw = state.run("get_tabs", json!({})).await.expect("get_tabs");
+    serde_json::from_str(&raw).expect("tabs JSON")
+}
+
+/// Tab management and history. `state` starts on `{base}/shop.html`.
+pub async fn tabs_and_history(state: &BrowserState, base: &str) {
+    let before = tabs(state).await;
+    let current = before.iter().find(|t| t["active"] == true).expect("current tab").clone();
+    assert!(current["url"].as_str().unwrap().ends_with("/shop.html"), "{current}");
+
+    let opened = state
+        .run("new_tab", json!({ "url": format!("{base}/article.html") }))
+        .await
+        .expect("new_tab");
+    let id = opened.split_whitespace().nth(3).expect("tab id").to_string();
+    assert_eq!(tabs(state).await.len(), before.len() + 1);
+
+    let switched = state.run("switch_tab", json!({ "tab": current["id"] })).await.expect("switch_tab");
+    assert!(switched.contains("Trail Shop"), "{switched}");
+    state.run("close_tab", json!({ "tab": id })).await.expect("close_tab");
+    assert!(tabs(state).await.iter().all(|t| t["id"] != id.as_str()));
+
+    let went = state
+        .run("navigate", json!({ "url": format!("{base}/article.html"), "wait_until": "networkidle" }))
+        .await
+        .expect("navigate");
+    assert!(went.contains("Tide Tables"), "{went}");
+    let back = state.run("go_back", json!({})).await.expect("go_back");
+    assert!(back.contains("/shop.html"), "{back}");
+    let forward = state.run("go_forward", json!({})).await.expect("go_forward");
+    assert!(forward.contains("/article.html"), "{forward}");
+    let reloaded = state.run("reload", json!({})).await.expect("reload");
+    assert!(reloaded.starts_with(&format!("Reloaded {base}/article.html")), "{reloaded}");
+}
+
+/// Reading, snapshots, extraction and screenshots.
+pub async fn reading_the_page(state: &BrowserState) {
+    assert_eq!(js(state, "document.title").await, "Trail Shop");
+    let heading = state.run("read", json!({ "selector": "h1" })).await.expect("read");
+    assert_eq!(heading, "Trail Shop");
+    let markdown = state.run("read", json!({ "format": "markdown", "main_content": false })).await.expect("markdown");
+    assert!(markdown.contains("# Trail Shop"), "{markdown}");
+
+    let snapshot = state.run("snapshot", json!({})).await.expect("snapshot");
+    assert!(snapshot.contains("Save order"), "{snapshot}");
+    let raw = state.run("list_elements", json!({ "filter": "save" })).await.expect("list_elements");
+    let elements: Vec<Value> = serde_json::from_str(&raw).expect("elements JSON");
+    assert_eq!(elements.len(), 1, "{raw}");
+    assert_eq!(elements[0]["text"], "Save order");
+
+    let spec = extract_spec(&json!({ "table": "#items" })).expect("spec");
+    let raw = state.run("extract", json!({ "spec": spec })).await.expect("extract");
+    let table: Value = serde_json::from_str(&raw).expect("extract JSON");
+    assert_eq!(table["items"][0], json!({ "Item": "Kayak", "Price": 499 }));
+
+    let found = state.run("wait_for", json!({ "text": "Paddle" })).await.expect("wait_for");
+    assert!(found.contains("Paddle"), "{found}");
+
+    let raw = state.run("screenshot", json!({ "selector": "#items" })).await.expect("screenshot");
+    let shot: Value = serde_json::from_str(&raw).expect("screenshot JSON");
+    let png = base64::engine::general_purpose::STANDARD
+        .decode(shot["data"].as_str().unwrap())
+        .expect("base64");
+    assert!(png.starts_with(b"\x89PNG"), "not a PNG");
+}
+
+/// Typing, filling, keys, selects, checkboxes, hover, click, scroll and upload.
+pub async fn interacting_with_the_form(state: &BrowserState, home: &std::path::Path) {
+    state.run("type", json!({ "selector": "#name", "value": "Ada" })).await.expect("type");
+    assert_eq!(js(state, "document.getElementById('name').value").await, "Ada");
+    state.run("fill", json!({ "selector": "#notes", "value": "Fragile" })).await.expect("fill");
+    assert_eq!(js(state, "document.getElementById('notes').value").await, "Fragile");
+    state.run("press_key", json!({ "selector": "#notes", "key": "Enter" })).await.expect("press_key");
+    assert_eq!(out(state).await, "Notes sent");
+
+    state
+        .run("select_option", json!({ "selector": "#plan", "values": ["Pro"] }))
+        .await
+        .expect("select_option");
+    assert_eq!(js(state, "document.getElementById('plan').value").await, "pro");
+    state.run("check", json!({ "selector": "#gift" })).await.expect("check");
+    assert_eq!(js(state, "String(document.getElementById('gift').checked)").await, "true");
+
+    state.run("hover", json!({ "selector": "#more" })).await.expect("hover");
+    assert_eq!(out(state).await, "Menu open");
+    state.run("click", json!({ "text": "Save order" })).await.expect("click");
+    state.run("wait_for", json!({ "text": "Saved: ok" })).await.expect("saved");
+
+    state.run("scroll", json!({ "selector": "#pane", "to": "bottom" })).await.expect("scroll");
+    let top: f64 = js(state, "String(document.getElementById('pane').scrollTop)").await.parse().unwrap();
+    assert!(top > 0.0, "pane did not scroll");
+
+    let receipt = home.join("uploads/receipt.pdf").to_string_lossy().into_owned();
+    let attached = state
+        .run("upload", json!({ "selector": "#receipt", "files": [receipt] }))
+        .await
+        .expect("upload");
+    assert_eq!(attached, "Attached 1 file: receipt.pdf");
+    assert_eq!(out(state).await, "receipt.pdf");
+}
+
+/// Captured requests, console errors, dialogs and downloads.
+pub async fn requests_errors_dialogs_and_downloads(state: &BrowserState, home: &std::path::Path) {
+    state.run("click", json!({ "selector": "#save" })).await.expect("click save");
+    let raw = state
+        .run("wait_for_request", json!({ "url": "*/data.json", "capture_body": true }))
+        .await
+        .expect("wait_for_request");
+    let request: Value = serde_json::from_str(&raw).expect("request JSON");
+    assert_eq!(request["status"], 200);
+    assert!(request["body"].as_str().unwrap().contains("\"ok\""), "{raw}");
+
+    let clicked = state.run("click", json!({ "selector": "#broken" })).await.expect("click broken");
+    assert!(clicked.contains("card declined"), "{clicked}");
+    let raw = state.run("console", json!({ "level": "error" })).await.expect("console");
+    assert!(raw.contains("checkout failed"), "{raw}");
+
+    let clicked = state.run("click", json!({ "selector": "#remove" })).await.expect("click remove");
+    assert!(clicked.contains("confirm dialog \"Remove the kayak?\""), "{clicked}");
+    state.run("dialog", json!({ "action": "accept" })).await.expect("dialog");
+    assert_eq!(out(state).await, "removed");
+
+    state.run("click", json!({ "selector": "#export" })).await.expect("click export");
+    let raw = state
+        .run("wait_for_download", json!({ "filename": "items.csv" }))
+        .await
+        .expect("wait_for_download");
+    let download: Value = serde_json::from_str(&raw).expect("download JSON");
+    let path = PathBuf::from(download["path"].as_str().unwrap());
+    assert!(path.starts_with(home.join("downloads")), "{path:?}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "kayak,499");
+}
+
+/// Cookies, localStorage and a session bundle round trip.
+pub async fn cookies_storage_and_session_bundles(state: &BrowserState, base: &str) {
+    let raw = state.run("cookies", json!({ "name": "visited" })).await.expect("cookies");
+    let found: Value = serde_json::from_str(&raw).expect("cookies JSON");
+    assert_eq!(found["domain"], "127.0.0.1");
+    assert_eq!(found["cookies"][0]["value"], "yes");
+    let raw = state.run("storage", json!({ "key": "cart" })).await.expect("storage");
+    assert!(raw.contains("2 items"), "{raw}");
+
+    let raw = state.run("export_session", json!({})).await.expect("export_session");
+    let config = SessionConfig { allowed_domains: vec!["127.0.0.1".into()] };
+    let bundle = web_session::filter_bundle(&serde_json::from_str(&raw).unwrap(), &config, &[]);
+    assert_eq!(bundle["local_storage"][base]["cart"], "2 items");
+
+    state.run("cookies", json!({ "clear": true })).await.expect("clear cookies");
+    state.run("storage", json!({ "clear": true })).await.expect("clear storage");
+    let raw = state.run("import_session", json!({ "bundle": bundle })).await.expect("import_session");
+    assert!(raw.contains("\"not_open\":[]"), "{raw}");
+    let raw = state.run("cookies", json!({ "name": "visited" })).await.expect("cookies after import");
+    assert!(raw.contains("\"yes\""), "{raw}");
+    let raw = state.run("storage", json!({ "key": "cart" })).await.expect("storage after import");
+    assert!(raw.contains("2 items"), "{raw}");
+}
@@ -0,0 +1,260 @@
+//! Shared helpers for the browser integration tests.
+//!
+//! Tests launch a headless Chrome through chromiumoxide and load pages from
+//! `tests/fixtures/`, either as `file://` URLs or from [`serve_fixtures`].
+//! When no Chrome/Chromium binary is installed they print a note and return
+//! early, so `cargo test` stays green without a browser. [`launch_firefox`]
+//! does the same for the Firefox backend.
+//!
+//! Every test binary runs with `$HOME` in [`sandbox_home`].
+
+#![allow(dead_code)]
+
+pub mod actions;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chromiumoxide::{Browser, BrowserConfig, Page};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use zeptobot_lib::tools::browser::{BrowserState, CHROME_ARGS_ENV};
+use zeptobot_lib::tools::web_bidi;
+
+/// The page-side helpers every backend injects (`window.__zb`).
+pub const DOM_JS: &str = include_str!("../../../extension/dom.js");
+
+pub struct TestBrowser {
+    pub browser: Browser,
+    _handler: JoinHandle<()>,
+}
+
+/// Launch headless Chrome, or `None` (with a note on stderr) if it isn't available.
+pub async fn launch() -> Option<TestBrowser> {
+    let config = match BrowserConfig::builder().no_sandbox().build() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("skipping: no Chrome for browser tests ({e})");
+            return None;
+        }
+    };
+    let (browser, mut handler) = match Browser::launch(config).await {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("skipping: Chrome failed to launch ({e})");
+            return None;
+        }
+    };
+    let handle = tokio::spawn(async move { while handler.next().await.is_some() {} });
+    Some(TestBrowser { browser, _handler: handle })
+}
+
+/// `file://` URL of a page in `tests/fixtures/`.
+pub fn fixture_url(name: &str) -> String {
+    format!("file://{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// The scratch `$HOME` every test runs in: `uploads/` and `downloads/`
+/// configured in `~/.zeptobot/files.json`, a file outside the uploads, and
+/// room for the dedicated browser profiles.
+pub fn sandbox_home() -> PathBuf {
+    std::env::temp_dir().join(format!("zeptobot-tests-{}", std::process::id()))
+}
+
+/// Set up [`sandbox_home`] and point the environment at it. This runs
+/// before `main`, while the process has one thread: setting variables
+/// while test threads read them is a data race.
+#[ctor::ctor]
+fn sandbox_env() {
+    let home = sandbox_home();
+    std::fs::create_dir_all(home.join("uploads")).unwrap();
+    std::fs::create_dir_all(home.join(".zeptobot")).unwrap();
+    std::fs::write(home.join("uploads/note.txt"), "hello").unwrap();
+    std::fs::write(home.join("uploads/receipt.pdf"), "%PDF-1.4").unwrap();
+    std::fs::write(home.join("secret.txt"), "keep out").unwrap();
+    let config = json!({ "upload_dirs": ["~/uploads"], "download_dir": "~/downloads" });
+    std::fs::write(home.join(".zeptobot/files.json"), config.to_string()).unwrap();
+    std::env::set_var("HOME", &home);
+    // Containers usually run as root, where Chrome needs --no-sandbox.
+    std::env::set_var(CHROME_ARGS_ENV, "--no-sandbox");
+}
+
+/// How [`serve_fixtures`] answers one fixture: `delay_ms` late, with extra
+/// `headers` (whole `Name: value` lines).
+#[derive(Debug, Clone, Copy)]
+pub struct Route {
+    pub path: &'static str,
+    pub delay_ms: u64,
+    pub headers: &'static [&'static str],
+}
+
+/// Serve `tests/fixtures/` over HTTP from a throwaway local server, for
+/// pages that fetch, set cookies or use storage, with `routes` changing how
+/// some paths answer. Returns the base URL.
+pub async fn serve_fixtures(routes: &[Route]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let routes: Arc<[Route]> = routes.into();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_fixture(stream, routes.clone()));
+        }
+    });
+    format!("http://{addr}")
+}
+
+async fn serve_fixture(mut stream: TcpStream, routes: Arc<[Route]>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    // Read the head and any body, so the connection closes cleanly.
+    let path = loop {
+        let Ok(n) = stream.read(&mut chunk).await else { return };
+        if n == 0 {
+            return;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+                .unwrap_or(0usize);
+            if buf.len() >= end + 4 + length {
+                break text.split_whitespace().nth(1).unwrap_or("/").to_string();
+            }
+        }
+    };
+    let name = path.split(['?', '#']).next().unwrap_or("").trim_start_matches('/');
+    let file = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let (status, body) = match std::fs::read(&file) {
+        Ok(body) if !name.is_empty() && !name.contains("..") => ("200 OK", body),
+        _ => ("404 Not Found", b"not found".to_vec()),
+    };
+    let mime = match name.rsplit('.').next() {
+        Some("html") => "text/html; charset=utf-8",
+        Some("json") => "application/json",
+        _ => "text/plain",
+    };
+    let mut extra = String::new();
+    if let Some(route) = routes.iter().find(|r| r.path == name) {
+        tokio::time::sleep(Duration::from_millis(route.delay_ms)).await;
+        for header in route.headers {
+            extra.push_str(header);
+            extra.push_str("\r\n");
+        }
+    }
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {mime}\r\nContent-Length: {}\r\n{extra}Connection: close\r\n\r\n",
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(&body).await;
+}
+
+impl TestBrowser {
+    /// Open a fixture page in a new tab.
+    pub async fn open(&self, fixture: &str) -> Page {
+        let page = self
+            .browser
+            .new_page(fixture_url(fixture))
+            .await
+            .expect("open fixture");
+        page.wait_for_navigation().await.expect("fixture load");
+        page
+    }
+
+    /// A `BrowserState` driving this browser through the CDP backend, with
+    /// `fixture` loaded in its current tab.
+    pub async fn cdp_state(&self, fixture: &str) -> BrowserState {
+        self.cdp_state_at(&fixture_url(fixture)).await
+    }
+
+    /// Like [`cdp_state`](Self::cdp_state), with any URL loaded.
+    pub async fn cdp_state_at(&self, url: &str) -> BrowserState {
+        let state = BrowserState::new();
+        state
+            .connect_cdp(self.browser.websocket_address())
+            .await
+            .expect("connect CDP backend");
+        state
+            .run("navigate", serde_json::json!({ "url": url }))
+            .await
+            .expect("navigate to fixture");
+        state
+    }
+
+    pub async fn close(mut self) {
+        let _ = self.browser.close().await;
+        let _ = self.browser.wait().await;
+    }
+}
+
+/// Evaluate `expr` with `window.__zb` loaded, as the CDP backend does.
+pub async fn eval_dom(page: &Page, expr: &str) -> Value {
+    page.evaluate(format!("{DOM_JS}\n;{expr}"))
+        .await
+        .expect("evaluate")
+        .into_value::<Value>()
+        .unwrap_or(Value::Null)
+}
+
+pub struct TestFirefox {
+    child: std::process::Child,
+    port: u16,
+    _profile: PathBuf,
+}
+
+/// Launch headless Firefox with WebDriver BiDi on a free port and a
+/// throwaway profile saving downloads to `download_dir`, or `None` (with a
+/// note on stderr) if it isn't available.
+pub async fn launch_firefox(download_dir: &std::path::Path) -> Option<TestFirefox> {
+    let Some(bin) = web_bidi::find_firefox() else {
+        eprintln!("skipping: no Firefox for browser tests");
+        return None;
+    };
+    let port = {
+        let probe = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        probe.local_addr().expect("addr").port()
+    };
+    let profile = std::env::temp_dir().join(format!("zeptobot-firefox-{}-{port}", std::process::id()));
+    web_bidi::write_prefs(&profile, download_dir).expect("Firefox profile");
+    let mut child = match web_bidi::launch(&bin, &profile, port, true) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("skipping: Firefox failed to launch ({e})");
+            return None;
+        }
+    };
+    if let Err(e) = web_bidi::wait_ready(&mut child, port, Duration::from_secs(30)).await {
+        eprintln!("skipping: Firefox failed to start ({e})");
+        let _ = child.kill();
+        return None;
+    }
+    Some(TestFirefox { child, port, _profile: profile })
+}
+
+impl TestFirefox {
+    /// A `BrowserState` driving this Firefox through the BiDi backend, with
+    /// `url` loaded in its current tab.
+    pub async fn bidi_state_at(&self, url: &str) -> BrowserState {
+        let state = BrowserState::new();
+        state
+            .connect_bidi(&format!("ws://127.0.0.1:{}/session", self.port))
+            .await
+            .expect("connect BiDi backend");
+        state
+            .run("navigate", serde_json::json!({ "url": url }))
+            .await
+            .expect("navigate to fixture");
+        state
+    }
+
+    pub async fn close(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
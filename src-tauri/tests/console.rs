@@ -0,0 +1,59 @@
+//! Console capture for `browser_console` and the error report on actions.
+
+mod common;
+
+use serde_json::{json, Value};
+
+async fn console(state: &zeptobot_lib::tools::browser::BrowserState, args: Value) -> Vec<Value> {
+    let raw = state.run("console", args).await.expect("console");
+    serde_json::from_str(&raw).expect("console returns a JSON array")
+}
+
+fn texts(entries: &[Value]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|e| format!("{} {}", e["level"].as_str().unwrap_or(""), e["text"].as_str().unwrap_or("")))
+        .collect()
+}
+
+#[tokio::test]
+async fn levels_filter_the_log() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("console.html").await;
+
+    let all = texts(&console(&state, json!({})).await);
+    assert!(all.contains(&"log app booted".to_string()), "{all:?}");
+    assert!(all.contains(&"warning Deprecated option 'legacy'".to_string()), "{all:?}");
+    assert!(all.iter().any(|t| t.starts_with("error") && t.contains("missing-logo.png")), "{all:?}");
+
+    let warnings = texts(&console(&state, json!({ "level": "warning" })).await);
+    assert!(!warnings.iter().any(|t| t.contains("app booted")), "{warnings:?}");
+    assert!(warnings.iter().any(|t| t.contains("Deprecated")), "{warnings:?}");
+
+    console(&state, json!({ "clear": true })).await;
+    assert!(console(&state, json!({})).await.is_empty());
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn errors_are_reported_with_the_action() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("console.html").await;
+
+    let out = state.run("click", json!({ "selector": "#boom" })).await.expect("click");
+    assert!(out.contains("Page errors since this action"), "{out}");
+    assert!(out.contains("TypeError"), "{out}");
+
+    let out = state.run("click", json!({ "selector": "#complain" })).await.expect("click");
+    assert!(out.contains("Save failed:") && out.contains("42"), "{out}");
+
+    // Errors from earlier actions aren't repeated.
+    let out = state.run("click", json!({ "selector": "#quiet" })).await.expect("click");
+    assert!(!out.contains("Page errors"), "{out}");
+
+    let since = console(&state, json!({ "since_action": true })).await;
+    assert!(since.is_empty(), "{since:?}");
+
+    browser.close().await;
+}
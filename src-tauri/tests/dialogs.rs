@@ -0,0 +1,68 @@
+//! JavaScript dialogs: reported on the action that opens them, answered
+//! with `browser_dialog`, or handled by the default policy.
+
+mod common;
+
+use serde_json::json;
+use zeptobot_lib::tools::web_dialog::DialogPolicy;
+
+#[tokio::test]
+async fn an_open_dialog_is_reported_then_answered() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("dialogs.html").await;
+
+    let out = state.run("click", json!({ "selector": "#delete" })).await.expect("click");
+    assert!(out.contains("confirm dialog \"Delete 3 items?\""), "{out}");
+    assert!(out.contains("browser_dialog"), "{out}");
+
+    // The page is blocked: other page actions refuse to run.
+    let err = state.run("read", json!({ "selector": "#out" })).await.unwrap_err();
+    assert!(err.contains("answer it with browser_dialog first"), "{err}");
+
+    let out = state.run("dialog", json!({ "action": "dismiss" })).await.expect("dialog");
+    assert!(out.starts_with("Dismissed the confirm dialog"), "{out}");
+    let text = state.run("read", json!({ "selector": "#out" })).await.expect("read");
+    assert_eq!(text, "kept");
+
+    let err = state.run("dialog", json!({ "action": "accept" })).await.unwrap_err();
+    assert_eq!(err, "No dialog is open");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn prompts_take_text() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("dialogs.html").await;
+
+    let out = state.run("click", json!({ "selector": "#rename" })).await.expect("click");
+    assert!(out.contains("prompt dialog \"Your name?\" (default answer \"guest\")"), "{out}");
+
+    state
+        .run("dialog", json!({ "action": "accept", "prompt_text": "Ada" }))
+        .await
+        .expect("dialog");
+    let text = state.run("read", json!({ "selector": "#out" })).await.expect("read");
+    assert_eq!(text, "Hello, Ada");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn the_policy_answers_dialogs_automatically() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("dialogs.html").await;
+    state.set_dialog_policy(DialogPolicy::Accept).await;
+
+    let out = state.run("click", json!({ "selector": "#notice" })).await.expect("click");
+    assert!(out.contains("Dialog accepted automatically: alert dialog \"Saved\""), "{out}");
+    let text = state.run("read", json!({ "selector": "#out" })).await.expect("read");
+    assert_eq!(text, "acknowledged");
+
+    state.set_dialog_policy(DialogPolicy::Dismiss).await;
+    state.run("click", json!({ "selector": "#delete" })).await.expect("click");
+    let text = state.run("read", json!({ "selector": "#out" })).await.expect("read");
+    assert_eq!(text, "kept");
+
+    browser.close().await;
+}
@@ -0,0 +1,117 @@
+//! Structured extraction for `browser_extract`.
+
+mod common;
+
+use serde_json::{json, Value};
+use zeptobot_lib::tools::browser::extract_spec;
+
+async fn extract(state: &zeptobot_lib::tools::browser::BrowserState, args: Value) -> Value {
+    let spec = extract_spec(&args).expect("spec");
+    let raw = state
+        .run("extract", json!({ "spec": spec }))
+        .await
+        .expect("extract");
+    serde_json::from_str(&raw).expect("extract returns JSON")
+}
+
+#[tokio::test]
+async fn items_and_fields_are_typed() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("results.html").await;
+
+    let data = extract(
+        &state,
+        json!({
+            "items": "li.result",
+            "fields": {
+                "title": "h3",
+                "url": { "selector": "a", "type": "url" },
+                "price": { "selector": ".price", "type": "number" },
+                "compare": { "selector": ".compare", "type": "boolean" }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(data["count"], 3);
+    assert_eq!(
+        data["items"][0],
+        json!({
+            "title": "River Runner",
+            "url": "file:///p/river-runner",
+            "price": 1249.0,
+            "compare": true
+        })
+    );
+    assert_eq!(data["items"][1]["price"], 899.0);
+    assert_eq!(data["items"][1]["compare"], false);
+    assert_eq!(data["items"][2]["url"], "https://example.com/lake-lite");
+    assert_eq!(data["items"][2]["price"], Value::Null);
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn nested_schema_and_missing_fields() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("results.html").await;
+
+    let data = extract(
+        &state,
+        json!({
+            "schema": {
+                "type": "array",
+                "selector": "li.result",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string", "selector": "h3" },
+                        "tags": { "type": "array", "selector": ".tag", "items": { "type": "string" } },
+                        "rating": { "type": "number", "selector": ".stars" }
+                    }
+                }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(data["items"][0]["tags"], json!(["river", "touring"]));
+    assert_eq!(data["items"][2]["tags"], json!([]));
+    // `.stars` matches nothing anywhere — reported so the caller can fix it.
+    assert_eq!(data["missing"], json!(["rating"]));
+
+    let data = extract(&state, json!({ "items": "li.result", "fields": { "title": "h3" }, "limit": 1 })).await;
+    assert_eq!(data["items"], json!([{ "title": "River Runner" }]));
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn table_rows_keyed_by_header() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("results.html").await;
+
+    let data = extract(&state, json!({ "table": "#specs" })).await;
+    assert_eq!(
+        data["items"],
+        json!([
+            { "Model": "River Runner", "Length (cm)": 1120, "Weight": 21.5 },
+            { "Model": "Sea Scout", "Length (cm)": "Discontinued", "Weight": "Discontinued" }
+        ])
+    );
+
+    let err = state
+        .run("extract", json!({ "spec": { "table": "#nope" } }))
+        .await
+        .expect_err("no such table");
+    assert!(err.contains("No visible table"), "{err}");
+
+    browser.close().await;
+}
+
+#[test]
+fn spec_needs_a_source() {
+    assert!(extract_spec(&json!({})).is_err());
+    assert!(extract_spec(&json!({ "items": "li" })).is_err());
+    let spec = extract_spec(&json!({ "items": { "role": "listitem" }, "fields": { "name": "" } })).unwrap();
+    assert_eq!(spec["schema"]["role"], "listitem");
+    assert_eq!(spec["schema"]["items"]["properties"]["name"]["selector"], "");
+}
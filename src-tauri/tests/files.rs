@@ -0,0 +1,76 @@
+//! `browser_upload` and `browser_wait_for_download`, with files kept inside
+//! the configured directories.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::sandbox_home;
+use serde_json::{json, Value};
+use zeptobot_lib::tools::web_files::FilesConfig;
+
+#[test]
+fn uploads_are_limited_to_the_upload_directories() {
+    let home = sandbox_home();
+    let config = FilesConfig::load().expect("config");
+    let uploads = home.join("uploads").canonicalize().unwrap();
+
+    assert_eq!(config.upload_path("note.txt").unwrap(), uploads.join("note.txt"));
+    assert_eq!(config.upload_path("~/uploads/note.txt").unwrap(), uploads.join("note.txt"));
+
+    let err = config.upload_path("~/secret.txt").unwrap_err();
+    assert!(err.contains("outside the upload directories"), "{err}");
+    // No escaping through `..` either.
+    let err = config.upload_path("../secret.txt").unwrap_err();
+    assert!(err.contains("No file") || err.contains("outside"), "{err}");
+    assert!(config.upload_path("missing.pdf").unwrap_err().starts_with("No file"));
+
+    assert!(config.check_download(&home.join("downloads/a.pdf").to_string_lossy()).is_ok());
+    assert!(config.check_download(&home.join("a.pdf").to_string_lossy()).is_err());
+}
+
+#[tokio::test]
+async fn files_are_set_on_the_input_behind_a_label() {
+    let home = sandbox_home();
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("files.html").await;
+    let note = home.join("uploads/note.txt").to_string_lossy().into_owned();
+
+    let out = state
+        .run("upload", json!({ "selector": "#attach", "files": [note] }))
+        .await
+        .expect("upload");
+    assert_eq!(out, "Attached 1 file: note.txt");
+    let text = state.run("read", json!({ "selector": "#out" })).await.expect("read");
+    assert_eq!(text, "note.txt:5");
+
+    let err = state
+        .run("upload", json!({ "selector": "#plain", "files": [note] }))
+        .await
+        .unwrap_err();
+    assert!(err.contains("not a file input"), "{err}");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn downloads_are_saved_to_the_download_directory() {
+    let home = sandbox_home();
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("files.html").await;
+
+    state.run("click", json!({ "selector": "#report" })).await.expect("click");
+    let raw = state
+        .run("wait_for_download", json!({ "filename": "*.txt" }))
+        .await
+        .expect("download");
+    let download: Value = serde_json::from_str(&raw).expect("download JSON");
+    assert_eq!(download["state"], "completed");
+    assert_eq!(download["filename"], "report.txt");
+
+    let path = PathBuf::from(download["path"].as_str().unwrap());
+    assert!(path.starts_with(home.join("downloads")), "{path:?}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "quarterly numbers");
+
+    browser.close().await;
+}
@@ -0,0 +1,125 @@
+//! `fill` (the per-field step of browser_fill_form) against a local form.
+
+mod common;
+
+use serde_json::{json, Value};
+use zeptobot_lib::tools::browser::BrowserState;
+
+async fn js(state: &BrowserState, code: &str) -> String {
+    state
+        .run("execute_js", json!({ "code": code }))
+        .await
+        .unwrap_or_else(|e| panic!("{code}: {e}"))
+}
+
+async fn fill(state: &BrowserState, field: Value) -> Result<String, String> {
+    state.run("fill", field).await
+}
+
+#[tokio::test]
+async fn fills_every_field_kind() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+
+    let fields = [
+        json!({ "label": "Full name", "value": "Ada Lovelace" }),
+        json!({ "selector": "#email", "value": "ada@example.com" }),
+        json!({ "label": "Notes", "value": "line one\nline two" }),
+        json!({ "role": "textbox", "name": "Bio", "value": "Mathematician" }),
+        json!({ "label": "Country", "value": "Malaysia" }),
+        json!({ "label": "Languages", "value": ["en", "Chinese"] }),
+        json!({ "label": "Subscribe", "value": false }),
+        json!({ "label": "Accept terms", "value": true }),
+        json!({ "selector": "#size", "value": "Large" }),
+        json!({ "label": "Start date", "value": "2026-03-01" }),
+    ];
+    for field in fields {
+        fill(&state, field.clone())
+            .await
+            .unwrap_or_else(|e| panic!("{field}: {e}"));
+    }
+
+    assert_eq!(js(&state, "document.getElementById('name').value").await, "Ada Lovelace");
+    assert_eq!(js(&state, "document.getElementById('email').value").await, "ada@example.com");
+    assert_eq!(js(&state, "document.getElementById('notes').value").await, "line one\nline two");
+    assert_eq!(js(&state, "document.getElementById('bio').textContent").await, "Mathematician");
+    assert_eq!(js(&state, "document.getElementById('country').value").await, "my");
+    assert_eq!(
+        js(&state, "[...document.getElementById('langs').selectedOptions].map(o => o.value).join()").await,
+        "en,zh"
+    );
+    assert_eq!(js(&state, "document.getElementById('subscribe').checked").await, "false");
+    assert_eq!(js(&state, "document.getElementById('terms').checked").await, "true");
+    assert_eq!(js(&state, "document.querySelector('[name=size]:checked').value").await, "l");
+    assert_eq!(js(&state, "document.getElementById('start').value").await, "2026-03-01");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn radio_group_by_any_member_and_value() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+
+    // Any radio of the group stands for the whole group.
+    let done = fill(&state, json!({ "selector": "input[name=size]", "value": "s" }))
+        .await
+        .expect("radio by value");
+    assert!(done.contains("Small"), "{done}");
+    assert_eq!(js(&state, "document.querySelector('[name=size]:checked').value").await, "s");
+
+    let err = fill(&state, json!({ "selector": "#size", "value": "XL" }))
+        .await
+        .expect_err("unknown radio option");
+    assert!(err.contains("No radio option 'XL'") && err.contains("Medium"), "{err}");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn reports_bad_values_per_field() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+
+    let err = fill(&state, json!({ "label": "Country", "value": "France" }))
+        .await
+        .expect_err("unknown option");
+    assert!(err.contains("No option 'France'") && err.contains("Singapore"), "{err}");
+
+    let err = fill(&state, json!({ "label": "Start date", "value": "March 1st" }))
+        .await
+        .expect_err("unparseable date");
+    assert!(err.contains("YYYY-MM-DD"), "{err}");
+
+    let err = fill(&state, json!({ "selector": "#locked", "value": "x" }))
+        .await
+        .expect_err("disabled field");
+    assert!(err.contains("disabled"), "{err}");
+
+    let err = fill(&state, json!({ "label": "Accept terms", "value": "maybe" }))
+        .await
+        .expect_err("not a boolean");
+    assert!(err.contains("true or false"), "{err}");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn submit_sends_the_fields_form() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+
+    fill(&state, json!({ "label": "Full name", "value": "Ada" })).await.expect("name");
+    let done = fill(&state, json!({ "label": "Email", "value": "ada@example.com", "submit": true }))
+        .await
+        .expect("fill and submit");
+    assert!(done.contains("submitted"), "{done}");
+
+    let out: Value = serde_json::from_str(&js(&state, "document.getElementById('out').textContent").await)
+        .expect("submitted form data");
+    assert_eq!(out["name"], "Ada");
+    assert_eq!(out["email"], "ada@example.com");
+    assert_eq!(out["size"], "m");
+
+    browser.close().await;
+}
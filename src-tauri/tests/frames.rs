@@ -0,0 +1,97 @@
+//! Frame targeting and shadow-piercing selectors on the CDP backend.
+
+mod common;
+
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn clicks_inside_iframe_by_name() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("frames.html").await;
+
+    // A real mouse click: lands only if the frame offset (margin, border,
+    // padding) is added to the element's position.
+    state
+        .run("click", json!({ "selector": "#inside", "frame": "inner" }))
+        .await
+        .expect("click in frame");
+    let inner = state
+        .run("read", json!({ "selector": "#log", "frame": "inner" }))
+        .await
+        .expect("read in frame");
+    assert_eq!(inner, "inside");
+    let top = state.run("read", json!({ "selector": "#log" })).await.expect("read top");
+    assert_eq!(top, "");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn list_elements_covers_frames_and_shadow_roots() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("frames.html").await;
+
+    let raw = state.run("list_elements", json!({})).await.expect("list");
+    let elements: Vec<Value> = serde_json::from_str(&raw).expect("json");
+    let find = |text: &str| {
+        elements
+            .iter()
+            .find(|el| el["text"] == text)
+            .unwrap_or_else(|| panic!("{text} not listed in {raw}"))
+            .clone()
+    };
+    assert!(find("Top").get("frame").is_none());
+    assert_eq!(find("Save")["shadow"], "save-bar");
+    let inside = find("Inside");
+    assert_eq!(inside["frame"], 1);
+
+    // Refs from a frame work when the frame is passed along.
+    state
+        .run("click", json!({ "ref": inside["ref"], "frame": "1" }))
+        .await
+        .expect("click ref in frame");
+    state
+        .run("type", json!({ "placeholder": "Inner field", "value": "hello", "frame": "1" }))
+        .await
+        .expect("type in frame");
+    let value = state
+        .run("execute_js", json!({ "code": "document.getElementById('field').value", "frame": "1" }))
+        .await
+        .expect("js in frame");
+    assert_eq!(value, "hello");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn selectors_pierce_shadow_roots() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("frames.html").await;
+
+    state
+        .run("click", json!({ "selector": "save-bar >>> button.save" }))
+        .await
+        .expect("click in shadow root");
+    let state_text = state
+        .run("read", json!({ "selector": "save-bar >>> #state" }))
+        .await
+        .expect("read in shadow root");
+    assert_eq!(state_text, "saved");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn unknown_frame_lists_frames() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("frames.html").await;
+
+    let err = state
+        .run("click", json!({ "selector": "#inside", "frame": "nope" }))
+        .await
+        .expect_err("no such frame");
+    assert!(err.contains("No frame matches 'nope'"), "{err}");
+    assert!(err.contains("inner"), "{err}");
+
+    browser.close().await;
+}
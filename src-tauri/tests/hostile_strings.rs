@@ -0,0 +1,126 @@
+//! Hostile strings passed through the CDP backend must stay data: they
+//! round-trip exactly and never run as script.
+
+mod common;
+
+use serde_json::json;
+use zeptobot_lib::tools::browser::BrowserState;
+
+/// Values that broke (or could break) JS built with string interpolation.
+const HOSTILE: &[&str] = &[
+    "'",
+    "\"",
+    "\\",
+    "\\'",
+    "\\\\'",
+    "`${window.__pwned = 1}`",
+    "'); window.__pwned = 1; ('",
+    "\"); window.__pwned = 1; (\"",
+    "</script><script>window.__pwned = 1</script>",
+    "<img src=x onerror=\"window.__pwned = 1\">",
+    "line one\nline two\r\n\ttabbed",
+    "\u{2028}\u{2029}",
+    "héllo wörld 🌍",
+];
+
+/// Selectors crafted to escape a quoted `querySelector('...')` call.
+const HOSTILE_SELECTORS: &[&str] = &[
+    "#x'); window.__pwned = 1; ('",
+    "#x\"); window.__pwned = 1; (\"",
+    "#x\\'); window.__pwned = 1; //",
+    "</script><script>window.__pwned = 1</script>",
+];
+
+async fn pwned(state: &BrowserState) -> bool {
+    let out = state
+        .run("execute_js", json!({ "code": "String(window.__pwned)" }))
+        .await
+        .expect("execute_js");
+    out != "undefined"
+}
+
+async fn area_value(state: &BrowserState) -> String {
+    state
+        .run("execute_js", json!({ "code": "document.getElementById('area').value" }))
+        .await
+        .expect("read value")
+}
+
+#[tokio::test]
+async fn typed_values_round_trip() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("hostile.html").await;
+
+    for value in HOSTILE {
+        // No selector: the focused-element path sets the value from JS.
+        state.run("click", json!({ "selector": "#area" })).await.expect("focus");
+        state
+            .run("type", json!({ "value": value }))
+            .await
+            .unwrap_or_else(|e| panic!("type {value:?}: {e}"));
+        // textarea normalises CRLF to LF.
+        assert_eq!(area_value(&state).await, value.replace("\r\n", "\n"), "focused path");
+
+        state
+            .run("type", json!({ "selector": "#area", "value": value, "trusted": true }))
+            .await
+            .unwrap_or_else(|e| panic!("trusted type {value:?}: {e}"));
+        assert_eq!(area_value(&state).await, value.replace("\r\n", "\n"), "trusted path");
+    }
+    assert!(!pwned(&state).await);
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn hostile_selectors_are_not_injected() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("hostile.html").await;
+
+    for sel in HOSTILE_SELECTORS {
+        // Invalid selectors may error; they must never execute.
+        let _ = state.run("read", json!({ "selector": sel })).await;
+        let _ = state.run("wait_for", json!({ "selector": sel, "timeout_ms": 0 })).await;
+        let _ = state.run("type", json!({ "selector": sel, "value": "x", "timeout_ms": 0 })).await;
+        assert!(!pwned(&state).await, "selector {sel:?} ran as script");
+    }
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn hostile_text_is_found_verbatim() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("hostile.html").await;
+
+    let text = r#"It's a "quoted" \ back\slash </script> ${line}"#;
+    let found = state
+        .run("wait_for", json!({ "text": text, "timeout_ms": 1000 }))
+        .await
+        .expect("wait_for hostile text");
+    assert!(found.contains(text), "{found}");
+
+    let read = state.run("read", json!({ "selector": "#quote" })).await.unwrap();
+    assert_eq!(read, text);
+
+    for filter in HOSTILE {
+        state
+            .run("list_elements", json!({ "filter": filter }))
+            .await
+            .unwrap_or_else(|e| panic!("list_elements {filter:?}: {e}"));
+    }
+    assert!(!pwned(&state).await);
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn locator_names_with_quotes_match() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("hostile.html").await;
+
+    state
+        .run("click", json!({ "role": "button", "name": r#"Say "hi" & 'bye'"#, "exact": true }))
+        .await
+        .expect("click by quoted name");
+    let out = state.run("read", json!({ "selector": "#out" })).await.unwrap();
+    assert_eq!(out, "clicked");
+    browser.close().await;
+}
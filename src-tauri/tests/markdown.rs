@@ -0,0 +1,74 @@
+//! Markdown extraction for `browser_read format='markdown'`.
+
+mod common;
+
+use serde_json::json;
+
+#[tokio::test]
+async fn main_content_keeps_structure() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("article.html").await;
+
+    let md = state
+        .run("read", json!({ "format": "markdown" }))
+        .await
+        .expect("markdown");
+    for expected in [
+        "Title: Tide Tables",
+        "# Reading tide tables",
+        "## Steps",
+        "**twice a day**",
+        "[official tables](https://example.com/tides)",
+        "1. Find your harbour",
+        "2. Note the *high water* time",
+        "  - Adjust for daylight saving",
+        "| Time | Height |\n| --- | --- |\n| 06:12 | 3.4 m |",
+        "> Time and tide wait for no one.",
+        "```\ntide --harbour dover\n```",
+    ] {
+        assert!(md.contains(expected), "missing {expected:?} in:\n{md}");
+    }
+    for chrome in ["Menu A", "Sailing knots", "Harbour Office", "Hidden paragraph"] {
+        assert!(!md.contains(chrome), "{chrome:?} should be skipped:\n{md}");
+    }
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn whole_page_and_single_element() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("article.html").await;
+
+    let md = state
+        .run("read", json!({ "format": "markdown", "main_content": false }))
+        .await
+        .expect("whole page");
+    assert!(md.contains("[Menu A]("), "{md}");
+    assert!(md.contains("Harbour Office"), "{md}");
+
+    let md = state
+        .run("read", json!({ "format": "markdown", "selector": "table" }))
+        .await
+        .expect("table only");
+    assert!(md.starts_with("| Time | Height |"), "{md}");
+    assert!(!md.contains("Title:"), "{md}");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn page_text_is_not_truncated() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("article.html").await;
+
+    // Paging happens in browser_read; the backend returns everything.
+    state
+        .run("execute_js", json!({ "code": "document.getElementById('long').textContent = 'x'.repeat(20000) + 'END'" }))
+        .await
+        .expect("grow page");
+    let text = state.run("read", json!({ "page_text": true })).await.expect("read");
+    assert!(text.contains("END"), "text was cut at {} chars", text.len());
+
+    browser.close().await;
+}
@@ -0,0 +1,111 @@
+//! Network tracking: `wait_until`, `network_idle` and `wait_for_request`.
+//!
+//! `file://` pages can't fetch, so these tests serve `fixtures/network.html`
+//! and its slow JSON API over HTTP (`common::serve_fixtures`).
+
+mod common;
+
+use common::Route;
+use serde_json::{json, Value};
+
+/// Both APIs answer slowly, like a real backend.
+const SLOW_API: &[Route] = &[
+    Route { path: "api/items.json", delay_ms: 800, headers: &[] },
+    Route { path: "api/save.json", delay_ms: 300, headers: &[] },
+];
+
+/// Serve the fixtures with [`SLOW_API`]; returns the URL of `network.html`.
+async fn serve() -> String {
+    format!("{}/network.html", common::serve_fixtures(SLOW_API).await)
+}
+
+#[tokio::test]
+async fn networkidle_waits_for_late_fetches() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+    let base = serve().await;
+
+    state
+        .run("navigate", json!({ "url": base, "wait_until": "networkidle" }))
+        .await
+        .expect("navigate");
+    // The list is rendered from /api/items.json, which answers 800ms after load.
+    let items = state.run("read", json!({ "selector": "#items" })).await.expect("read");
+    assert!(items.contains("Kayak"), "{items}");
+
+    state
+        .run("navigate", json!({ "url": base, "wait_until": "sometime" }))
+        .await
+        .expect_err("unknown wait_until");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn wait_for_request_captures_json_body() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+    let base = serve().await;
+    state
+        .run("navigate", json!({ "url": base, "wait_until": "networkidle" }))
+        .await
+        .expect("navigate");
+
+    state.run("click", json!({ "selector": "#save" })).await.expect("click");
+    let raw = state
+        .run(
+            "wait_for_request",
+            json!({ "url": "*/api/save.json", "method": "POST", "capture_body": true }),
+        )
+        .await
+        .expect("request seen");
+    let request: Value = serde_json::from_str(&raw).expect("json");
+    assert_eq!(request["status"], 200);
+    assert_eq!(request["mime_type"], "application/json");
+    let body: Value = serde_json::from_str(request["body"].as_str().expect("body")).expect("json body");
+    assert_eq!(body, json!({ "status": "saved", "id": 7 }));
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn only_requests_after_the_last_action_match() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+    let base = serve().await;
+    state
+        .run("navigate", json!({ "url": base, "wait_until": "networkidle" }))
+        .await
+        .expect("navigate");
+
+    // /api/items.json belongs to the navigation, not to this click.
+    state.run("click", json!({ "selector": "#noop" })).await.expect("click");
+    let err = state
+        .run("wait_for_request", json!({ "url": "/api/items", "timeout_ms": 500 }))
+        .await
+        .expect_err("stale request must not match");
+    assert!(err.contains("Latest requests"), "{err}");
+    assert!(err.contains("/api/items.json → 200"), "{err}");
+
+    browser.close().await;
+}
+
+#[tokio::test]
+async fn wait_for_network_idle_after_click() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+    let base = serve().await;
+    state
+        .run("navigate", json!({ "url": base, "wait_until": "networkidle" }))
+        .await
+        .expect("navigate");
+
+    state.run("click", json!({ "selector": "#save" })).await.expect("click");
+    let done = state
+        .run("wait_for", json!({ "network_idle": true, "text": "saved" }))
+        .await
+        .expect("idle");
+    assert!(done.contains("saved"), "{done}");
+
+    browser.close().await;
+}
@@ -0,0 +1,37 @@
+//! `browser_screenshot` captures: viewport, full page and element clips.
+
+mod common;
+
+use base64::Engine;
+use serde_json::{json, Value};
+use zeptobot_lib::tools::browser::BrowserState;
+
+/// Width and height of the PNG a screenshot action returns.
+async fn capture(state: &BrowserState, args: Value) -> (u32, u32) {
+    let raw = state.run("screenshot", args).await.expect("screenshot");
+    let shot: Value = serde_json::from_str(&raw).expect("screenshot JSON");
+    let png = base64::engine::general_purpose::STANDARD
+        .decode(shot["data"].as_str().expect("data"))
+        .expect("base64");
+    image::load_from_memory(&png).expect("PNG").to_rgba8().dimensions()
+}
+
+#[tokio::test]
+async fn viewport_full_page_and_element() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("screenshot.html").await;
+
+    let (_, viewport_height) = capture(&state, json!({})).await;
+    assert!(viewport_height < 3000, "{viewport_height}");
+
+    let (_, full_height) = capture(&state, json!({ "full_page": true })).await;
+    assert_eq!(full_height, 3000);
+
+    // Below the fold, so the clip has to follow the scroll.
+    assert_eq!(capture(&state, json!({ "selector": "#badge" })).await, (120, 40));
+
+    let err = state.run("screenshot", json!({ "selector": "#empty" })).await.unwrap_err();
+    assert!(err.contains("has no size"), "{err}");
+
+    browser.close().await;
+}
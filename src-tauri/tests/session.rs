@@ -0,0 +1,130 @@
+//! Cookies, web storage and session bundles, with values limited to the
+//! allowed domains.
+//!
+//! Cookies need an http origin, so the fixture is served over HTTP
+//! (`common::serve_fixtures`) with a session cookie and a persistent one.
+
+mod common;
+
+use common::Route;
+use serde_json::{json, Value};
+use zeptobot_lib::tools::web_session::{self, SessionConfig};
+
+/// `session.html` sets a session cookie and a persistent one.
+const COOKIES: &[Route] = &[Route {
+    path: "session.html",
+    delay_ms: 0,
+    headers: &[
+        "Set-Cookie: sid=abc123; Path=/; HttpOnly",
+        "Set-Cookie: prefs=compact; Path=/; Max-Age=3600",
+    ],
+}];
+
+fn names(found: &Value) -> Vec<&str> {
+    let mut names: Vec<&str> = found["cookies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|c| c["name"].as_str())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn values_are_limited_to_allowed_domains() {
+    let config = SessionConfig { allowed_domains: vec!["github.com".into()] };
+    assert!(config.allows("github.com"));
+    assert!(config.allows(".github.com"));
+    assert!(config.allows("gist.GitHub.com"));
+    assert!(!config.allows("evilgithub.com"));
+    assert!(!config.allows("google.com"));
+    assert!(!SessionConfig::default().allows("github.com"));
+
+    assert_eq!(web_session::host_of("https://mail.google.com:443/u/0?x=1").as_deref(), Some("mail.google.com"));
+    assert_eq!(web_session::host_of("file:///tmp/a.html"), None);
+
+    // A site receives its parent domain's cookies too.
+    let cookie = json!({ "name": "SID", "domain": ".google.com" });
+    assert!(web_session::cookie_matches(&cookie, "mail.google.com", None));
+    assert!(web_session::cookie_matches(&cookie, "google.com", Some("SID")));
+    assert!(!web_session::cookie_matches(&cookie, "google.com", Some("HSID")));
+    assert!(!web_session::cookie_matches(&cookie, "notgoogle.com", None));
+
+    let bundle = json!({
+        "saved_at": 1,
+        "cookies": [
+            { "name": "user_session", "domain": ".github.com" },
+            { "name": "SID", "domain": ".google.com" },
+        ],
+        "local_storage": {
+            "https://github.com": { "theme": "dark" },
+            "https://mail.google.com": { "draft": "secret" },
+        },
+    });
+    let kept = web_session::filter_bundle(&bundle, &config, &[]);
+    assert_eq!(kept["cookies"].as_array().unwrap().len(), 1);
+    assert_eq!(kept["cookies"][0]["name"], "user_session");
+    assert_eq!(kept["local_storage"], json!({ "https://github.com": { "theme": "dark" } }));
+    let none = web_session::filter_bundle(&bundle, &config, &["gitlab.com".into()]);
+    assert_eq!(web_session::describe_bundle(&none), "0 cookies");
+
+    assert!(web_session::bundle_path("work-2").is_ok());
+    assert!(web_session::bundle_path("../evil").is_err());
+}
+
+#[tokio::test]
+async fn cookies_and_storage_round_trip_through_a_bundle() {
+    let Some(browser) = common::launch().await else { return };
+    let state = browser.cdp_state("forms.html").await;
+    let base = common::serve_fixtures(COOKIES).await;
+    state
+        .run("navigate", json!({ "url": format!("{base}/session.html") }))
+        .await
+        .expect("navigate");
+
+    let raw = state.run("cookies", json!({})).await.expect("cookies");
+    let found: Value = serde_json::from_str(&raw).expect("cookies JSON");
+    assert_eq!(found["domain"], "127.0.0.1");
+    assert_eq!(names(&found), ["prefs", "sid"]);
+    let sid = found["cookies"].as_array().unwrap().iter().find(|c| c["name"] == "sid").unwrap();
+    assert_eq!(sid["value"], "abc123");
+    assert_eq!(sid["http_only"], true);
+    assert!(sid["expires"].is_null(), "{sid}");
+
+    let raw = state.run("storage", json!({})).await.expect("storage");
+    let local: Value = serde_json::from_str(&raw).expect("storage JSON");
+    assert_eq!(local["origin"], base);
+    assert_eq!(local["items"], json!({ "theme": "dark" }));
+    let raw = state.run("storage", json!({ "area": "session" })).await.expect("session storage");
+    assert!(raw.contains("Hello there"), "{raw}");
+
+    let raw = state.run("export_session", json!({})).await.expect("export");
+    let bundle: Value = serde_json::from_str(&raw).expect("bundle JSON");
+    let config = SessionConfig { allowed_domains: vec!["127.0.0.1".into()] };
+    let bundle = web_session::filter_bundle(&bundle, &config, &[]);
+    assert_eq!(bundle["local_storage"][&base], json!({ "theme": "dark" }));
+
+    // Log out: clear the cookies and storage…
+    let raw = state.run("cookies", json!({ "clear": true })).await.expect("clear cookies");
+    assert_eq!(names(&serde_json::from_str(&raw).unwrap()), ["prefs", "sid"]);
+    let raw = state.run("cookies", json!({})).await.expect("cookies after clear");
+    assert!(names(&serde_json::from_str(&raw).unwrap()).is_empty(), "{raw}");
+    let raw = state.run("storage", json!({ "clear": true })).await.expect("clear storage");
+    assert!(raw.contains("\"cleared\":1"), "{raw}");
+
+    // …and log back in from the bundle.
+    let raw = state
+        .run("import_session", json!({ "bundle": bundle }))
+        .await
+        .expect("import");
+    let imported: Value = serde_json::from_str(&raw).expect("import JSON");
+    assert_eq!(imported["cookies"], 2);
+    assert_eq!(imported["not_open"], json!([]));
+    let raw = state.run("cookies", json!({ "name": "sid" })).await.expect("cookies after import");
+    assert_eq!(names(&serde_json::from_str(&raw).unwrap()), ["sid"]);
+    let raw = state.run("storage", json!({ "key": "theme" })).await.expect("storage after import");
+    assert!(raw.contains("dark"), "{raw}");
+
+    browser.close().await;
+}